@@ -1,6 +1,11 @@
 use core::future::Future;
 
-pub use embedded_batteries::charger::{Error, ErrorKind, ErrorType};
+use crate::smart_battery::{CapacityModeValue, SmartBattery};
+
+pub use embedded_batteries::charger::{
+    amps, amps_milli, centiamps, centivolts, volts, volts_milli, ChargeCommand, ChargerBatteryError, ChargingProfile,
+    Error, ErrorKind, ErrorType, InhibitReason, InputSource, Setpoint,
+};
 pub use embedded_batteries::{MilliAmps, MilliVolts};
 
 /// Asynchronous Smart Battery Charger methods
@@ -22,6 +27,196 @@ pub trait Charger: ErrorType {
     /// charge. The Smart Battery can effectively turn off the Smart Battery Charger by returning a value of 0 for
     /// this function.
     fn charging_voltage(&mut self, voltage: MilliVolts) -> impl Future<Output = Result<MilliVolts, Self::Error>>;
+
+    /// Asynchronously returns the highest charging current the charger can deliver, so callers can clamp
+    /// setpoints before commanding them.
+    ///
+    /// Chargers that don't report their ceiling return `MilliAmps::MAX`.
+    fn max_charging_current(&mut self) -> impl Future<Output = Result<MilliAmps, Self::Error>> {
+        async { Ok(MilliAmps::MAX) }
+    }
+
+    /// Asynchronously returns the highest charging voltage the charger can deliver, so callers can clamp
+    /// setpoints before commanding them.
+    ///
+    /// Chargers that don't report their ceiling return `MilliVolts::MAX`.
+    fn max_charging_voltage(&mut self) -> impl Future<Output = Result<MilliVolts, Self::Error>> {
+        async { Ok(MilliVolts::MAX) }
+    }
+
+    /// Asynchronously returns the type of the input power source currently feeding the charger, for use in
+    /// power policy decisions such as input current limits.
+    ///
+    /// Chargers that don't report their input source return `InputSource::Other`.
+    fn input_source(&mut self) -> impl Future<Output = Result<InputSource, Self::Error>> {
+        async { Ok(InputSource::Other) }
+    }
+
+    /// Asynchronously receives a ChargingCurrent() (0x14) broadcast from the Smart Battery.
+    ///
+    /// In SBS, the battery acts as an SMBus master and periodically broadcasts its desired charging current to
+    /// the charger. This models that battery-to-charger path, as opposed to the host setting the current with
+    /// `charging_current()`. The default forwards to `charging_current()`, discarding the acknowledged value.
+    fn receive_charging_current(&mut self, current: MilliAmps) -> impl Future<Output = Result<(), Self::Error>> {
+        async move { self.charging_current(current).await.map(|_| ()) }
+    }
+
+    /// Asynchronously receives a ChargingVoltage() (0x15) broadcast from the Smart Battery.
+    ///
+    /// In SBS, the battery acts as an SMBus master and periodically broadcasts its desired charging voltage to
+    /// the charger. This models that battery-to-charger path, as opposed to the host setting the voltage with
+    /// `charging_voltage()`. The default forwards to `charging_voltage()`, discarding the acknowledged value.
+    fn receive_charging_voltage(&mut self, voltage: MilliVolts) -> impl Future<Output = Result<(), Self::Error>> {
+        async move { self.charging_voltage(voltage).await.map(|_| ()) }
+    }
+
+    /// Asynchronously sets the charging current to a C-rate fraction of the battery's full charge capacity,
+    /// returning the current acknowledged by the charger.
+    ///
+    /// `c_rate_milli` is the C-rate in thousandths (e.g. 500 for 0.5C), so the requested current is
+    /// `full_charge_capacity() * c_rate_milli / 1000` mA, clamped to `max_charging_current()`. The battery must
+    /// report capacities in mAh (CAPACITY_MODE bit clear), otherwise `ChargerBatteryError::CapacityMode` is
+    /// returned.
+    fn set_charge_c_rate<B: SmartBattery>(
+        &mut self,
+        batt: &mut B,
+        c_rate_milli: u16,
+    ) -> impl Future<Output = Result<MilliAmps, ChargerBatteryError<Self::Error, B::Error>>> {
+        async move {
+            let capacity = match batt
+                .full_charge_capacity()
+                .await
+                .map_err(ChargerBatteryError::Battery)?
+            {
+                CapacityModeValue::MilliAmpUnsigned(capacity) => capacity,
+                CapacityModeValue::CentiWattUnsigned(_) => return Err(ChargerBatteryError::CapacityMode),
+            };
+            let current = u32::from(capacity) * u32::from(c_rate_milli) / 1000;
+            let max_current = self
+                .max_charging_current()
+                .await
+                .map_err(ChargerBatteryError::Charger)?;
+            let current = MilliAmps::try_from(current).unwrap_or(MilliAmps::MAX).min(max_current);
+            self.charging_current(current)
+                .await
+                .map_err(ChargerBatteryError::Charger)
+        }
+    }
+
+    /// Asynchronously sets the charging current from a `ChargeCommand`, returning the command acknowledged by the
+    /// charger.
+    ///
+    /// `ChargeCommand::Off` is sent as a charging current of 0, and an acknowledged current of 0 is returned as
+    /// `ChargeCommand::Off`.
+    fn set_charge(&mut self, command: ChargeCommand) -> impl Future<Output = Result<ChargeCommand, Self::Error>> {
+        async move { self.charging_current(command.into()).await.map(ChargeCommand::from) }
+    }
+
+    /// Asynchronously returns `true` if the charger is currently refusing to charge (e.g. thermal, watchdog or
+    /// input fault).
+    ///
+    /// An inhibited charger may still acknowledge charging setpoints, typically as 0. The default reports
+    /// whether `charge_inhibit_reason()` returns a reason.
+    fn charge_inhibited(&mut self) -> impl Future<Output = Result<bool, Self::Error>> {
+        async move { Ok(self.charge_inhibit_reason().await?.is_some()) }
+    }
+
+    /// Asynchronously returns why the charger is currently refusing to charge, or `None` if charging is
+    /// allowed.
+    ///
+    /// Chargers that can't report inhibition return `None`.
+    fn charge_inhibit_reason(&mut self) -> impl Future<Output = Result<Option<InhibitReason>, Self::Error>> {
+        async { Ok(None) }
+    }
+
+    /// Asynchronously returns the current the charger is presently delivering to the battery in mA, or `None` if
+    /// the charger can't measure its output.
+    ///
+    /// Unlike the setpoint acknowledged by `charging_current()`, this is the measured output current, which stays 0
+    /// while the charger is inhibited or has no input power. Chargers without an output current monitor return
+    /// `None`.
+    fn delivered_current(&mut self) -> impl Future<Output = Result<Option<MilliAmps>, Self::Error>> {
+        async { Ok(None) }
+    }
+
+    /// Asynchronously sets the charging current like `charging_current()`, returning both the requested and the
+    /// acknowledged value so callers can detect clamping.
+    fn apply_charging_current(
+        &mut self,
+        current: MilliAmps,
+    ) -> impl Future<Output = Result<Setpoint<MilliAmps>, Self::Error>> {
+        async move {
+            Ok(Setpoint {
+                requested: current,
+                acknowledged: self.charging_current(current).await?,
+            })
+        }
+    }
+
+    /// Asynchronously sets the charging voltage like `charging_voltage()`, returning both the requested and the
+    /// acknowledged value so callers can detect clamping.
+    fn apply_charging_voltage(
+        &mut self,
+        voltage: MilliVolts,
+    ) -> impl Future<Output = Result<Setpoint<MilliVolts>, Self::Error>> {
+        async move {
+            Ok(Setpoint {
+                requested: voltage,
+                acknowledged: self.charging_voltage(voltage).await?,
+            })
+        }
+    }
+
+    /// Asynchronously returns an error of kind `ErrorKind::ChargeInhibited` if `charge_inhibited()` reports
+    /// that the charger is refusing to charge, turning a silently acknowledged 0 into an actionable error.
+    fn ensure_charging_allowed(&mut self) -> impl Future<Output = Result<(), Self::Error>>
+    where
+        Self::Error: From<ErrorKind>,
+    {
+        async {
+            if self.charge_inhibited().await? {
+                Err(ErrorKind::ChargeInhibited.into())
+            } else {
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Asynchronously applies the Smart Battery's charging request to the charger, following the SBS charging
+/// algorithm, and returns the negotiated profile.
+///
+/// The battery's desired ChargingVoltage() (0x15) and ChargingCurrent() (0x14) are read and written to the
+/// charger, voltage first so the current is never applied against a stale voltage limit. If the battery
+/// requests a current of 0, charging is stopped by writing a current of 0 and the voltage is left untouched.
+/// Hosts should call this periodically, since the battery updates its request as it charges.
+pub async fn negotiate_charge<B: SmartBattery, C: Charger>(
+    batt: &mut B,
+    charger: &mut C,
+) -> Result<ChargingProfile, ChargerBatteryError<C::Error, B::Error>> {
+    let current = batt.charging_current().await.map_err(ChargerBatteryError::Battery)?;
+    if current == 0 {
+        return Ok(ChargingProfile {
+            current: charger
+                .apply_charging_current(0)
+                .await
+                .map_err(ChargerBatteryError::Charger)?,
+            voltage: None,
+        });
+    }
+    let voltage = batt.charging_voltage().await.map_err(ChargerBatteryError::Battery)?;
+    let voltage = charger
+        .apply_charging_voltage(voltage)
+        .await
+        .map_err(ChargerBatteryError::Charger)?;
+    let current = charger
+        .apply_charging_current(current)
+        .await
+        .map_err(ChargerBatteryError::Charger)?;
+    Ok(ChargingProfile {
+        current,
+        voltage: Some(voltage),
+    })
 }
 
 impl<T: Charger + ?Sized> Charger for &mut T {
@@ -34,4 +229,180 @@ impl<T: Charger + ?Sized> Charger for &mut T {
     async fn charging_voltage(&mut self, voltage: MilliVolts) -> Result<MilliVolts, Self::Error> {
         T::charging_voltage(self, voltage).await
     }
+
+    #[inline]
+    async fn max_charging_current(&mut self) -> Result<MilliAmps, Self::Error> {
+        T::max_charging_current(self).await
+    }
+
+    #[inline]
+    async fn max_charging_voltage(&mut self) -> Result<MilliVolts, Self::Error> {
+        T::max_charging_voltage(self).await
+    }
+
+    #[inline]
+    async fn input_source(&mut self) -> Result<InputSource, Self::Error> {
+        T::input_source(self).await
+    }
+
+    #[inline]
+    async fn receive_charging_current(&mut self, current: MilliAmps) -> Result<(), Self::Error> {
+        T::receive_charging_current(self, current).await
+    }
+
+    #[inline]
+    async fn receive_charging_voltage(&mut self, voltage: MilliVolts) -> Result<(), Self::Error> {
+        T::receive_charging_voltage(self, voltage).await
+    }
+
+    #[inline]
+    async fn charge_inhibited(&mut self) -> Result<bool, Self::Error> {
+        T::charge_inhibited(self).await
+    }
+
+    #[inline]
+    async fn charge_inhibit_reason(&mut self) -> Result<Option<InhibitReason>, Self::Error> {
+        T::charge_inhibit_reason(self).await
+    }
+
+    #[inline]
+    async fn delivered_current(&mut self) -> Result<Option<MilliAmps>, Self::Error> {
+        T::delivered_current(self).await
+    }
+}
+
+/// Charger adapter that records the last requested and acknowledged charging current and voltage.
+///
+/// Since `charging_current()` and `charging_voltage()` return the value acknowledged by the charger,
+/// comparing it against the value that was requested helps diagnose chargers that silently clamp
+/// their setpoints during negotiation.
+#[derive(Debug, Clone)]
+pub struct ChargerHistory<C> {
+    charger: C,
+    current: Option<(MilliAmps, MilliAmps)>,
+    voltage: Option<(MilliVolts, MilliVolts)>,
+}
+
+impl<C> ChargerHistory<C> {
+    /// Wrap a charger, starting with an empty history.
+    pub const fn new(charger: C) -> Self {
+        Self {
+            charger,
+            current: None,
+            voltage: None,
+        }
+    }
+
+    /// Returns the last `(requested, acknowledged)` charging current pair,
+    /// or `None` if no charging current has been successfully set yet.
+    pub const fn last_current(&self) -> Option<(MilliAmps, MilliAmps)> {
+        self.current
+    }
+
+    /// Returns the last `(requested, acknowledged)` charging voltage pair,
+    /// or `None` if no charging voltage has been successfully set yet.
+    pub const fn last_voltage(&self) -> Option<(MilliVolts, MilliVolts)> {
+        self.voltage
+    }
+
+    /// Returns a reference to the wrapped charger.
+    pub const fn inner(&self) -> &C {
+        &self.charger
+    }
+
+    /// Returns a mutable reference to the wrapped charger.
+    ///
+    /// Setpoints changed directly through the wrapped charger are not recorded.
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.charger
+    }
+
+    /// Consumes the adapter, returning the wrapped charger.
+    pub fn into_inner(self) -> C {
+        self.charger
+    }
+}
+
+impl<C: ErrorType> ErrorType for ChargerHistory<C> {
+    type Error = C::Error;
+}
+
+impl<C: Charger> Charger for ChargerHistory<C> {
+    async fn charging_current(&mut self, current: MilliAmps) -> Result<MilliAmps, Self::Error> {
+        let acknowledged = self.charger.charging_current(current).await?;
+        self.current = Some((current, acknowledged));
+        Ok(acknowledged)
+    }
+
+    async fn charging_voltage(&mut self, voltage: MilliVolts) -> Result<MilliVolts, Self::Error> {
+        let acknowledged = self.charger.charging_voltage(voltage).await?;
+        self.voltage = Some((voltage, acknowledged));
+        Ok(acknowledged)
+    }
+
+    async fn max_charging_current(&mut self) -> Result<MilliAmps, Self::Error> {
+        self.charger.max_charging_current().await
+    }
+
+    async fn max_charging_voltage(&mut self) -> Result<MilliVolts, Self::Error> {
+        self.charger.max_charging_voltage().await
+    }
+
+    async fn input_source(&mut self) -> Result<InputSource, Self::Error> {
+        self.charger.input_source().await
+    }
+
+    async fn receive_charging_current(&mut self, current: MilliAmps) -> Result<(), Self::Error> {
+        self.charger.receive_charging_current(current).await
+    }
+
+    async fn receive_charging_voltage(&mut self, voltage: MilliVolts) -> Result<(), Self::Error> {
+        self.charger.receive_charging_voltage(voltage).await
+    }
+
+    async fn charge_inhibited(&mut self) -> Result<bool, Self::Error> {
+        self.charger.charge_inhibited().await
+    }
+
+    async fn charge_inhibit_reason(&mut self) -> Result<Option<InhibitReason>, Self::Error> {
+        self.charger.charge_inhibit_reason().await
+    }
+
+    async fn delivered_current(&mut self) -> Result<Option<MilliAmps>, Self::Error> {
+        self.charger.delivered_current().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{block_on, MockCharger};
+
+    #[test]
+    fn history_records_requested_and_acknowledged() {
+        let mut history = ChargerHistory::new(MockCharger::new(2000, 12600));
+        assert_eq!(history.last_current(), None);
+
+        assert_eq!(block_on(history.charging_current(1500)), Ok(1500));
+        assert_eq!(history.last_current(), Some((1500, 1500)));
+
+        assert_eq!(block_on(history.charging_current(3000)), Ok(2000));
+        assert_eq!(history.last_current(), Some((3000, 2000)));
+
+        assert_eq!(block_on(history.charging_voltage(13000)), Ok(12600));
+        assert_eq!(history.last_voltage(), Some((13000, 12600)));
+    }
+
+    #[test]
+    fn history_records_through_defaults() {
+        let mut history = ChargerHistory::new(MockCharger::new(2000, 12600));
+
+        let setpoint = block_on(history.apply_charging_current(2500)).unwrap();
+        assert!(setpoint.is_clamped());
+        assert_eq!(history.last_current(), Some((2500, 2000)));
+
+        block_on(history.inner_mut().charging_current(500)).unwrap();
+        assert_eq!(history.last_current(), Some((2500, 2000)));
+        assert_eq!(history.into_inner().current, 500);
+    }
 }
@@ -2,6 +2,25 @@
 #![no_std]
 #![warn(missing_docs)]
 
+// Shared math
+
+/// Returns `part` as a percentage of `whole`, rounded to the nearest percent.
+///
+/// The intermediate is widened to `u32` so it can't overflow. Results above `u8::MAX` and a `whole` of 0
+/// saturate at `u8::MAX`.
+pub(crate) const fn percent_u16(part: u16, whole: u16) -> u8 {
+    if whole == 0 {
+        return u8::MAX;
+    }
+    let whole = whole as u32;
+    let percent = (part as u32 * 100 + whole / 2) / whole;
+    if percent > u8::MAX as u32 {
+        u8::MAX
+    } else {
+        percent as u8
+    }
+}
+
 /// Async Smart Battery Charger module
 pub mod charger;
 
@@ -11,3 +30,23 @@ pub mod smart_battery;
 /// Advanced Configuration and Power Interface (ACPI)
 /// Power Source and Power Meter Devices module
 pub use embedded_batteries::acpi;
+
+#[cfg(test)]
+mod mock;
+
+// Lets the unit tests expand the exported macros, which refer to the crate by name.
+#[cfg(test)]
+extern crate self as embedded_batteries_async;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_u16_matches_blocking_crate() {
+        assert_eq!(percent_u16(1, 3), 33);
+        assert_eq!(percent_u16(u16::MAX, u16::MAX), 100);
+        assert_eq!(percent_u16(u16::MAX, 1), u8::MAX);
+        assert_eq!(percent_u16(10, 0), u8::MAX);
+    }
+}
@@ -0,0 +1,473 @@
+//! Test doubles and a minimal executor shared by the unit tests.
+
+use core::future::Future;
+use core::pin::pin;
+use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+use crate::charger::{self, Charger, InhibitReason, MilliAmps, MilliVolts};
+use crate::smart_battery::{
+    name_fits, BatteryModeFields, BatteryStatusFields, CapacityModeSignedValue, CapacityModeValue, Command, Cycles,
+    DeciKelvin, ErrorCode, ErrorKind, ErrorType, LifetimeData, ManufactureDate, MilliAmpsSigned, Minutes, Percent,
+    SmartBattery, SpecificationInfoFields,
+};
+
+/// Polls `future` to completion on the current thread.
+///
+/// The mocks never return `Poll::Pending` for long, so busy polling with a no-op waker is enough.
+pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+    const VTABLE: RawWakerVTable = RawWakerVTable::new(|_| RAW, |_| {}, |_| {}, |_| {});
+    const RAW: RawWaker = RawWaker::new(core::ptr::null(), &VTABLE);
+
+    // SAFETY: the vtable functions don't dereference the data pointer.
+    let waker = unsafe { Waker::from_raw(RAW) };
+    let mut cx = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+/// Smart Battery backed by a plain register file indexed by command code, using only the trait defaults.
+///
+/// Capacity registers are reported in the unit selected by the CAPACITY_MODE bit of the BatteryMode() register,
+/// and set_battery_mode() only writes the host writable bits, like a real gauge.
+#[derive(Debug, Clone)]
+pub(crate) struct RegisterFileBattery {
+    pub words: [u16; 0x1D],
+    pub manufacturer_name: &'static str,
+    pub device_name: &'static str,
+    pub device_chemistry: &'static str,
+    /// Command whose reads and writes fail with `ErrorKind::CommError`.
+    pub failing: Option<Command>,
+    /// Number of register reads, including failed ones.
+    pub reads: usize,
+}
+
+impl RegisterFileBattery {
+    /// A 3S Li-ion pack at 25 °C, 75% charged and idle, reporting in mAh.
+    pub(crate) fn new() -> Self {
+        let mut battery = Self {
+            words: [0; 0x1D],
+            manufacturer_name: "ACME",
+            device_name: "PACK-3S1P",
+            device_chemistry: "LION",
+            failing: None,
+            reads: 0,
+        };
+        battery.set(Command::RemainingCapacityAlarm, 400);
+        battery.set(Command::RemainingTimeAlarm, 10);
+        battery.set(Command::AtRateTimeToFull, 0xFFFF);
+        battery.set(Command::AtRateTimeToEmpty, 0xFFFF);
+        battery.set(Command::AtRateOk, 1);
+        battery.set(Command::Temperature, 2981);
+        battery.set(Command::Voltage, 11_400);
+        battery.set(Command::MaxError, 2);
+        battery.set(Command::RelativeStateOfCharge, 75);
+        battery.set(Command::AbsoluteStateOfCharge, 72);
+        battery.set(Command::RemainingCapacity, 3000);
+        battery.set(Command::FullChargeCapacity, 4000);
+        battery.set(Command::RunTimeToEmpty, 0xFFFF);
+        battery.set(Command::AverageTimeToEmpty, 0xFFFF);
+        battery.set(Command::AverageTimeToFull, 0xFFFF);
+        battery.set(Command::ChargingCurrent, 2000);
+        battery.set(Command::ChargingVoltage, 12_600);
+        battery.set(
+            Command::BatteryStatus,
+            BatteryStatusFields::new().with_initialized(true).into_bits(),
+        );
+        battery.set(Command::CycleCount, 12);
+        battery.set(Command::DesignCapacity, 4200);
+        battery.set(Command::DesignVoltage, 11_100);
+        battery.set(Command::SpecificationInfo, 0x0031);
+        battery.set(
+            Command::ManufactureDate,
+            ManufactureDate::new()
+                .with_year(44)
+                .with_month(3)
+                .with_day(14)
+                .into_bits(),
+        );
+        battery.set(Command::SerialNumber, 4711);
+        battery
+    }
+
+    pub(crate) fn get(&self, command: Command) -> u16 {
+        self.words[command as usize]
+    }
+
+    pub(crate) fn set(&mut self, command: Command, value: u16) {
+        self.words[command as usize] = value;
+    }
+
+    fn read(&mut self, command: Command) -> Result<u16, ErrorKind> {
+        self.reads += 1;
+        self.check(command)?;
+        Ok(self.get(command))
+    }
+
+    fn write(&mut self, command: Command, value: u16) -> Result<(), ErrorKind> {
+        self.check(command)?;
+        self.set(command, value);
+        Ok(())
+    }
+
+    fn check(&self, command: Command) -> Result<(), ErrorKind> {
+        if self.failing == Some(command) {
+            Err(ErrorKind::CommError)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn capacity_mode(&self) -> bool {
+        BatteryModeFields::from_bits(self.get(Command::BatteryMode)).capacity_mode()
+    }
+
+    fn read_capacity(&mut self, command: Command) -> Result<CapacityModeValue, ErrorKind> {
+        let raw = self.read(command)?;
+        Ok(if self.capacity_mode() {
+            CapacityModeValue::CentiWattUnsigned(raw)
+        } else {
+            CapacityModeValue::MilliAmpUnsigned(raw)
+        })
+    }
+
+    fn read_name(&mut self, command: Command, name: &'static str, buf: &mut [u8]) -> Result<(), ErrorKind> {
+        self.reads += 1;
+        self.check(command)?;
+        name_fits(buf, name.len())?;
+        buf[..name.len()].copy_from_slice(name.as_bytes());
+        buf[name.len()] = 0;
+        Ok(())
+    }
+}
+
+impl ErrorType for RegisterFileBattery {
+    type Error = ErrorKind;
+}
+
+impl SmartBattery for RegisterFileBattery {
+    async fn remaining_capacity_alarm(&mut self) -> Result<CapacityModeValue, Self::Error> {
+        self.read_capacity(Command::RemainingCapacityAlarm)
+    }
+
+    async fn set_remaining_capacity_alarm(&mut self, capacity: CapacityModeValue) -> Result<(), Self::Error> {
+        let (CapacityModeValue::MilliAmpUnsigned(raw) | CapacityModeValue::CentiWattUnsigned(raw)) = capacity;
+        self.write(Command::RemainingCapacityAlarm, raw)
+    }
+
+    async fn remaining_time_alarm(&mut self) -> Result<Minutes, Self::Error> {
+        self.read(Command::RemainingTimeAlarm)
+    }
+
+    async fn set_remaining_time_alarm(&mut self, time: Minutes) -> Result<(), Self::Error> {
+        self.write(Command::RemainingTimeAlarm, time)
+    }
+
+    async fn battery_mode(&mut self) -> Result<BatteryModeFields, Self::Error> {
+        self.read(Command::BatteryMode).map(BatteryModeFields::from_bits)
+    }
+
+    async fn set_battery_mode(&mut self, flags: BatteryModeFields) -> Result<(), Self::Error> {
+        let base = BatteryModeFields::from_bits(self.get(Command::BatteryMode));
+        self.write(Command::BatteryMode, flags.with_writable_from(base).into_bits())
+    }
+
+    async fn at_rate(&mut self) -> Result<CapacityModeSignedValue, Self::Error> {
+        let raw = self.read(Command::AtRate)? as i16;
+        Ok(if self.capacity_mode() {
+            CapacityModeSignedValue::CentiWattSigned(raw)
+        } else {
+            CapacityModeSignedValue::MilliAmpSigned(raw)
+        })
+    }
+
+    async fn set_at_rate(&mut self, rate: CapacityModeSignedValue) -> Result<(), Self::Error> {
+        let (CapacityModeSignedValue::MilliAmpSigned(raw) | CapacityModeSignedValue::CentiWattSigned(raw)) = rate;
+        self.write(Command::AtRate, raw as u16)
+    }
+
+    async fn at_rate_time_to_full(&mut self) -> Result<Minutes, Self::Error> {
+        self.read(Command::AtRateTimeToFull)
+    }
+
+    async fn at_rate_time_to_empty(&mut self) -> Result<Minutes, Self::Error> {
+        self.read(Command::AtRateTimeToEmpty)
+    }
+
+    async fn at_rate_ok(&mut self) -> Result<bool, Self::Error> {
+        self.read(Command::AtRateOk).map(|ok| ok != 0)
+    }
+
+    async fn temperature(&mut self) -> Result<DeciKelvin, Self::Error> {
+        self.read(Command::Temperature)
+    }
+
+    async fn voltage(&mut self) -> Result<MilliVolts, Self::Error> {
+        self.read(Command::Voltage)
+    }
+
+    async fn current(&mut self) -> Result<MilliAmpsSigned, Self::Error> {
+        self.read(Command::Current).map(|raw| raw as i16)
+    }
+
+    async fn average_current(&mut self) -> Result<MilliAmpsSigned, Self::Error> {
+        self.read(Command::AverageCurrent).map(|raw| raw as i16)
+    }
+
+    async fn max_error(&mut self) -> Result<Percent, Self::Error> {
+        self.read(Command::MaxError).map(|raw| raw as u8)
+    }
+
+    async fn relative_state_of_charge(&mut self) -> Result<Percent, Self::Error> {
+        self.read(Command::RelativeStateOfCharge).map(|raw| raw as u8)
+    }
+
+    async fn absolute_state_of_charge(&mut self) -> Result<Percent, Self::Error> {
+        self.read(Command::AbsoluteStateOfCharge).map(|raw| raw as u8)
+    }
+
+    async fn remaining_capacity(&mut self) -> Result<CapacityModeValue, Self::Error> {
+        self.read_capacity(Command::RemainingCapacity)
+    }
+
+    async fn full_charge_capacity(&mut self) -> Result<CapacityModeValue, Self::Error> {
+        self.read_capacity(Command::FullChargeCapacity)
+    }
+
+    async fn run_time_to_empty(&mut self) -> Result<Minutes, Self::Error> {
+        self.read(Command::RunTimeToEmpty)
+    }
+
+    async fn average_time_to_empty(&mut self) -> Result<Minutes, Self::Error> {
+        self.read(Command::AverageTimeToEmpty)
+    }
+
+    async fn average_time_to_full(&mut self) -> Result<Minutes, Self::Error> {
+        self.read(Command::AverageTimeToFull)
+    }
+
+    async fn charging_current(&mut self) -> Result<MilliAmps, Self::Error> {
+        self.read(Command::ChargingCurrent)
+    }
+
+    async fn charging_voltage(&mut self) -> Result<MilliVolts, Self::Error> {
+        self.read(Command::ChargingVoltage)
+    }
+
+    async fn battery_status(&mut self) -> Result<BatteryStatusFields, Self::Error> {
+        self.read(Command::BatteryStatus).map(BatteryStatusFields::from_bits)
+    }
+
+    async fn cycle_count(&mut self) -> Result<Cycles, Self::Error> {
+        self.read(Command::CycleCount)
+    }
+
+    async fn design_capacity(&mut self) -> Result<CapacityModeValue, Self::Error> {
+        self.read_capacity(Command::DesignCapacity)
+    }
+
+    async fn design_voltage(&mut self) -> Result<MilliVolts, Self::Error> {
+        self.read(Command::DesignVoltage)
+    }
+
+    async fn specification_info(&mut self) -> Result<SpecificationInfoFields, Self::Error> {
+        self.read(Command::SpecificationInfo)
+            .map(SpecificationInfoFields::from_bits)
+    }
+
+    async fn manufacture_date(&mut self) -> Result<ManufactureDate, Self::Error> {
+        self.read(Command::ManufactureDate).map(ManufactureDate::from_bits)
+    }
+
+    async fn serial_number(&mut self) -> Result<u16, Self::Error> {
+        self.read(Command::SerialNumber)
+    }
+
+    async fn manufacturer_name(&mut self, name: &mut [u8]) -> Result<(), Self::Error> {
+        self.read_name(Command::ManufacturerName, self.manufacturer_name, name)
+    }
+
+    async fn device_name(&mut self, name: &mut [u8]) -> Result<(), Self::Error> {
+        self.read_name(Command::DeviceName, self.device_name, name)
+    }
+
+    async fn device_chemistry(&mut self, chemistry: &mut [u8]) -> Result<(), Self::Error> {
+        self.read_name(Command::DeviceChemistry, self.device_chemistry, chemistry)
+    }
+}
+
+/// Gauge that overrides the optional Smart Battery functions, on top of a `RegisterFileBattery`.
+///
+/// manufacturer_access() returns `data` with its bytes swapped and manufacturer_data() returns `block`.
+#[derive(Debug, Clone)]
+pub(crate) struct VendorGauge {
+    pub battery: RegisterFileBattery,
+    pub block: &'static [u8],
+    pub lifetime: Option<LifetimeData>,
+    pub impedance: Option<u16>,
+    pub interval: Option<u32>,
+    pub cells: Option<u8>,
+}
+
+impl VendorGauge {
+    pub(crate) fn new() -> Self {
+        Self {
+            battery: RegisterFileBattery::new(),
+            block: &[0x01, 0x02, 0x03],
+            lifetime: Some(LifetimeData {
+                max_voltage: 12_800,
+                min_voltage: 8_900,
+                max_charge_current: 3000,
+                max_discharge_current: -6000,
+                max_temperature: 512,
+                min_temperature: -104,
+            }),
+            impedance: Some(85),
+            interval: Some(250),
+            cells: Some(4),
+        }
+    }
+}
+
+macro_rules! forward {
+    ($($name:ident($($arg:ident: $ty:ty),*) -> $ret:ty;)*) => {
+        $(
+            async fn $name(&mut self, $($arg: $ty),*) -> Result<$ret, Self::Error> {
+                self.battery.$name($($arg),*).await
+            }
+        )*
+    };
+}
+
+impl ErrorType for VendorGauge {
+    type Error = ErrorKind;
+}
+
+impl SmartBattery for VendorGauge {
+    forward! {
+        remaining_capacity_alarm() -> CapacityModeValue;
+        set_remaining_capacity_alarm(capacity: CapacityModeValue) -> ();
+        remaining_time_alarm() -> Minutes;
+        set_remaining_time_alarm(time: Minutes) -> ();
+        battery_mode() -> BatteryModeFields;
+        set_battery_mode(flags: BatteryModeFields) -> ();
+        at_rate() -> CapacityModeSignedValue;
+        set_at_rate(rate: CapacityModeSignedValue) -> ();
+        at_rate_time_to_full() -> Minutes;
+        at_rate_time_to_empty() -> Minutes;
+        at_rate_ok() -> bool;
+        temperature() -> DeciKelvin;
+        voltage() -> MilliVolts;
+        current() -> MilliAmpsSigned;
+        average_current() -> MilliAmpsSigned;
+        max_error() -> Percent;
+        relative_state_of_charge() -> Percent;
+        absolute_state_of_charge() -> Percent;
+        remaining_capacity() -> CapacityModeValue;
+        full_charge_capacity() -> CapacityModeValue;
+        run_time_to_empty() -> Minutes;
+        average_time_to_empty() -> Minutes;
+        average_time_to_full() -> Minutes;
+        charging_current() -> MilliAmps;
+        charging_voltage() -> MilliVolts;
+        battery_status() -> BatteryStatusFields;
+        cycle_count() -> Cycles;
+        design_capacity() -> CapacityModeValue;
+        design_voltage() -> MilliVolts;
+        specification_info() -> SpecificationInfoFields;
+        manufacture_date() -> ManufactureDate;
+        serial_number() -> u16;
+        manufacturer_name(name: &mut [u8]) -> ();
+        device_name(name: &mut [u8]) -> ();
+        device_chemistry(chemistry: &mut [u8]) -> ();
+    }
+
+    async fn manufacturer_access(&mut self, data: u16) -> Result<u16, Self::Error> {
+        Ok(data.swap_bytes())
+    }
+
+    async fn manufacturer_data(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let dst = buf
+            .get_mut(..self.block.len())
+            .ok_or(ErrorKind::BatteryStatus(ErrorCode::BadSize))?;
+        dst.copy_from_slice(self.block);
+        Ok(self.block.len())
+    }
+
+    async fn lifetime_data(&mut self) -> Result<Option<LifetimeData>, Self::Error> {
+        Ok(self.lifetime)
+    }
+
+    async fn internal_impedance_milliohm(&mut self) -> Result<Option<u16>, Self::Error> {
+        Ok(self.impedance)
+    }
+
+    async fn measurement_interval_ms(&mut self) -> Result<Option<u32>, Self::Error> {
+        Ok(self.interval)
+    }
+
+    async fn cell_count(&mut self) -> Result<Option<u8>, Self::Error> {
+        Ok(self.cells)
+    }
+}
+
+/// Charger that clamps its setpoints to `max_current` and `max_voltage`, like real chargers do when asked for
+/// more than they can deliver.
+#[derive(Debug, Clone)]
+pub(crate) struct MockCharger {
+    pub max_current: MilliAmps,
+    pub max_voltage: MilliVolts,
+    pub current: MilliAmps,
+    pub voltage: MilliVolts,
+    pub inhibit: Option<InhibitReason>,
+    /// Measured output current, `None` if the charger can't measure it.
+    pub delivered: Option<MilliAmps>,
+}
+
+impl MockCharger {
+    pub(crate) const fn new(max_current: MilliAmps, max_voltage: MilliVolts) -> Self {
+        Self {
+            max_current,
+            max_voltage,
+            current: 0,
+            voltage: 0,
+            inhibit: None,
+            delivered: None,
+        }
+    }
+}
+
+impl charger::ErrorType for MockCharger {
+    type Error = charger::ErrorKind;
+}
+
+impl Charger for MockCharger {
+    async fn charging_current(&mut self, current: MilliAmps) -> Result<MilliAmps, Self::Error> {
+        self.current = current.min(self.max_current);
+        Ok(self.current)
+    }
+
+    async fn charging_voltage(&mut self, voltage: MilliVolts) -> Result<MilliVolts, Self::Error> {
+        self.voltage = voltage.min(self.max_voltage);
+        Ok(self.voltage)
+    }
+
+    async fn max_charging_current(&mut self) -> Result<MilliAmps, Self::Error> {
+        Ok(self.max_current)
+    }
+
+    async fn max_charging_voltage(&mut self) -> Result<MilliVolts, Self::Error> {
+        Ok(self.max_voltage)
+    }
+
+    async fn charge_inhibit_reason(&mut self) -> Result<Option<InhibitReason>, Self::Error> {
+        Ok(self.inhibit)
+    }
+
+    async fn delivered_current(&mut self) -> Result<Option<MilliAmps>, Self::Error> {
+        Ok(self.delivered)
+    }
+}
@@ -1,14 +1,38 @@
 use core::future::Future;
 
+use embedded_batteries::acpi::PowerUnit;
+
 pub use embedded_batteries::smart_battery::{
-    BatteryModeFields, BatteryStatusFields, CapacityModeSignedValue, CapacityModeValue, Cycles, DeciKelvin, Error,
-    ErrorCode, ErrorKind, ErrorType, ManufactureDate, Minutes, Percent, Revision, SpecificationInfoFields, Version,
+    crc8_smbus, decikelvin_to_decicelsius, name_buffer, name_fits, sbs_str, Alarm, AtRateAnalysis, AtRateState,
+    BatteryModeFields, BatterySnapshot, BatteryStatusFields, BlockQuirk, CapacityModeSignedValue, CapacityModeValue,
+    CapacityOverflow, ChargeStateTracker, ChargeTransition, Chemistry, Command, CoulombCounter, Cycles, DeciCelsius,
+    DeciKelvin, Error, ErrorCode, ErrorKind, ErrorType, LifetimeData, LifetimeDataLayout, ManufactureDate, Minutes,
+    PartialSnapshot, Percent, PollSchedule, Revision, Severity, SnapshotDiff, SocFilter, SpecificationInfoFields,
+    StatusLog, TenMilliWattHours, TenMilliWatts, TimeEstimate, Version, IDLE_CURRENT_THRESHOLD,
+    MAX_DEVICE_CHEMISTRY_LEN, MAX_DEVICE_NAME_LEN, MAX_MANUFACTURER_NAME_LEN, SBS_MAX_STRING_LEN,
+    STANDARD_REGISTER_DUMP_MAX_LEN,
 };
+#[cfg(feature = "defmt")]
+pub use embedded_batteries::smart_battery::{log_cycles, log_decikelvin, log_minutes, log_percent, UnitValue};
 use embedded_batteries::MilliAmps;
 pub use embedded_batteries::{MilliAmpsSigned, MilliVolts};
 
 /// Asynchronous Smart Battery methods.
 pub trait SmartBattery: ErrorType {
+    /// 0x00
+    ///
+    /// Asynchronously writes `data` to ManufacturerAccess() and returns the word read back. The meaning of both
+    /// words is vendor specific (e.g. entering sleep mode or reading the device type), see the gauge's datasheet.
+    ///
+    /// This function is optional in the SBS spec, so the default returns an error of kind `ErrorKind::Unsupported`.
+    fn manufacturer_access(&mut self, data: u16) -> impl Future<Output = Result<u16, Self::Error>>
+    where
+        Self::Error: From<ErrorKind>,
+    {
+        let _ = data;
+        async { Err(ErrorKind::Unsupported.into()) }
+    }
+
     /// 0x01
     ///
     /// Gets the Low Capacity alarm threshold value. Whenever the RemainingCapacity() falls below the
@@ -295,6 +319,15 @@ pub trait SmartBattery: ErrorType {
     /// This function accepts a mutable buffer of u8s and returns it filled with a **null-terminated** character array
     /// containing the battery's manufacturer's name. For example, "MyBattCo\0" would identify the Smart Battery's
     /// manufacturer as MyBattCo.
+    ///
+    /// The SMBus block read returns a length byte before the string. Implementations must strip it (see
+    /// `BlockQuirk::decode_block()`) and write only the string followed by its null terminator. Use `sbs_str()`
+    /// to read the result back as a `&str`.
+    ///
+    /// If the buffer is too short to hold the string plus its null terminator, implementations must return an
+    /// error of kind `ErrorKind::BatteryStatus(ErrorCode::BadSize)`. See `name_fits()`.
+    ///
+    /// The string is at most `MAX_MANUFACTURER_NAME_LEN` bytes long, so a buffer from `name_buffer()` always fits.
     fn manufacturer_name(&mut self, name: &mut [u8]) -> impl Future<Output = Result<(), Self::Error>>;
 
     /// 0x21
@@ -302,6 +335,15 @@ pub trait SmartBattery: ErrorType {
     /// This function accepts a mutable buffer of u8s and returns it filled with a **null-terminated** character array
     /// that contains the battery's name. For example, a DeviceName() of "MBC101\0" would indicate that
     /// the battery is a model MBC101.
+    ///
+    /// The SMBus block read returns a length byte before the string. Implementations must strip it (see
+    /// `BlockQuirk::decode_block()`) and write only the string followed by its null terminator. Use `sbs_str()`
+    /// to read the result back as a `&str`.
+    ///
+    /// If the buffer is too short to hold the string plus its null terminator, implementations must return an
+    /// error of kind `ErrorKind::BatteryStatus(ErrorCode::BadSize)`. See `name_fits()`.
+    ///
+    /// The string is at most `MAX_DEVICE_NAME_LEN` bytes long, so a buffer from `name_buffer()` always fits.
     fn device_name(&mut self, name: &mut [u8]) -> impl Future<Output = Result<(), Self::Error>>;
 
     /// 0x22
@@ -309,7 +351,470 @@ pub trait SmartBattery: ErrorType {
     /// This function accepts a mutable buffer of u8s and returns it filled with a **null-terminated** character array
     /// that contains the battery's chemistry. For example, if the DeviceChemistry() function returns "NiMH\0",
     /// the battery pack would contain nickel metal hydride cells.
+    ///
+    /// The SMBus block read returns a length byte before the string. Implementations must strip it (see
+    /// `BlockQuirk::decode_block()`) and write only the string followed by its null terminator. Use `sbs_str()`
+    /// to read the result back as a `&str`.
+    ///
+    /// If the buffer is too short to hold the string plus its null terminator, implementations must return an
+    /// error of kind `ErrorKind::BatteryStatus(ErrorCode::BadSize)`. See `name_fits()`.
+    ///
+    /// The string is at most `MAX_DEVICE_CHEMISTRY_LEN` bytes long, so a buffer from `name_buffer()` always fits.
     fn device_chemistry(&mut self, chemistry: &mut [u8]) -> impl Future<Output = Result<(), Self::Error>>;
+
+    /// 0x23
+    ///
+    /// Asynchronously reads the ManufacturerData() block into `buf` and returns the number of valid bytes written.
+    /// The block length is taken from the SMBus length byte, which isn't copied into `buf`, so at most 32 bytes are
+    /// written. The content is vendor specific, see the gauge's datasheet.
+    ///
+    /// If `buf` is too short to hold the block, implementations must return an error of kind
+    /// `ErrorKind::BatteryStatus(ErrorCode::BadSize)`.
+    ///
+    /// This function is optional in the SBS spec, so the default returns an error of kind `ErrorKind::Unsupported`.
+    fn manufacturer_data(&mut self, buf: &mut [u8]) -> impl Future<Output = Result<usize, Self::Error>>
+    where
+        Self::Error: From<ErrorKind>,
+    {
+        let _ = buf;
+        async { Err(ErrorKind::Unsupported.into()) }
+    }
+
+    /// Asynchronously returns the cell-pack's internal temperature in decidegrees Celsius.
+    ///
+    /// See `decikelvin_to_decicelsius()` for the conversion and its range.
+    fn temperature_celsius(&mut self) -> impl Future<Output = Result<DeciCelsius, Self::Error>> {
+        async { Ok(decikelvin_to_decicelsius(self.temperature().await?)) }
+    }
+
+    /// Asynchronously returns an estimate of the battery's self-discharge (quiescent) current in mA.
+    ///
+    /// This is a heuristic based on current(): when the battery reports it is discharging and the
+    /// measured discharge current is no larger than `IDLE_CURRENT_THRESHOLD`, the system is assumed to be
+    /// idle and the measured current is returned as the self-discharge estimate. Otherwise the battery is
+    /// either charging or under load and `None` is returned.
+    fn self_discharge_estimate(&mut self) -> impl Future<Output = Result<Option<MilliAmps>, Self::Error>> {
+        async {
+            let current = self.current().await?;
+            if current > 0 || current.unsigned_abs() > IDLE_CURRENT_THRESHOLD {
+                return Ok(None);
+            }
+
+            if self.battery_status().await?.discharging() {
+                Ok(Some(current.unsigned_abs()))
+            } else {
+                Ok(None)
+            }
+        }
+    }
+
+    /// Asynchronously returns the unit capacity and rate values are currently reported in, based on the
+    /// BatteryMode()'s CAPACITY_MODE bit.
+    ///
+    /// `PowerUnit::MilliAmps` means capacities are in mAh and rates in mA (CAPACITY_MODE = 0).
+    /// `PowerUnit::MilliWatts` means capacities are in 10mWh and rates in 10mW (CAPACITY_MODE = 1).
+    fn capacity_unit(&mut self) -> impl Future<Output = Result<PowerUnit, Self::Error>> {
+        async {
+            if self.battery_mode().await?.capacity_mode() {
+                Ok(PowerUnit::MilliWatts)
+            } else {
+                Ok(PowerUnit::MilliAmps)
+            }
+        }
+    }
+
+    /// Asynchronously reads the SBS strings needed to populate an ACPI `BixReturn` in one call.
+    ///
+    /// `model` is filled from `device_name()`, `serial` with the decimal ASCIIZ representation of
+    /// `serial_number()` and `chem` from `device_chemistry()`. BIX's `oem_info` has no SBS source and must be
+    /// supplied by the caller.
+    ///
+    /// Returns an error of kind `ErrorKind::BatteryStatus(ErrorCode::BadSize)` if `serial` is too short to hold
+    /// the serial number digits plus a null terminator (6 bytes always suffice).
+    fn bix_strings(
+        &mut self,
+        model: &mut [u8],
+        serial: &mut [u8],
+        chem: &mut [u8],
+    ) -> impl Future<Output = Result<(), Self::Error>>
+    where
+        Self::Error: From<ErrorKind>,
+    {
+        async move {
+            self.device_name(model).await?;
+
+            let mut number = self.serial_number().await?;
+            let mut digits = [0u8; 5];
+            let mut len = 0;
+            loop {
+                digits[len] = b'0' + (number % 10) as u8;
+                len += 1;
+                number /= 10;
+                if number == 0 {
+                    break;
+                }
+            }
+            name_fits(serial, len)?;
+            for (dst, src) in serial.iter_mut().zip(digits[..len].iter().rev()) {
+                *dst = *src;
+            }
+            serial[len] = 0;
+
+            self.device_chemistry(chem).await
+        }
+    }
+
+    /// Asynchronously reads the commonly polled dynamic values in one call.
+    ///
+    /// The fields are read sequentially in declaration order, not atomically, so values may come from different
+    /// measurement updates. Reading stops at the first error, which is returned. See `snapshot_partial()` to keep
+    /// the values read before a failure.
+    fn snapshot(&mut self) -> impl Future<Output = Result<BatterySnapshot, Self::Error>> {
+        async {
+            Ok(BatterySnapshot {
+                voltage: self.voltage().await?,
+                current: self.current().await?,
+                average_current: self.average_current().await?,
+                temperature: self.temperature().await?,
+                relative_state_of_charge: self.relative_state_of_charge().await?,
+                absolute_state_of_charge: self.absolute_state_of_charge().await?,
+                remaining_capacity: self.remaining_capacity().await?,
+                full_charge_capacity: self.full_charge_capacity().await?,
+                battery_status: self.battery_status().await?,
+                cycle_count: self.cycle_count().await?,
+            })
+        }
+    }
+
+    /// Asynchronously reads the commonly polled dynamic values, recording each read's result independently.
+    ///
+    /// Unlike chaining the individual reads with `?`, a failing read doesn't discard the values that were read
+    /// successfully. Errors are converted to their `ErrorKind`.
+    fn snapshot_partial(&mut self) -> impl Future<Output = PartialSnapshot> {
+        async {
+            PartialSnapshot {
+                voltage: self.voltage().await.map_err(|e| e.kind()),
+                current: self.current().await.map_err(|e| e.kind()),
+                average_current: self.average_current().await.map_err(|e| e.kind()),
+                temperature: self.temperature().await.map_err(|e| e.kind()),
+                relative_state_of_charge: self.relative_state_of_charge().await.map_err(|e| e.kind()),
+                absolute_state_of_charge: self.absolute_state_of_charge().await.map_err(|e| e.kind()),
+                remaining_capacity: self.remaining_capacity().await.map_err(|e| e.kind()),
+                full_charge_capacity: self.full_charge_capacity().await.map_err(|e| e.kind()),
+                battery_status: self.battery_status().await.map_err(|e| e.kind()),
+                cycle_count: self.cycle_count().await.map_err(|e| e.kind()),
+            }
+        }
+    }
+
+    /// Asynchronously writes a human readable report of the commonly polled dynamic values to `out`.
+    ///
+    /// The values are read with `snapshot_partial()`, so a failing read is reported in its line instead of
+    /// aborting the report. See the `Display` implementation of `PartialSnapshot` for the format.
+    fn report<W: core::fmt::Write>(&mut self, out: &mut W) -> impl Future<Output = core::fmt::Result> {
+        async move { write!(out, "{}", self.snapshot_partial().await) }
+    }
+
+    /// Asynchronously checks that the battery implements at least version `min` of the SBS specification.
+    ///
+    /// Reads specification_info() and returns an error of kind `ErrorKind::UnsupportedVersion` carrying the
+    /// reported version if it is older than `min`.
+    fn require_version(&mut self, min: Version) -> impl Future<Output = Result<(), Self::Error>>
+    where
+        Self::Error: From<ErrorKind>,
+    {
+        async move {
+            let version = self.specification_info().await?.version();
+            if version < min {
+                Err(ErrorKind::UnsupportedVersion(version).into())
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    /// Asynchronously estimates pack wear as `cycle_count() * 100 / rated_cycles`, rounded to the nearest percent
+    /// and saturating at `Percent::MAX`.
+    ///
+    /// Rated cycle life is chemistry and vendor specific and is not reported by the battery, so it must be
+    /// supplied by the caller (e.g. from the cell datasheet). A `rated_cycles` of 0 yields `Percent::MAX`.
+    fn cycle_wear_percent(&mut self, rated_cycles: Cycles) -> impl Future<Output = Result<Percent, Self::Error>> {
+        async move { Ok(crate::percent_u16(self.cycle_count().await?, rated_cycles)) }
+    }
+
+    /// Asynchronously returns the cell-pack's internal temperature, or `None` if the gauge doesn't measure
+    /// temperature.
+    ///
+    /// Some gauges without a temperature sensor report 0 dK (absolute zero). Since no real pack can be at
+    /// absolute zero, a reading of 0 is treated as "unsupported" rather than reported as -273.1 °C.
+    fn temperature_opt(&mut self) -> impl Future<Output = Result<Option<DeciKelvin>, Self::Error>> {
+        async {
+            match self.temperature().await? {
+                0 => Ok(None),
+                temperature => Ok(Some(temperature)),
+            }
+        }
+    }
+
+    /// Asynchronously estimates the remaining energy in mWh from remaining_capacity().
+    ///
+    /// In 10mWh capacity mode the value is converted directly. In mAh mode it is multiplied by design_voltage(),
+    /// which is an approximation: the actual energy depends on the pack voltage over the rest of the discharge,
+    /// not the nominal design voltage.
+    fn remaining_energy_mwh(&mut self) -> impl Future<Output = Result<u32, Self::Error>> {
+        async {
+            match self.remaining_capacity().await? {
+                CapacityModeValue::CentiWattUnsigned(capacity) => Ok(u32::from(capacity) * 10),
+                CapacityModeValue::MilliAmpUnsigned(capacity) => {
+                    let voltage = self.design_voltage().await?;
+                    Ok((u64::from(capacity) * u64::from(voltage) / 1000) as u32)
+                }
+            }
+        }
+    }
+
+    /// Asynchronously writes BatteryMode() and verifies that its read only bits (INTERNAL_CHARGE_CONTROLLER,
+    /// PRIMARY_BATTERY_SUPPORT and CONDITION_FLAG) are unchanged, returning the mode read back after the write.
+    ///
+    /// Returns an error of kind `ErrorKind::ReadOnlyModeChanged` if any read only bit differs from its value
+    /// before the write, which indicates a gauge fault.
+    fn write_battery_mode_verified(
+        &mut self,
+        fields: BatteryModeFields,
+    ) -> impl Future<Output = Result<BatteryModeFields, Self::Error>>
+    where
+        Self::Error: From<ErrorKind>,
+    {
+        async move {
+            let before = self.battery_mode().await?;
+            self.set_battery_mode(fields).await?;
+            let after = self.battery_mode().await?;
+
+            if before.internal_charge_controller() != after.internal_charge_controller()
+                || before.primary_battery_support() != after.primary_battery_support()
+                || before.condition_flag() != after.condition_flag()
+            {
+                Err(ErrorKind::ReadOnlyModeChanged.into())
+            } else {
+                Ok(after)
+            }
+        }
+    }
+
+    /// Asynchronous best-effort indication that the gauge has just powered on or been reset and its averaged
+    /// values (e.g. average_current(), average_time_to_empty()) may still be settling.
+    ///
+    /// SBS doesn't expose gauge uptime, so this returns `true` if the INITIALIZED bit of battery_status() is
+    /// clear, or if cycle_count() is 0, i.e. the gauge has no learned history yet. It may report `false` for a
+    /// gauge that restarted with retained calibration data.
+    fn freshly_powered(&mut self) -> impl Future<Output = Result<bool, Self::Error>> {
+        async {
+            if !self.battery_status().await?.initialized() {
+                return Ok(true);
+            }
+            Ok(self.cycle_count().await? == 0)
+        }
+    }
+
+    /// Asynchronously reads the gauge's lifetime data, or `None` if the gauge doesn't provide it.
+    ///
+    /// Lifetime data lives in a vendor specific manufacturer block, so the default returns `None`. Gauges that
+    /// support it should read the block and decode it with a `LifetimeDataLayout` matching their firmware.
+    fn lifetime_data(&mut self) -> impl Future<Output = Result<Option<LifetimeData>, Self::Error>> {
+        async { Ok(None) }
+    }
+
+    /// Asynchronously reads the pack's internal impedance in milliohms, or `None` if the gauge doesn't provide it.
+    ///
+    /// The impedance is used to estimate the voltage sag under load (sag = current * impedance), e.g. before
+    /// allowing a power burst. It isn't an SBS function, so the default returns `None`. Gauges that expose it
+    /// through a manufacturer register should override this to read it.
+    fn internal_impedance_milliohm(&mut self) -> impl Future<Output = Result<Option<u16>, Self::Error>> {
+        async { Ok(None) }
+    }
+
+    /// Asynchronously reads the gauge's effective measurement interval in milliseconds, or `None` if it isn't
+    /// known.
+    ///
+    /// Polling faster than this only returns the same values again. SBS has no function for it, so the default
+    /// returns `None`. Gauges that expose it through a manufacturer register should override this to read it.
+    ///
+    /// This is the interval currently in effect, which ACPI `_BMS` sets and which must lie between the BIX
+    /// `min_sampling_time` and `max_sampling_time`.
+    fn measurement_interval_ms(&mut self) -> impl Future<Output = Result<Option<u32>, Self::Error>> {
+        async { Ok(None) }
+    }
+
+    /// Asynchronously returns the number of series cells in the pack, or `None` if it can't be determined.
+    ///
+    /// Gauges that report the cell count through a manufacturer register should override this to read it. The
+    /// default derives it from DesignVoltage() and DeviceChemistry() with `Chemistry::plausible_cell_count()`, and
+    /// returns `None` for chemistries not defined by the SBS spec.
+    fn cell_count(&mut self) -> impl Future<Output = Result<Option<u8>, Self::Error>> {
+        async {
+            let mut chemistry = name_buffer();
+            self.device_chemistry(&mut chemistry).await?;
+            let Some(chemistry) = Chemistry::from_device_chemistry(&chemistry) else {
+                return Ok(None);
+            };
+            Ok(chemistry.plausible_cell_count(self.design_voltage().await?))
+        }
+    }
+
+    /// Asynchronously reads every standard register into `out` for offline analysis, returning the number of
+    /// bytes written.
+    ///
+    /// The word registers 0x01 - 0x1C are written first, in command order, as little-endian words (56 bytes).
+    /// They are followed by the block registers 0x20 - 0x22, each length-prefixed: one length byte, then that
+    /// many string bytes without the null terminator. The dump is at most `STANDARD_REGISTER_DUMP_MAX_LEN` bytes,
+    /// and an error of kind `ErrorKind::BatteryStatus(ErrorCode::BadSize)` is returned if it doesn't fit in
+    /// `out`. The dump reflects the current BatteryMode() and AtRate() settings, and AtRateOK() is dumped as 1
+    /// or 0.
+    fn dump_standard_registers(&mut self, out: &mut [u8]) -> impl Future<Output = Result<usize, Self::Error>>
+    where
+        Self::Error: From<ErrorKind>,
+    {
+        async move {
+            let capacity = |value: CapacityModeValue| match value {
+                CapacityModeValue::MilliAmpUnsigned(raw) | CapacityModeValue::CentiWattUnsigned(raw) => raw,
+            };
+            let at_rate = match self.at_rate().await? {
+                CapacityModeSignedValue::MilliAmpSigned(raw) | CapacityModeSignedValue::CentiWattSigned(raw) => {
+                    raw as u16
+                }
+            };
+            let words = [
+                capacity(self.remaining_capacity_alarm().await?),
+                self.remaining_time_alarm().await?,
+                self.battery_mode().await?.into_bits(),
+                at_rate,
+                self.at_rate_time_to_full().await?,
+                self.at_rate_time_to_empty().await?,
+                u16::from(self.at_rate_ok().await?),
+                self.temperature().await?,
+                self.voltage().await?,
+                self.current().await? as u16,
+                self.average_current().await? as u16,
+                u16::from(self.max_error().await?),
+                u16::from(self.relative_state_of_charge().await?),
+                u16::from(self.absolute_state_of_charge().await?),
+                capacity(self.remaining_capacity().await?),
+                capacity(self.full_charge_capacity().await?),
+                self.run_time_to_empty().await?,
+                self.average_time_to_empty().await?,
+                self.average_time_to_full().await?,
+                self.charging_current().await?,
+                self.charging_voltage().await?,
+                self.battery_status().await?.into_bits(),
+                self.cycle_count().await?,
+                capacity(self.design_capacity().await?),
+                self.design_voltage().await?,
+                self.specification_info().await?.into_bits(),
+                self.manufacture_date().await?.into_bits(),
+                self.serial_number().await?,
+            ];
+
+            let mut written = 0;
+            for word in words {
+                let dst = out
+                    .get_mut(written..written + 2)
+                    .ok_or(ErrorKind::BatteryStatus(ErrorCode::BadSize))?;
+                dst.copy_from_slice(&word.to_le_bytes());
+                written += 2;
+            }
+
+            let mut names = [name_buffer(); 3];
+            self.manufacturer_name(&mut names[0]).await?;
+            self.device_name(&mut names[1]).await?;
+            self.device_chemistry(&mut names[2]).await?;
+            for name in names {
+                let len = name.iter().position(|&b| b == 0).unwrap_or(name.len() - 1);
+                let dst = out
+                    .get_mut(written..written + 1 + len)
+                    .ok_or(ErrorKind::BatteryStatus(ErrorCode::BadSize))?;
+                dst[0] = len as u8;
+                dst[1..].copy_from_slice(&name[..len]);
+                written += 1 + len;
+            }
+            Ok(written)
+        }
+    }
+
+    /// Asynchronously returns the deviation of current() from average_current(), saturating at the `i16` range.
+    ///
+    /// A large magnitude indicates a load transient. A positive value means the instantaneous current is higher
+    /// than the one minute average (more charge, or less discharge, than usual), a negative value means it is
+    /// lower (e.g. a discharge burst).
+    fn current_transient(&mut self) -> impl Future<Output = Result<i16, Self::Error>> {
+        async {
+            let current = self.current().await?;
+            let average = self.average_current().await?;
+            Ok(current.saturating_sub(average))
+        }
+    }
+
+    /// Asynchronously returns the instantaneous power flowing into the battery in mW, as
+    /// `voltage() * current() / 1000`.
+    ///
+    /// The sign follows current(): positive while charging, negative while discharging. The result is
+    /// truncated toward zero, so magnitudes below 1mW read as 0.
+    fn present_power_mw(&mut self) -> impl Future<Output = Result<i32, Self::Error>> {
+        async {
+            let voltage = self.voltage().await?;
+            let current = self.current().await?;
+            Ok((i64::from(voltage) * i64::from(current) / 1000) as i32)
+        }
+    }
+
+    /// Asynchronously returns true if current() (0x0A) is positive, i.e. the battery is accepting charge.
+    ///
+    /// A current of 0 (idle or fully charged) reads as not charging. Use battery_status() to tell the two apart.
+    fn is_charging(&mut self) -> impl Future<Output = Result<bool, Self::Error>> {
+        async { Ok(self.current().await? > 0) }
+    }
+
+    /// Asynchronously returns at_rate_time_to_full() (0x05) as a `TimeEstimate`.
+    fn at_rate_time_to_full_estimate(&mut self) -> impl Future<Output = Result<TimeEstimate, Self::Error>> {
+        async { self.at_rate_time_to_full().await.map(TimeEstimate::from) }
+    }
+
+    /// Asynchronously returns at_rate_time_to_empty() (0x06) as a `TimeEstimate`.
+    fn at_rate_time_to_empty_estimate(&mut self) -> impl Future<Output = Result<TimeEstimate, Self::Error>> {
+        async { self.at_rate_time_to_empty().await.map(TimeEstimate::from) }
+    }
+
+    /// Asynchronously returns run_time_to_empty() (0x11) as a `TimeEstimate`.
+    fn run_time_to_empty_estimate(&mut self) -> impl Future<Output = Result<TimeEstimate, Self::Error>> {
+        async { self.run_time_to_empty().await.map(TimeEstimate::from) }
+    }
+
+    /// Asynchronously returns average_time_to_empty() (0x12) as a `TimeEstimate`.
+    fn average_time_to_empty_estimate(&mut self) -> impl Future<Output = Result<TimeEstimate, Self::Error>> {
+        async { self.average_time_to_empty().await.map(TimeEstimate::from) }
+    }
+
+    /// Asynchronously returns average_time_to_full() (0x13) as a `TimeEstimate`.
+    fn average_time_to_full_estimate(&mut self) -> impl Future<Output = Result<TimeEstimate, Self::Error>> {
+        async { self.average_time_to_full().await.map(TimeEstimate::from) }
+    }
+
+    /// Asynchronously writes `rate` to AtRate() (0x04) then reads AtRateTimeToFull() (0x05),
+    /// AtRateTimeToEmpty() (0x06) and AtRateOK() (0x07), in that order.
+    ///
+    /// The reads follow the write directly so all three predictions are made for the same rate. Callers sharing
+    /// the battery with other hosts must hold it for the whole call.
+    fn at_rate_full_analysis(
+        &mut self,
+        rate: CapacityModeSignedValue,
+    ) -> impl Future<Output = Result<AtRateAnalysis, Self::Error>> {
+        async move {
+            self.set_at_rate(rate).await?;
+            let time_to_full = self.at_rate_time_to_full().await?;
+            let time_to_empty = self.at_rate_time_to_empty().await?;
+            let ok = self.at_rate_ok().await?;
+            Ok(AtRateAnalysis::new(rate, time_to_full, time_to_empty, ok))
+        }
+    }
 }
 
 #[macro_export]
@@ -324,6 +829,8 @@ pub trait SmartBattery: ErrorType {
 ///
 /// - `From<inner::Error>` must be implemented for the wrapper error type to enable error conversion
 /// - The wrapper error type must implement the `Error` trait
+/// - The inner error type must implement `From<ErrorKind>`, as `manufacturer_access()` and
+///   `manufacturer_data()` are forwarded to it
 ///
 /// # Parameters
 ///
@@ -545,6 +1052,187 @@ macro_rules! impl_smart_battery_for_wrapper_type {
             async fn device_chemistry(&mut self, chemistry: &mut [u8]) -> Result<(), Self::Error> {
                 Ok(self.$inner.device_chemistry(chemistry).await?)
             }
+
+            async fn lifetime_data(
+                &mut self,
+            ) -> Result<Option<embedded_batteries_async::smart_battery::LifetimeData>, Self::Error> {
+                Ok(self.$inner.lifetime_data().await?)
+            }
+
+            async fn internal_impedance_milliohm(&mut self) -> Result<Option<u16>, Self::Error> {
+                Ok(self.$inner.internal_impedance_milliohm().await?)
+            }
+
+            async fn manufacturer_access(&mut self, data: u16) -> Result<u16, Self::Error>
+            where
+                Self::Error: From<embedded_batteries_async::smart_battery::ErrorKind>,
+            {
+                Ok(self.$inner.manufacturer_access(data).await?)
+            }
+
+            async fn measurement_interval_ms(&mut self) -> Result<Option<u32>, Self::Error> {
+                Ok(self.$inner.measurement_interval_ms().await?)
+            }
+
+            async fn manufacturer_data(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error>
+            where
+                Self::Error: From<embedded_batteries_async::smart_battery::ErrorKind>,
+            {
+                Ok(self.$inner.manufacturer_data(buf).await?)
+            }
+
+            async fn cell_count(&mut self) -> Result<Option<u8>, Self::Error> {
+                Ok(self.$inner.cell_count().await?)
+            }
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{block_on, RegisterFileBattery};
+
+    #[test]
+    fn snapshot_partial_keeps_successful_reads() {
+        let mut battery = RegisterFileBattery::new();
+        battery.failing = Some(Command::Current);
+
+        let partial = block_on(battery.snapshot_partial());
+        assert_eq!(partial.current, Err(ErrorKind::CommError));
+        assert_eq!(partial.voltage, Ok(11_400));
+        assert_eq!(partial.average_current, Ok(0));
+        assert_eq!(partial.temperature, Ok(2981));
+        assert_eq!(partial.relative_state_of_charge, Ok(75));
+        assert_eq!(
+            partial.remaining_capacity,
+            Ok(CapacityModeValue::MilliAmpUnsigned(3000))
+        );
+        assert_eq!(partial.cycle_count, Ok(12));
+        assert_eq!(battery.reads, 10);
+    }
+
+    #[test]
+    fn snapshot_partial_without_failures() {
+        let mut battery = RegisterFileBattery::new();
+        let partial = block_on(battery.snapshot_partial());
+        assert_eq!(partial.current, Ok(0));
+        assert_eq!(
+            partial.full_charge_capacity,
+            Ok(CapacityModeValue::MilliAmpUnsigned(4000))
+        );
+        assert!(partial.battery_status.unwrap().initialized());
+    }
+
+    struct Wrapper {
+        gauge: crate::mock::VendorGauge,
+    }
+
+    crate::impl_smart_battery_for_wrapper_type!(Wrapper, gauge, ErrorKind);
+
+    #[test]
+    fn wrapper_forwards_lifetime_data() {
+        let mut wrapper = Wrapper {
+            gauge: crate::mock::VendorGauge::new(),
+        };
+        let expected = wrapper.gauge.lifetime;
+        assert!(expected.is_some());
+        assert_eq!(block_on(wrapper.lifetime_data()), Ok(expected));
+    }
+
+    #[test]
+    fn report_matches_blocking_format() {
+        extern crate std;
+        use std::string::String;
+
+        let mut battery = RegisterFileBattery::new();
+        let mut report = String::new();
+        block_on(battery.report(&mut report)).unwrap();
+        assert_eq!(report, std::format!("{}", block_on(battery.snapshot_partial())));
+        assert!(report.starts_with("voltage:            11400 mV\n"));
+    }
+
+    #[test]
+    fn wrapper_forwards_internal_impedance() {
+        let mut wrapper = Wrapper {
+            gauge: crate::mock::VendorGauge::new(),
+        };
+        assert_eq!(block_on(wrapper.internal_impedance_milliohm()), Ok(Some(85)));
+        assert_eq!(block_on(wrapper.gauge.battery.internal_impedance_milliohm()), Ok(None));
+    }
+
+    #[test]
+    fn wrapper_forwards_manufacturer_access() {
+        let mut wrapper = Wrapper {
+            gauge: crate::mock::VendorGauge::new(),
+        };
+        assert_eq!(block_on(wrapper.manufacturer_access(0x1234)), Ok(0x3412));
+    }
+
+    #[test]
+    fn wrapper_forwards_measurement_interval() {
+        let mut wrapper = Wrapper {
+            gauge: crate::mock::VendorGauge::new(),
+        };
+        assert_eq!(block_on(wrapper.measurement_interval_ms()), Ok(Some(250)));
+        wrapper.gauge.interval = None;
+        assert_eq!(block_on(wrapper.measurement_interval_ms()), Ok(None));
+    }
+
+    #[test]
+    fn wrapper_forwards_manufacturer_data() {
+        let mut wrapper = Wrapper {
+            gauge: crate::mock::VendorGauge::new(),
+        };
+        let mut buf = [0u8; 4];
+        assert_eq!(block_on(wrapper.manufacturer_data(&mut buf)), Ok(3));
+        assert_eq!(&buf[..3], &[1, 2, 3]);
+    }
+
+    #[test]
+    fn wrapper_forwards_cell_count() {
+        let mut wrapper = Wrapper {
+            gauge: crate::mock::VendorGauge::new(),
+        };
+        assert_eq!(block_on(wrapper.cell_count()), Ok(Some(4)));
+    }
+
+    #[test]
+    fn snapshot_reads_every_value() {
+        let mut battery = RegisterFileBattery::new();
+        battery.set(Command::Current, (-850i16) as u16);
+        let snapshot = block_on(battery.snapshot()).unwrap();
+        assert_eq!(snapshot.voltage, 11400);
+        assert_eq!(snapshot.current, -850);
+        assert_eq!(snapshot.temperature, 2981);
+        assert_eq!(snapshot.relative_state_of_charge, 75);
+        assert_eq!(snapshot.absolute_state_of_charge, 72);
+        assert_eq!(snapshot.remaining_capacity, CapacityModeValue::MilliAmpUnsigned(3000));
+        assert_eq!(snapshot.full_charge_capacity, CapacityModeValue::MilliAmpUnsigned(4000));
+        assert_eq!(snapshot.battery_status.into_bits(), 0x0080);
+        assert_eq!(snapshot.cycle_count, 12);
+
+        battery.failing = Some(Command::CycleCount);
+        assert_eq!(block_on(battery.snapshot()), Err(ErrorKind::CommError));
+    }
+
+    #[test]
+    fn temperature_celsius_converts_decikelvin() {
+        let mut battery = RegisterFileBattery::new();
+        assert_eq!(block_on(battery.temperature_celsius()), Ok(250));
+        battery.set(Command::Temperature, 2600);
+        assert_eq!(block_on(battery.temperature_celsius()), Ok(-131));
+        battery.failing = Some(Command::Temperature);
+        assert_eq!(block_on(battery.temperature_celsius()), Err(ErrorKind::CommError));
+    }
+
+    #[test]
+    fn is_charging_follows_current_sign() {
+        let mut battery = RegisterFileBattery::new();
+        assert_eq!(block_on(battery.is_charging()), Ok(false));
+        battery.set(Command::Current, 1200);
+        assert_eq!(block_on(battery.is_charging()), Ok(true));
+        battery.set(Command::Current, (-850i16) as u16);
+        assert_eq!(block_on(battery.is_charging()), Ok(false));
+    }
+}
@@ -0,0 +1,303 @@
+//! Reads a Smart Battery over a Linux I2C/SMBus adapter and prints a short report.
+//!
+//! ```text
+//! cargo run -p embedded-batteries --example sbs_report --features std -- /dev/i2c-1
+//! ```
+
+// The driver is portable, only main() needs a Linux I2C adapter.
+#![cfg_attr(not(target_os = "linux"), allow(dead_code))]
+
+use embedded_batteries::smart_battery::{
+    self, BatteryModeFields, BatteryStatusFields, BlockQuirk, CapacityModeSignedValue, CapacityModeValue, Cycles,
+    DeciKelvin, ErrorKind, ErrorType, ManufactureDate, Minutes, Percent, SmartBattery, SpecificationInfoFields,
+};
+use embedded_batteries::{MilliAmps, MilliAmpsSigned, MilliVolts};
+use embedded_hal::i2c::I2c;
+
+/// Default SMBus address of a Smart Battery.
+const SBS_ADDRESS: u8 = 0x0B;
+
+#[derive(Debug)]
+enum GaugeError<E> {
+    I2c(E),
+    Kind(ErrorKind),
+}
+
+impl<E: core::fmt::Debug> smart_battery::Error for GaugeError<E> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::I2c(_) => ErrorKind::CommError,
+            Self::Kind(kind) => *kind,
+        }
+    }
+}
+
+impl<E> From<ErrorKind> for GaugeError<E> {
+    fn from(kind: ErrorKind) -> Self {
+        Self::Kind(kind)
+    }
+}
+
+/// SMBus Smart Battery driver implementing every SBS command with word and block transactions.
+struct SbsGauge<I2C> {
+    i2c: I2C,
+    capacity_mode: bool,
+}
+
+impl<I2C: I2c> SbsGauge<I2C> {
+    fn new(i2c: I2C) -> Result<Self, GaugeError<I2C::Error>> {
+        let mut gauge = Self {
+            i2c,
+            capacity_mode: false,
+        };
+        gauge.battery_mode()?;
+        Ok(gauge)
+    }
+
+    fn read_word(&mut self, command: u8) -> Result<u16, GaugeError<I2C::Error>> {
+        let mut buf = [0u8; 2];
+        self.i2c
+            .write_read(SBS_ADDRESS, &[command], &mut buf)
+            .map_err(GaugeError::I2c)?;
+        Ok(u16::from_le_bytes(buf))
+    }
+
+    fn write_word(&mut self, command: u8, value: u16) -> Result<(), GaugeError<I2C::Error>> {
+        let [lo, hi] = value.to_le_bytes();
+        self.i2c.write(SBS_ADDRESS, &[command, lo, hi]).map_err(GaugeError::I2c)
+    }
+
+    fn read_string(&mut self, command: u8, dst: &mut [u8]) -> Result<(), GaugeError<I2C::Error>> {
+        let mut block = [0u8; 33];
+        self.i2c
+            .write_read(SBS_ADDRESS, &[command], &mut block)
+            .map_err(GaugeError::I2c)?;
+        let data = BlockQuirk::LengthPrefixed.decode_block(&block)?;
+        smart_battery::name_fits(dst, data.len())?;
+        dst[..data.len()].copy_from_slice(data);
+        dst[data.len()] = 0;
+        Ok(())
+    }
+
+    fn read_capacity(&mut self, command: u8) -> Result<CapacityModeValue, GaugeError<I2C::Error>> {
+        let raw = self.read_word(command)?;
+        Ok(if self.capacity_mode {
+            CapacityModeValue::CentiWattUnsigned(raw)
+        } else {
+            CapacityModeValue::MilliAmpUnsigned(raw)
+        })
+    }
+
+    fn write_capacity(&mut self, command: u8, value: CapacityModeValue) -> Result<(), GaugeError<I2C::Error>> {
+        match value {
+            CapacityModeValue::MilliAmpUnsigned(raw) | CapacityModeValue::CentiWattUnsigned(raw) => {
+                self.write_word(command, raw)
+            }
+        }
+    }
+}
+
+impl<I2C: I2c> ErrorType for SbsGauge<I2C> {
+    type Error = GaugeError<I2C::Error>;
+}
+
+impl<I2C: I2c> SmartBattery for SbsGauge<I2C> {
+    fn manufacturer_access(&mut self, data: u16) -> Result<u16, Self::Error> {
+        self.write_word(0x00, data)?;
+        self.read_word(0x00)
+    }
+
+    fn remaining_capacity_alarm(&mut self) -> Result<CapacityModeValue, Self::Error> {
+        self.read_capacity(0x01)
+    }
+
+    fn set_remaining_capacity_alarm(&mut self, capacity: CapacityModeValue) -> Result<(), Self::Error> {
+        self.write_capacity(0x01, capacity)
+    }
+
+    fn remaining_time_alarm(&mut self) -> Result<Minutes, Self::Error> {
+        self.read_word(0x02)
+    }
+
+    fn set_remaining_time_alarm(&mut self, time: Minutes) -> Result<(), Self::Error> {
+        self.write_word(0x02, time)
+    }
+
+    fn battery_mode(&mut self) -> Result<BatteryModeFields, Self::Error> {
+        let mode = BatteryModeFields::from_bits(self.read_word(0x03)?);
+        self.capacity_mode = mode.capacity_mode();
+        Ok(mode)
+    }
+
+    fn set_battery_mode(&mut self, flags: BatteryModeFields) -> Result<(), Self::Error> {
+        self.write_word(0x03, flags.into_bits())?;
+        self.capacity_mode = flags.capacity_mode();
+        Ok(())
+    }
+
+    fn at_rate(&mut self) -> Result<CapacityModeSignedValue, Self::Error> {
+        let raw = self.read_word(0x04)? as i16;
+        Ok(if self.capacity_mode {
+            CapacityModeSignedValue::CentiWattSigned(raw)
+        } else {
+            CapacityModeSignedValue::MilliAmpSigned(raw)
+        })
+    }
+
+    fn set_at_rate(&mut self, rate: CapacityModeSignedValue) -> Result<(), Self::Error> {
+        match rate {
+            CapacityModeSignedValue::MilliAmpSigned(raw) | CapacityModeSignedValue::CentiWattSigned(raw) => {
+                self.write_word(0x04, raw as u16)
+            }
+        }
+    }
+
+    fn at_rate_time_to_full(&mut self) -> Result<Minutes, Self::Error> {
+        self.read_word(0x05)
+    }
+
+    fn at_rate_time_to_empty(&mut self) -> Result<Minutes, Self::Error> {
+        self.read_word(0x06)
+    }
+
+    fn at_rate_ok(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.read_word(0x07)? != 0)
+    }
+
+    fn temperature(&mut self) -> Result<DeciKelvin, Self::Error> {
+        self.read_word(0x08)
+    }
+
+    fn voltage(&mut self) -> Result<MilliVolts, Self::Error> {
+        self.read_word(0x09)
+    }
+
+    fn current(&mut self) -> Result<MilliAmpsSigned, Self::Error> {
+        Ok(self.read_word(0x0A)? as i16)
+    }
+
+    fn average_current(&mut self) -> Result<MilliAmpsSigned, Self::Error> {
+        Ok(self.read_word(0x0B)? as i16)
+    }
+
+    fn max_error(&mut self) -> Result<Percent, Self::Error> {
+        Ok(self.read_word(0x0C)? as u8)
+    }
+
+    fn relative_state_of_charge(&mut self) -> Result<Percent, Self::Error> {
+        Ok(self.read_word(0x0D)? as u8)
+    }
+
+    fn absolute_state_of_charge(&mut self) -> Result<Percent, Self::Error> {
+        Ok(self.read_word(0x0E)? as u8)
+    }
+
+    fn remaining_capacity(&mut self) -> Result<CapacityModeValue, Self::Error> {
+        self.read_capacity(0x0F)
+    }
+
+    fn full_charge_capacity(&mut self) -> Result<CapacityModeValue, Self::Error> {
+        self.read_capacity(0x10)
+    }
+
+    fn run_time_to_empty(&mut self) -> Result<Minutes, Self::Error> {
+        self.read_word(0x11)
+    }
+
+    fn average_time_to_empty(&mut self) -> Result<Minutes, Self::Error> {
+        self.read_word(0x12)
+    }
+
+    fn average_time_to_full(&mut self) -> Result<Minutes, Self::Error> {
+        self.read_word(0x13)
+    }
+
+    fn charging_current(&mut self) -> Result<MilliAmps, Self::Error> {
+        self.read_word(0x14)
+    }
+
+    fn charging_voltage(&mut self) -> Result<MilliVolts, Self::Error> {
+        self.read_word(0x15)
+    }
+
+    fn battery_status(&mut self) -> Result<BatteryStatusFields, Self::Error> {
+        Ok(BatteryStatusFields::from_bits(self.read_word(0x16)?))
+    }
+
+    fn cycle_count(&mut self) -> Result<Cycles, Self::Error> {
+        self.read_word(0x17)
+    }
+
+    fn design_capacity(&mut self) -> Result<CapacityModeValue, Self::Error> {
+        self.read_capacity(0x18)
+    }
+
+    fn design_voltage(&mut self) -> Result<MilliVolts, Self::Error> {
+        self.read_word(0x19)
+    }
+
+    fn specification_info(&mut self) -> Result<SpecificationInfoFields, Self::Error> {
+        Ok(SpecificationInfoFields::from_bits(self.read_word(0x1A)?))
+    }
+
+    fn manufacture_date(&mut self) -> Result<ManufactureDate, Self::Error> {
+        Ok(ManufactureDate::from_bits(self.read_word(0x1B)?))
+    }
+
+    fn serial_number(&mut self) -> Result<u16, Self::Error> {
+        self.read_word(0x1C)
+    }
+
+    fn manufacturer_name(&mut self, name: &mut [u8]) -> Result<(), Self::Error> {
+        self.read_string(0x20, name)
+    }
+
+    fn device_name(&mut self, name: &mut [u8]) -> Result<(), Self::Error> {
+        self.read_string(0x21, name)
+    }
+
+    fn device_chemistry(&mut self, chemistry: &mut [u8]) -> Result<(), Self::Error> {
+        self.read_string(0x22, chemistry)
+    }
+
+    fn manufacturer_data(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let mut block = [0u8; 33];
+        self.i2c
+            .write_read(SBS_ADDRESS, &[0x23], &mut block)
+            .map_err(GaugeError::I2c)?;
+        let data = BlockQuirk::LengthPrefixed.decode_block(&block)?;
+        buf.get_mut(..data.len())
+            .ok_or(ErrorKind::BatteryStatus(smart_battery::ErrorCode::BadSize))?
+            .copy_from_slice(data);
+        Ok(data.len())
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn main() {
+    let path = std::env::args().nth(1).unwrap_or_else(|| String::from("/dev/i2c-1"));
+    let i2c = linux_embedded_hal::I2cdev::new(&path).expect("failed to open I2C adapter");
+    let mut gauge = SbsGauge::new(i2c).expect("failed to read BatteryMode");
+
+    let mut name = [0u8; 33];
+    let mut serial = [0u8; 6];
+    let mut chemistry = [0u8; 33];
+    match gauge.bix_strings(&mut name, &mut serial, &mut chemistry) {
+        Ok(()) => println!(
+            "{} (serial {}, {})",
+            smart_battery::sbs_str(&name),
+            smart_battery::sbs_str(&serial),
+            smart_battery::sbs_str(&chemistry)
+        ),
+        Err(e) => println!("device strings unavailable: {e:?}"),
+    }
+
+    let mut report = String::new();
+    gauge.report(&mut report).expect("formatting into a String can't fail");
+    print!("{report}");
+}
+
+#[cfg(not(target_os = "linux"))]
+fn main() {
+    eprintln!("sbs_report needs a Linux I2C adapter (/dev/i2c-*)");
+}
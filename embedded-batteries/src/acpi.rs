@@ -1,6 +1,10 @@
 use bitflags::bitflags;
 use zerocopy::{FromBytes, Immutable, IntoBytes};
 
+use crate::charger::{Charger, ChargerBatteryError};
+use crate::smart_battery::{crc8_smbus, BatteryStatusFields, CapacityModeValue, Chemistry, Cycles, SmartBattery};
+use crate::MilliAmpsSigned;
+
 /// BST: Battery Status.
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, FromBytes, IntoBytes, Immutable)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -27,16 +31,91 @@ pub struct BstReturn {
 /// Size of BstReturn in bytes
 pub const BST_RETURN_SIZE_BYTES: usize = 16;
 
+impl From<[u8; BST_RETURN_SIZE_BYTES]> for BstReturn {
+    fn from(value: [u8; BST_RETURN_SIZE_BYTES]) -> Self {
+        zerocopy::transmute!(value)
+    }
+}
+
+impl TryFrom<&[u8]> for BstReturn {
+    type Error = AcpiSliceError;
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Ok(Self::from(slice_to_array::<BST_RETURN_SIZE_BYTES>(value)?))
+    }
+}
+
+/// Builds a _BST return value from a Smart Battery.
+///
+/// battery_present_rate is taken from current() (0x0A). Units follow the battery's CAPACITY_MODE: mA and mAh,
+/// or mW and mWh when capacity is reported in 10mWh. See `bst_from_battery_averaged()` for a smoother rate.
+pub fn bst_from_battery<B: SmartBattery>(battery: &mut B) -> Result<BstReturn, B::Error> {
+    let current = battery.current()?;
+    bst_with_current(battery, current)
+}
+
+/// Builds a _BST return value from a Smart Battery, like `bst_from_battery()`, but takes battery_present_rate
+/// from average_current() (0x0B).
+///
+/// The one-minute rolling average is smoother than the instantaneous current, which avoids a jumpy OS time
+/// estimate, but lags behind load changes.
+pub fn bst_from_battery_averaged<B: SmartBattery>(battery: &mut B) -> Result<BstReturn, B::Error> {
+    let current = battery.average_current()?;
+    bst_with_current(battery, current)
+}
+
+fn bst_with_current<B: SmartBattery>(battery: &mut B, current: MilliAmpsSigned) -> Result<BstReturn, B::Error> {
+    let battery_state = BatteryState::from(battery.battery_status()?);
+    let voltage = battery.voltage()?;
+    let current = u32::from(current.unsigned_abs());
+    let (battery_present_rate, battery_remaining_capacity) = match battery.remaining_capacity()? {
+        CapacityModeValue::MilliAmpUnsigned(capacity) => (current, u32::from(capacity)),
+        CapacityModeValue::CentiWattUnsigned(capacity) => {
+            (current * u32::from(voltage) / 1000, u32::from(capacity) * 10)
+        }
+    };
+    Ok(BstReturn {
+        battery_state,
+        battery_present_rate,
+        battery_remaining_capacity,
+        battery_present_voltage: voltage.into(),
+    })
+}
+
+/// Error type when parsing a fixed-size ACPI return value from a byte slice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AcpiSliceError {
+    /// The slice length doesn't match the size of the return value.
+    WrongLength {
+        /// Size of the return value in bytes.
+        expected: usize,
+        /// Length of the slice.
+        got: usize,
+    },
+    /// The slice has the right length but contains a value that isn't valid for the return value.
+    InvalidValue,
+}
+
+fn slice_to_array<const N: usize>(value: &[u8]) -> Result<[u8; N], AcpiSliceError> {
+    value.try_into().map_err(|_| AcpiSliceError::WrongLength {
+        expected: N,
+        got: value.len(),
+    })
+}
+
+/// Sentinel used by ACPI control methods to report an unknown or unavailable value.
+pub const ACPI_UNKNOWN: u32 = 0xFFFFFFFF;
+
 /// Battery State (BST).
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, FromBytes, IntoBytes, Immutable)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct BatteryState(u32);
 bitflags! {
     impl BatteryState: u32 {
-        /// Battery is discharging.
+        /// Battery is discharging. Must not be set together with `CHARGING`.
         const DISCHARGING = 1 << 0;
 
-        /// Battery is charging.
+        /// Battery is charging. Must not be set together with `DISCHARGING`.
         const CHARGING = 1 << 1;
 
         /// Battery is in a critical energy state.
@@ -47,6 +126,97 @@ bitflags! {
     }
 }
 
+impl BatteryState {
+    /// Returns `false` if both `DISCHARGING` and `CHARGING` are set.
+    ///
+    /// ACPI requires that a battery is never reported as charging and discharging at the same time.
+    pub const fn is_valid(&self) -> bool {
+        !self.contains(Self::DISCHARGING.union(Self::CHARGING))
+    }
+
+    /// Converts from raw bits, returning `None` if any unknown bits are set or if the state is not valid
+    /// (see `is_valid()`).
+    pub const fn from_bits_validated(bits: u32) -> Option<Self> {
+        match Self::from_bits(bits) {
+            Some(state) if state.is_valid() => Some(state),
+            _ => None,
+        }
+    }
+
+    /// Builds a battery state from a Smart Battery and the charger feeding it.
+    ///
+    /// Unlike the conversion from battery_status() (0x16) alone, CHARGING is only set if the charger is actually
+    /// delivering current. A charger reporting `charge_inhibited()` is never delivering. Otherwise the measured
+    /// output from `Charger::delivered_current()` decides, since an unpowered charger may acknowledge a charging
+    /// current setpoint without delivering anything. If the charger can't measure its output, the battery's
+    /// current() (0x0A) is used instead and a positive (charging) current counts as delivered. A battery that
+    /// isn't discharging and isn't being charged is reported with neither flag set. CRITICAL is set as in
+    /// `From<BatteryStatusFields>`.
+    pub fn from_battery_and_charger<B: SmartBattery, C: Charger>(
+        batt: &mut B,
+        charger: &mut C,
+    ) -> Result<Self, ChargerBatteryError<C::Error, B::Error>> {
+        let status = batt.battery_status().map_err(ChargerBatteryError::Battery)?;
+        let mut state = if status.discharging() {
+            Self::DISCHARGING
+        } else {
+            let delivering = if charger.charge_inhibited().map_err(ChargerBatteryError::Charger)? {
+                false
+            } else {
+                match charger.delivered_current().map_err(ChargerBatteryError::Charger)? {
+                    Some(current) => current != 0,
+                    None => batt.current().map_err(ChargerBatteryError::Battery)? > 0,
+                }
+            };
+            if delivering {
+                Self::CHARGING
+            } else {
+                Self::empty()
+            }
+        };
+        if status.fully_discharged() || status.terminate_discharge_alarm() {
+            state |= Self::CRITICAL;
+        }
+        Ok(state)
+    }
+}
+
+impl From<BatteryStatusFields> for BatteryState {
+    /// Maps an SBS battery_status() (0x16) to an ACPI battery state.
+    ///
+    /// DISCHARGING follows the SBS DISCHARGING bit. A battery that isn't discharging is reported as CHARGING
+    /// unless it is FULLY_CHARGED. CRITICAL is set if the battery is FULLY_DISCHARGED or the
+    /// TERMINATE_DISCHARGE_ALARM is raised. The result is always valid (see `BatteryState::is_valid()`).
+    fn from(status: BatteryStatusFields) -> Self {
+        let mut state = if status.discharging() {
+            Self::DISCHARGING
+        } else if !status.fully_charged() {
+            Self::CHARGING
+        } else {
+            Self::empty()
+        };
+        if status.fully_discharged() || status.terminate_discharge_alarm() {
+            state |= Self::CRITICAL;
+        }
+        state
+    }
+}
+
+impl From<BatteryState> for BatteryStatusFields {
+    /// Best-effort mapping of an ACPI battery state back to an SBS battery_status() (0x16).
+    ///
+    /// This is lossy: only DISCHARGING, FULLY_CHARGED (for a state that is neither charging nor discharging) and
+    /// FULLY_DISCHARGED (for CRITICAL) are set. CHARGE_LIMITING, the error code and all alarm bits are dropped.
+    fn from(state: BatteryState) -> Self {
+        let charging = state.contains(BatteryState::CHARGING);
+        let discharging = state.contains(BatteryState::DISCHARGING);
+        Self::new()
+            .with_discharging(discharging)
+            .with_fully_charged(!charging && !discharging)
+            .with_fully_discharged(state.contains(BatteryState::CRITICAL))
+    }
+}
+
 /// BIX: Battery Information Extended.
 ///
 /// Represents static battery information that remains constant until the battery is replaced.
@@ -109,10 +279,60 @@ pub enum BixReturnSerializeErr {
     InputSliceTooSmall,
 }
 
+/// Error type when deserializing BixReturn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BixReturnDeserializeErr {
+    /// The input slice ends before all the fields or the trailing PEC.
+    InputSliceTooSmall,
+    /// A power unit, battery technology or swapping capability field holds an undefined value.
+    InvalidValue,
+    /// The trailing PEC doesn't match the serialized bytes.
+    ChecksumMismatch,
+}
+
+/// Reasons a `BixReturn` is rejected by `BixReturn::validate()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BixValidationError {
+    /// `revision` isn't `BixReturn::REVISION`.
+    UnsupportedRevision,
+    /// `design_cap_of_low` is above `design_cap_of_warning`, so the low alarm would fire before the warning.
+    LowAboveWarning,
+    /// `battery_capacity_granularity_1` (low to warning) is larger than `battery_capacity_granularity_2`
+    /// (warning to full), which usually means the two were swapped.
+    GranularityInverted,
+}
+
+/// Returns the size in bytes of a BIX serialized by `BixReturn::to_bytes()` with the given string sizes.
+///
+/// This is 64 bytes of fixed fields, the four strings and the 4 byte battery swapping capability. Being `const`,
+/// it can size a buffer at compile time (add one byte for `to_bytes_checked()`):
+///
+/// ```
+/// use embedded_batteries::acpi::bix_serialized_size;
+///
+/// const BIX_BUFFER_LEN: usize = bix_serialized_size(16, 16, 8, 16);
+/// let buffer = [0u8; BIX_BUFFER_LEN];
+/// assert_eq!(buffer.len(), 124);
+/// ```
+pub const fn bix_serialized_size(
+    model_num_size: usize,
+    serial_num_size: usize,
+    battery_type_size: usize,
+    oem_info_size: usize,
+) -> usize {
+    64 + model_num_size + serial_num_size + battery_type_size + oem_info_size + 4
+}
+
 impl<'a> BixReturn<'a> {
+    /// Revision of the BIX structure described by this type.
+    pub const REVISION: u32 = 1;
+
     /// Serialize BIX return value, needed because BixReturn doesn't support zerocopy::IntoBytes derive.
     ///
-    /// `dst_slice` should be at least 64 + model_num_size + serial_num_size + battery_type_size + oem_info_size bytes large.
+    /// `dst_slice` should be at least `bix_serialized_size()` bytes large. Sizes whose sum overflows `usize` are
+    /// reported as `BixReturnSerializeErr::InputSliceTooSmall`.
     pub fn to_bytes(
         self,
         dst_slice: &mut [u8],
@@ -122,15 +342,18 @@ impl<'a> BixReturn<'a> {
         oem_info_size: usize,
     ) -> Result<(), BixReturnSerializeErr> {
         const MODEL_NUM_START_IDX: usize = 64;
-        let model_num_end_idx: usize = MODEL_NUM_START_IDX + model_num_size;
+        let end_idx =
+            |start: usize, size: usize| start.checked_add(size).ok_or(BixReturnSerializeErr::InputSliceTooSmall);
+        let model_num_end_idx: usize = end_idx(MODEL_NUM_START_IDX, model_num_size)?;
         let serial_num_start_idx = model_num_end_idx;
-        let serial_num_end_idx = serial_num_start_idx + serial_num_size;
+        let serial_num_end_idx = end_idx(serial_num_start_idx, serial_num_size)?;
         let battery_type_start_idx = serial_num_end_idx;
-        let battery_type_end_idx = battery_type_start_idx + battery_type_size;
+        let battery_type_end_idx = end_idx(battery_type_start_idx, battery_type_size)?;
         let oem_info_start_idx = battery_type_end_idx;
-        let oem_info_end_idx = oem_info_start_idx + oem_info_size;
+        let oem_info_end_idx = end_idx(oem_info_start_idx, oem_info_size)?;
+        let swap_capability_end_idx = end_idx(oem_info_end_idx, 4)?;
 
-        if dst_slice.len() < oem_info_end_idx {
+        if dst_slice.len() < swap_capability_end_idx {
             return Err(BixReturnSerializeErr::InputSliceTooSmall);
         }
 
@@ -162,10 +385,360 @@ impl<'a> BixReturn<'a> {
         dst_slice[serial_num_start_idx..serial_num_end_idx].copy_from_slice(self.serial_number);
         dst_slice[battery_type_start_idx..battery_type_end_idx].copy_from_slice(self.battery_type);
         dst_slice[oem_info_start_idx..oem_info_end_idx].copy_from_slice(self.oem_info);
-        dst_slice[oem_info_end_idx..oem_info_end_idx + 4]
+        dst_slice[oem_info_end_idx..swap_capability_end_idx]
             .copy_from_slice(&u32::to_le_bytes(self.battery_swapping_capability.into()));
         Ok(())
     }
+
+    /// Serializes the BIX like `to_bytes()`, followed by a 1-byte SMBus PEC (see `crc8_smbus()`) over the
+    /// serialized bytes.
+    ///
+    /// This framing isn't defined by ACPI and is only meant for internal transports (e.g. an EC mailbox) where
+    /// corruption must be detected. `dst_slice` must be one byte larger than for `to_bytes()`. Decode the result
+    /// with `from_bytes_checked()` and the same string sizes.
+    pub fn to_bytes_checked(
+        self,
+        dst_slice: &mut [u8],
+        model_num_size: usize,
+        serial_num_size: usize,
+        battery_type_size: usize,
+        oem_info_size: usize,
+    ) -> Result<(), BixReturnSerializeErr> {
+        let len = [model_num_size, serial_num_size, battery_type_size, oem_info_size, 4]
+            .into_iter()
+            .try_fold(64usize, usize::checked_add)
+            .ok_or(BixReturnSerializeErr::InputSliceTooSmall)?;
+        if dst_slice.len() <= len {
+            return Err(BixReturnSerializeErr::InputSliceTooSmall);
+        }
+        self.to_bytes(
+            &mut dst_slice[..len],
+            model_num_size,
+            serial_num_size,
+            battery_type_size,
+            oem_info_size,
+        )?;
+        dst_slice[len] = crc8_smbus(&dst_slice[..len]);
+        Ok(())
+    }
+
+    /// Deserializes a BIX written by `to_bytes_checked()` with the same string sizes, verifying its trailing PEC.
+    ///
+    /// The strings borrow from `src` and are exactly as wide as on the sending side, so NUL padding (e.g. from
+    /// `asciiz_field()`) is preserved. Sizes whose sum overflows `usize` are reported as
+    /// `BixReturnDeserializeErr::InputSliceTooSmall`. Bytes after the PEC are ignored.
+    pub fn from_bytes_checked(
+        src: &'a [u8],
+        model_num_size: usize,
+        serial_num_size: usize,
+        battery_type_size: usize,
+        oem_info_size: usize,
+    ) -> Result<Self, BixReturnDeserializeErr> {
+        const MODEL_NUM_START_IDX: usize = 64;
+        let word = |idx: usize| -> Result<u32, BixReturnDeserializeErr> {
+            src.get(idx..idx + 4)
+                .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                .ok_or(BixReturnDeserializeErr::InputSliceTooSmall)
+        };
+        let end_idx = |start: usize, size: usize| {
+            start
+                .checked_add(size)
+                .ok_or(BixReturnDeserializeErr::InputSliceTooSmall)
+        };
+        let model_num_end_idx = end_idx(MODEL_NUM_START_IDX, model_num_size)?;
+        let serial_num_end_idx = end_idx(model_num_end_idx, serial_num_size)?;
+        let battery_type_end_idx = end_idx(serial_num_end_idx, battery_type_size)?;
+        let oem_info_end_idx = end_idx(battery_type_end_idx, oem_info_size)?;
+        let pec_idx = end_idx(oem_info_end_idx, 4)?;
+
+        let pec = *src.get(pec_idx).ok_or(BixReturnDeserializeErr::InputSliceTooSmall)?;
+        if crc8_smbus(&src[..pec_idx]) != pec {
+            return Err(BixReturnDeserializeErr::ChecksumMismatch);
+        }
+        let model_number = &src[MODEL_NUM_START_IDX..model_num_end_idx];
+        let serial_number = &src[model_num_end_idx..serial_num_end_idx];
+        let battery_type = &src[serial_num_end_idx..battery_type_end_idx];
+        let oem_info = &src[battery_type_end_idx..oem_info_end_idx];
+        let swap_capability = word(oem_info_end_idx)?;
+
+        let invalid = |()| BixReturnDeserializeErr::InvalidValue;
+        Ok(Self {
+            revision: word(0)?,
+            power_unit: PowerUnit::try_from(word(4)?).map_err(invalid)?,
+            design_capacity: word(8)?,
+            last_full_charge_capacity: word(12)?,
+            battery_technology: BatteryTechnology::try_from(word(16)?).map_err(invalid)?,
+            design_voltage: word(20)?,
+            design_cap_of_warning: word(24)?,
+            design_cap_of_low: word(28)?,
+            cycle_count: word(32)?,
+            measurement_accuracy: word(36)?,
+            max_sampling_time: word(40)?,
+            min_sampling_time: word(44)?,
+            max_averaging_interval: word(48)?,
+            min_averaging_interval: word(52)?,
+            battery_capacity_granularity_1: word(56)?,
+            battery_capacity_granularity_2: word(60)?,
+            model_number,
+            serial_number,
+            battery_type,
+            oem_info,
+            battery_swapping_capability: BatterySwapCapability::try_from(swap_capability).map_err(invalid)?,
+        })
+    }
+
+    /// Returns `design_cap_of_warning` as a percentage of `design_capacity`.
+    ///
+    /// Rounds to the nearest percent. Returns `None` if either value is unknown, if `design_capacity` is 0, or if
+    /// the threshold exceeds 255%.
+    pub fn warning_percent(&self) -> Option<u8> {
+        Self::percent_of_design(self.design_cap_of_warning, self.design_capacity)
+    }
+
+    /// Returns `design_cap_of_low` as a percentage of `design_capacity`.
+    ///
+    /// Rounds to the nearest percent. Returns `None` if either value is unknown, if `design_capacity` is 0, or if
+    /// the threshold exceeds 255%.
+    pub fn low_percent(&self) -> Option<u8> {
+        Self::percent_of_design(self.design_cap_of_low, self.design_capacity)
+    }
+
+    /// Sets `design_cap_of_warning` to `percent` of `design_capacity`, rounding down.
+    ///
+    /// If `design_capacity` is unknown, the threshold is set to unknown as well.
+    pub fn set_warning_percent(&mut self, percent: u8) {
+        self.design_cap_of_warning = Self::from_percent_of_design(percent, self.design_capacity);
+    }
+
+    /// Sets `design_cap_of_low` to `percent` of `design_capacity`, rounding down.
+    ///
+    /// If `design_capacity` is unknown, the threshold is set to unknown as well.
+    pub fn set_low_percent(&mut self, percent: u8) {
+        self.design_cap_of_low = Self::from_percent_of_design(percent, self.design_capacity);
+    }
+
+    /// Returns the BIX with `cycle_count` set to `cycles`, leaving all other fields unchanged.
+    ///
+    /// Bridges should refresh this from a live SBS cycle_count() (0x17) read each time _BIX is evaluated, rather
+    /// than from a snapshot taken when the battery was inserted, since it keeps increasing while the battery is
+    /// installed. A cycle count of 0xFFFF, which gauges report when they don't track cycles, is mapped to
+    /// `ACPI_UNKNOWN`.
+    pub fn with_cycle_count(mut self, cycles: Cycles) -> Self {
+        self.cycle_count = match cycles {
+            Cycles::MAX => ACPI_UNKNOWN,
+            cycles => cycles.into(),
+        };
+        self
+    }
+
+    /// Checks the BIX for integration mistakes that commonly confuse OSPM.
+    ///
+    /// `power_unit` is always valid since it is a `PowerUnit`. Comparisons involving a field set to
+    /// `ACPI_UNKNOWN` are skipped.
+    pub const fn validate(&self) -> Result<(), BixValidationError> {
+        if self.revision != Self::REVISION {
+            return Err(BixValidationError::UnsupportedRevision);
+        }
+        if self.design_cap_of_low != ACPI_UNKNOWN
+            && self.design_cap_of_warning != ACPI_UNKNOWN
+            && self.design_cap_of_low > self.design_cap_of_warning
+        {
+            return Err(BixValidationError::LowAboveWarning);
+        }
+        if self.battery_capacity_granularity_1 != ACPI_UNKNOWN
+            && self.battery_capacity_granularity_2 != ACPI_UNKNOWN
+            && self.battery_capacity_granularity_1 > self.battery_capacity_granularity_2
+        {
+            return Err(BixValidationError::GranularityInverted);
+        }
+        Ok(())
+    }
+
+    /// Heuristically checks that `design_voltage` is plausible for `battery_technology`.
+    ///
+    /// The voltage is plausible if it is within 5% of a whole number of cells of a common chemistry for the
+    /// technology: alkaline (1.5V) or lithium (3.0V) cells for primary batteries, and the rechargeable
+    /// `Chemistry` nominal voltages (e.g. 1.2V NiMH, 3.6V Li-ion) for secondary ones. For example, a primary
+    /// battery reporting a 3S Li-ion 11.1V is flagged. Because the cell voltages overlap at many pack voltages,
+    /// a `true` result doesn't prove the BIX is correct. An unknown or 0 design voltage is always plausible.
+    pub fn technology_voltage_plausible(&self) -> bool {
+        if self.design_voltage == ACPI_UNKNOWN || self.design_voltage == 0 {
+            return true;
+        }
+        let voltage = u64::from(self.design_voltage);
+        let near_cell_multiple = |cell: u64| {
+            let count = ((voltage + cell / 2) / cell).max(1);
+            let expected = count * cell;
+            expected.abs_diff(voltage) * 20 <= expected
+        };
+        match self.battery_technology {
+            BatteryTechnology::Primary => [1500, 3000].into_iter().any(near_cell_multiple),
+            BatteryTechnology::Secondary => [
+                Chemistry::LeadAcid,
+                Chemistry::LithiumIon,
+                Chemistry::LithiumPolymer,
+                Chemistry::NickelCadmium,
+                Chemistry::NickelMetalHydride,
+                Chemistry::NickelZinc,
+                Chemistry::RechargeableAlkalineManganese,
+            ]
+            .into_iter()
+            .any(|chemistry| near_cell_multiple(u64::from(chemistry.nominal_cell_voltage()))),
+        }
+    }
+
+    fn percent_of_design(threshold: u32, design_capacity: u32) -> Option<u8> {
+        if threshold == ACPI_UNKNOWN || design_capacity == ACPI_UNKNOWN || design_capacity == 0 {
+            return None;
+        }
+        u8::try_from((u32::from(crate::permille_u32(threshold, design_capacity)) + 5) / 10).ok()
+    }
+
+    fn from_percent_of_design(percent: u8, design_capacity: u32) -> u32 {
+        if design_capacity == ACPI_UNKNOWN {
+            return ACPI_UNKNOWN;
+        }
+        // Saturate below the sentinel so a large percentage is never mistaken for unknown.
+        (u64::from(design_capacity) * u64::from(percent) / 100).min(u64::from(ACPI_UNKNOWN - 1)) as u32
+    }
+}
+
+/// BIX return value that owns its strings, for storage without a borrowed backing buffer (e.g. in a `static`).
+///
+/// The model number, serial number, battery type and OEM info strings are stored back to back in `strings`,
+/// which must be at least as large as their combined length. Convert from a `BixReturn` with `TryFrom` and back
+/// with `as_bix()`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BixReturnOwned<const S: usize> {
+    /// Revision of the BIX structure. Current revision is 1.
+    pub revision: u32,
+    /// Unit used for capacity and rate values.
+    pub power_unit: PowerUnit,
+    /// Design capacity of the battery (in mWh or mAh).
+    pub design_capacity: u32,
+    /// Last full charge capacity (in mWh or mAh).
+    pub last_full_charge_capacity: u32,
+    /// Battery technology type.
+    pub battery_technology: BatteryTechnology,
+    /// Design voltage (in mV).
+    pub design_voltage: u32,
+    /// Warning capacity threshold (in mWh or mAh).
+    pub design_cap_of_warning: u32,
+    /// Low capacity threshold (in mWh or mAh).
+    pub design_cap_of_low: u32,
+    /// Number of charge/discharge cycles.
+    pub cycle_count: u32,
+    /// Measurement accuracy in thousandths of a percent (e.g., 80000 = 80.000%).
+    pub measurement_accuracy: u32,
+    /// Maximum supported sampling time (in ms).
+    pub max_sampling_time: u32,
+    /// Minimum supported sampling time (in ms).
+    pub min_sampling_time: u32,
+    /// Maximum supported averaging interval (in ms).
+    pub max_averaging_interval: u32,
+    /// Minimum supported averaging interval (in ms).
+    pub min_averaging_interval: u32,
+    /// Capacity granularity between low and warning (in mWh or mAh).
+    pub battery_capacity_granularity_1: u32,
+    /// Capacity granularity between warning and full (in mWh or mAh).
+    pub battery_capacity_granularity_2: u32,
+    /// Battery swapping capability.
+    pub battery_swapping_capability: BatterySwapCapability,
+    strings: [u8; S],
+    model_number_len: usize,
+    serial_number_len: usize,
+    battery_type_len: usize,
+    oem_info_len: usize,
+}
+
+impl<const S: usize> BixReturnOwned<S> {
+    /// Borrows this value as a `BixReturn`, e.g. to serialize it with `BixReturn::to_bytes()`.
+    pub fn as_bix(&self) -> BixReturn<'_> {
+        let serial_number_start = self.model_number_len;
+        let battery_type_start = serial_number_start + self.serial_number_len;
+        let oem_info_start = battery_type_start + self.battery_type_len;
+        let oem_info_end = oem_info_start + self.oem_info_len;
+
+        BixReturn {
+            revision: self.revision,
+            power_unit: self.power_unit,
+            design_capacity: self.design_capacity,
+            last_full_charge_capacity: self.last_full_charge_capacity,
+            battery_technology: self.battery_technology,
+            design_voltage: self.design_voltage,
+            design_cap_of_warning: self.design_cap_of_warning,
+            design_cap_of_low: self.design_cap_of_low,
+            cycle_count: self.cycle_count,
+            measurement_accuracy: self.measurement_accuracy,
+            max_sampling_time: self.max_sampling_time,
+            min_sampling_time: self.min_sampling_time,
+            max_averaging_interval: self.max_averaging_interval,
+            min_averaging_interval: self.min_averaging_interval,
+            battery_capacity_granularity_1: self.battery_capacity_granularity_1,
+            battery_capacity_granularity_2: self.battery_capacity_granularity_2,
+            model_number: &self.strings[..serial_number_start],
+            serial_number: &self.strings[serial_number_start..battery_type_start],
+            battery_type: &self.strings[battery_type_start..oem_info_start],
+            oem_info: &self.strings[oem_info_start..oem_info_end],
+            battery_swapping_capability: self.battery_swapping_capability,
+        }
+    }
+}
+
+impl<const S: usize> TryFrom<&BixReturn<'_>> for BixReturnOwned<S> {
+    type Error = BixReturnSerializeErr;
+
+    /// Copies `value` and its strings, returning `BixReturnSerializeErr::InputSliceTooSmall` if the strings
+    /// don't fit in `S` bytes.
+    fn try_from(value: &BixReturn<'_>) -> Result<Self, Self::Error> {
+        let mut strings = [0u8; S];
+        let mut offset = 0;
+        for src in [
+            value.model_number,
+            value.serial_number,
+            value.battery_type,
+            value.oem_info,
+        ] {
+            let end = offset + src.len();
+            strings
+                .get_mut(offset..end)
+                .ok_or(BixReturnSerializeErr::InputSliceTooSmall)?
+                .copy_from_slice(src);
+            offset = end;
+        }
+
+        Ok(Self {
+            revision: value.revision,
+            power_unit: value.power_unit,
+            design_capacity: value.design_capacity,
+            last_full_charge_capacity: value.last_full_charge_capacity,
+            battery_technology: value.battery_technology,
+            design_voltage: value.design_voltage,
+            design_cap_of_warning: value.design_cap_of_warning,
+            design_cap_of_low: value.design_cap_of_low,
+            cycle_count: value.cycle_count,
+            measurement_accuracy: value.measurement_accuracy,
+            max_sampling_time: value.max_sampling_time,
+            min_sampling_time: value.min_sampling_time,
+            max_averaging_interval: value.max_averaging_interval,
+            min_averaging_interval: value.min_averaging_interval,
+            battery_capacity_granularity_1: value.battery_capacity_granularity_1,
+            battery_capacity_granularity_2: value.battery_capacity_granularity_2,
+            battery_swapping_capability: value.battery_swapping_capability,
+            strings,
+            model_number_len: value.model_number.len(),
+            serial_number_len: value.serial_number.len(),
+            battery_type_len: value.battery_type.len(),
+            oem_info_len: value.oem_info.len(),
+        })
+    }
+}
+
+impl<'a, const S: usize> From<&'a BixReturnOwned<S>> for BixReturn<'a> {
+    fn from(value: &'a BixReturnOwned<S>) -> Self {
+        value.as_bix()
+    }
 }
 
 /// Power Unit.
@@ -282,6 +855,22 @@ pub struct PsrReturn {
 /// Size of PsrReturn in bytes
 pub const PSR_RETURN_SIZE_BYTES: usize = 4;
 
+impl TryFrom<[u8; PSR_RETURN_SIZE_BYTES]> for PsrReturn {
+    type Error = ();
+    fn try_from(value: [u8; PSR_RETURN_SIZE_BYTES]) -> Result<Self, Self::Error> {
+        Ok(Self {
+            power_source: PowerSource::try_from(u32::from_le_bytes(value))?,
+        })
+    }
+}
+
+impl TryFrom<&[u8]> for PsrReturn {
+    type Error = AcpiSliceError;
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Self::try_from(slice_to_array::<PSR_RETURN_SIZE_BYTES>(value)?).map_err(|_| AcpiSliceError::InvalidValue)
+    }
+}
+
 /// Result of a _PSR query.
 ///
 /// Indicates whether the power source is currently supplying power to the system
@@ -357,6 +946,7 @@ impl<'a> Pif<'a> {
     /// Serialize PIF return value, needed because Pif doesn't support zerocopy::IntoBytes derive.
     ///
     /// `dst_slice` should be at least 12 + model_num_size + serial_num_size + oem_info_size bytes large.
+    /// Sizes whose sum overflows `usize` are reported as `PifSerializeErr::InputSliceTooSmall`.
     pub fn to_bytes(
         self,
         dst_slice: &mut [u8],
@@ -365,11 +955,12 @@ impl<'a> Pif<'a> {
         oem_info_size: usize,
     ) -> Result<(), PifSerializeErr> {
         const MODEL_NUM_START_IDX: usize = 12;
-        let model_num_end_idx: usize = MODEL_NUM_START_IDX + model_num_size;
+        let end_idx = |start: usize, size: usize| start.checked_add(size).ok_or(PifSerializeErr::InputSliceTooSmall);
+        let model_num_end_idx: usize = end_idx(MODEL_NUM_START_IDX, model_num_size)?;
         let serial_num_start_idx = model_num_end_idx;
-        let serial_num_end_idx = serial_num_start_idx + serial_num_size;
+        let serial_num_end_idx = end_idx(serial_num_start_idx, serial_num_size)?;
         let oem_info_start_idx = serial_num_end_idx;
-        let oem_info_end_idx = oem_info_start_idx + oem_info_size;
+        let oem_info_end_idx = end_idx(oem_info_start_idx, oem_info_size)?;
 
         if dst_slice.len() < oem_info_end_idx {
             return Err(PifSerializeErr::InputSliceTooSmall);
@@ -447,6 +1038,19 @@ pub struct Bps {
 /// Size of BpsReturn in bytes
 pub const BPS_RETURN_SIZE_BYTES: usize = 20;
 
+impl From<[u8; BPS_RETURN_SIZE_BYTES]> for Bps {
+    fn from(value: [u8; BPS_RETURN_SIZE_BYTES]) -> Self {
+        zerocopy::transmute!(value)
+    }
+}
+
+impl TryFrom<&[u8]> for Bps {
+    type Error = AcpiSliceError;
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Ok(Self::from(slice_to_array::<BPS_RETURN_SIZE_BYTES>(value)?))
+    }
+}
+
 /// BTP: Battery Trip Point.
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -456,6 +1060,40 @@ pub struct Btp {
     pub trip_point: u32,
 }
 
+/// Reasons a `Btp` trip point is rejected by `Btp::validate()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BtpError {
+    /// The trip point is in the reserved range above 0x7FFFFFFF.
+    Reserved,
+    /// The trip point is above the battery's design capacity and can never be reached.
+    AboveDesignCapacity,
+    /// The trip point equals the present remaining capacity, so it would fire immediately.
+    AtCurrentCapacity,
+}
+
+impl Btp {
+    /// Validates the trip point against the battery's present remaining capacity and design capacity, both in
+    /// the same units as the trip point.
+    ///
+    /// A trip point of 0 clears the trip point and always validates. The present capacity check is skipped if
+    /// `current_capacity` is `ACPI_UNKNOWN`.
+    ///
+    /// Trip points below (or above) the present capacity are accepted: ACPI notifies when the remaining capacity
+    /// crosses the trip point in either direction, so such a trip point fires later, once the battery has
+    /// discharged (or charged) past it. Only a trip point equal to the present capacity has no crossing ahead of
+    /// it and is rejected.
+    pub const fn validate(&self, current_capacity: u32, design_capacity: u32) -> Result<(), BtpError> {
+        match self.trip_point {
+            0 => Ok(()),
+            0x80000000.. => Err(BtpError::Reserved),
+            trip_point if trip_point > design_capacity => Err(BtpError::AboveDesignCapacity),
+            trip_point if trip_point == current_capacity => Err(BtpError::AtCurrentCapacity),
+            _ => Ok(()),
+        }
+    }
+}
+
 /// BPT: Battery Power Threshold Configuration.
 ///
 /// Represents a request to set or clear battery power delivery capability thresholds.
@@ -480,6 +1118,44 @@ pub struct Bpt {
     pub threshold_value: u32,
 }
 
+impl Bpt {
+    /// BPT structure revision defined by this version of the specification.
+    pub const REVISION: u32 = 1;
+
+    /// Creates a request to clear all threshold trip points.
+    pub const fn clear_all() -> Self {
+        Self {
+            revision: Self::REVISION,
+            threshold_id: ThresholdId::ClearAll,
+            threshold_value: 0,
+        }
+    }
+
+    /// Creates a request to set the Instantaneous Peak Power Threshold to `value` (in mW or mA).
+    pub const fn instantaneous(value: u32) -> Self {
+        Self {
+            revision: Self::REVISION,
+            threshold_id: ThresholdId::InstantaneousPeakPower,
+            threshold_value: value,
+        }
+    }
+
+    /// Creates a request to set the Sustainable Peak Power Threshold to `value` (in mW or mA).
+    pub const fn sustainable(value: u32) -> Self {
+        Self {
+            revision: Self::REVISION,
+            threshold_id: ThresholdId::SustainablePeakPower,
+            threshold_value: value,
+        }
+    }
+
+    /// Returns `true` if the revision is supported and, for `ThresholdId::ClearAll`, the threshold value is 0.
+    pub const fn is_valid(&self) -> bool {
+        self.revision == Self::REVISION
+            && !(matches!(self.threshold_id, ThresholdId::ClearAll) && self.threshold_value != 0)
+    }
+}
+
 /// Enum representing the threshold type for battery power delivery capability.
 #[repr(u32)]
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
@@ -565,6 +1241,19 @@ pub struct Bpc {
 /// Size of BpcReturn in bytes
 pub const BPC_RETURN_SIZE_BYTES: usize = 16;
 
+impl From<[u8; BPC_RETURN_SIZE_BYTES]> for Bpc {
+    fn from(value: [u8; BPC_RETURN_SIZE_BYTES]) -> Self {
+        zerocopy::transmute!(value)
+    }
+}
+
+impl TryFrom<&[u8]> for Bpc {
+    type Error = AcpiSliceError;
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Ok(Self::from(slice_to_array::<BPC_RETURN_SIZE_BYTES>(value)?))
+    }
+}
+
 /// Bitflags representing the power threshold support capabilities of the platform firmware.
 ///
 /// These values are encoded in the lower two bits of the `Power Threshold Support` field.
@@ -610,6 +1299,21 @@ bitflags! {
     }
 }
 
+impl BmcControlFlags {
+    /// Returns the control flags a host should apply for the alarms set in `status`.
+    ///
+    /// DISABLE_CHARGING is recommended when OVER_CHARGED, OVER_TEMP or TERMINATE_CHARGE is set, and no flags
+    /// otherwise. This is advisory only: the battery and charger enforce their own limits, and the host remains
+    /// responsible for clearing the flags once the alarms clear.
+    pub const fn recommended_for(status: BatteryStatusFields) -> Self {
+        if status.over_charged_alarm() || status.over_temp_alarm() || status.terminate_charge_alarm() {
+            Self::DISABLE_CHARGING
+        } else {
+            Self::empty()
+        }
+    }
+}
+
 /// BMD: Battery Maintenance Data.
 ///
 /// Contains information about the battery’s capabilities and current state
@@ -647,6 +1351,63 @@ pub struct Bmd {
 /// Size of BmdReturn in bytes
 pub const BMD_RETURN_SIZE_BYTES: usize = 20;
 
+impl From<[u8; BMD_RETURN_SIZE_BYTES]> for Bmd {
+    fn from(value: [u8; BMD_RETURN_SIZE_BYTES]) -> Self {
+        zerocopy::transmute!(value)
+    }
+}
+
+impl TryFrom<&[u8]> for Bmd {
+    type Error = AcpiSliceError;
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Ok(Self::from(slice_to_array::<BMD_RETURN_SIZE_BYTES>(value)?))
+    }
+}
+
+/// Decoded `Bmd` recalibration time.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RecalibrateTime {
+    /// The field is 0: for the quick time, standby isn't supported; for the slow time, calibration may not be
+    /// successful.
+    NotSupported,
+    /// Estimated time to recalibrate, in seconds.
+    Seconds(u32),
+    /// The field is 0xFFFFFFFF: the time is unknown.
+    Unknown,
+}
+
+impl From<u32> for RecalibrateTime {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => Self::NotSupported,
+            ACPI_UNKNOWN => Self::Unknown,
+            seconds => Self::Seconds(seconds),
+        }
+    }
+}
+
+impl Bmd {
+    /// Returns the decoded `quick_recalibrate_time`.
+    pub fn quick_recalibrate(&self) -> RecalibrateTime {
+        self.quick_recalibrate_time.into()
+    }
+
+    /// Returns the decoded `slow_recalibrate_time`.
+    pub fn slow_recalibrate(&self) -> RecalibrateTime {
+        self.slow_recalibrate_time.into()
+    }
+
+    /// Returns the number of battery cycles after which a recalibration is recommended, or `None` if the
+    /// battery should only be calibrated when the status flags request it (`recalibrate_count` is 0).
+    pub const fn recalibrate_after_cycles(&self) -> Option<u32> {
+        match self.recalibrate_count {
+            0 => None,
+            cycles => Some(cycles),
+        }
+    }
+}
+
 /// Status Flags returned by _BMD.
 ///
 /// These indicate the current state of battery maintenance operations.
@@ -706,30 +1467,149 @@ bitflags! {
     }
 }
 
-/// BCT: Battery Charge Time.
-///
-/// Represents a request to estimate the time required to charge the battery
-/// to a specified percentage of its Last Full Charge Capacity.
+/// State of an AML-controlled calibration driven by `CalibrationFlow`.
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
-pub struct Bct {
-    /// Target charge level as a percentage of Last Full Charge Capacity (1–100).
-    ///
-    /// For example, `96` means 96% of full charge.
-    pub charge_level_percent: u32,
+pub enum CalibrationState {
+    /// No calibration has been requested.
+    #[default]
+    Idle,
+    /// A calibration cycle was requested, waiting for `_BMD` to report AML_CALIBRATION_ACTIVE.
+    Requested,
+    /// The calibration cycle is running.
+    Active,
+    /// The calibration cycle ended and the battery no longer reports RECALIBRATION_NEEDED.
+    Complete,
+    /// The calibration is unsupported, could not be started, or ended without clearing RECALIBRATION_NEEDED.
+    Failed,
 }
 
-/// Result of a _BCT query.
+/// Helper running an AML-controlled calibration cycle through `_BMC` and `_BMD`.
 ///
-/// This enum represents the possible return values from the `_BCT` method.
-#[repr(u32)]
+/// Call `request()` with the latest `_BMD` read to start, then feed each `_BMD` read to `step()` and send
+/// `command()` to `_BMC` whenever the state changes. If the battery requires a full charge before calibration
+/// (FULL_CHARGE_BEFORE_CALIBRATION), Battery Charge Limiting is suspended for the duration of the cycle, and the
+/// calibration fails if a thermal or protection lock prevents that, whether the lock is reported before or during
+/// the cycle.
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
-pub enum BctReturnResult {
-    /// The requested charge level is invalid (less than current or greater than 100%).
-    InvalidTarget = 0x00000000,
+pub struct CalibrationFlow {
+    state: CalibrationState,
+    full_charge_required: bool,
+}
 
-    /// Estimated time in seconds to reach the target charge level.
+impl CalibrationFlow {
+    /// Creates an idle calibration flow.
+    pub const fn new() -> Self {
+        Self {
+            state: CalibrationState::Idle,
+            full_charge_required: false,
+        }
+    }
+
+    /// Returns the current state.
+    pub const fn state(&self) -> CalibrationState {
+        self.state
+    }
+
+    /// Requests a calibration cycle, using `bmd` to determine whether a full charge is required first. Has no
+    /// effect unless the flow is idle.
+    pub fn request(&mut self, bmd: &Bmd) {
+        if self.state == CalibrationState::Idle {
+            self.state = CalibrationState::Requested;
+            self.full_charge_required = bmd
+                .capability_flags
+                .contains(BmdCapabilityFlags::FULL_CHARGE_BEFORE_CALIBRATION);
+        }
+    }
+
+    /// Returns the flow to idle, e.g. after `Complete` or `Failed`. Send `command()` afterwards to end any cycle
+    /// still running.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Advances the flow based on the latest `_BMD` read and returns the new state.
+    pub fn step(&mut self, bmd: &Bmd) -> CalibrationState {
+        let status = bmd.status_flags;
+        let capabilities = bmd.capability_flags;
+
+        let locked = self.full_charge_required
+            && status.intersects(
+                BmdStatusFlags::CHARGE_LIMIT_THERMAL_LOCK.union(BmdStatusFlags::CHARGE_LIMIT_PROTECTION_LOCK),
+            );
+
+        self.state = match self.state {
+            CalibrationState::Requested => {
+                if !capabilities.contains(BmdCapabilityFlags::AML_CALIBRATION_SUPPORTED) || locked {
+                    CalibrationState::Failed
+                } else if status.contains(BmdStatusFlags::AML_CALIBRATION_ACTIVE) {
+                    CalibrationState::Active
+                } else {
+                    CalibrationState::Requested
+                }
+            }
+            CalibrationState::Active => {
+                if locked {
+                    CalibrationState::Failed
+                } else if status.contains(BmdStatusFlags::AML_CALIBRATION_ACTIVE) {
+                    CalibrationState::Active
+                } else if status.contains(BmdStatusFlags::RECALIBRATION_NEEDED) {
+                    CalibrationState::Failed
+                } else {
+                    CalibrationState::Complete
+                }
+            }
+            state => state,
+        };
+        self.state
+    }
+
+    /// Returns the `_BMC` command matching the current state.
+    ///
+    /// While `Requested` or `Active` this sets CALIBRATION_CYCLE (and SUSPEND_CHARGE_LIMITING if a full charge is
+    /// required). In all other states it clears all control flags, ending any calibration cycle.
+    pub fn command(&self) -> Bmc {
+        let maintenance_control_flags = match self.state {
+            CalibrationState::Requested | CalibrationState::Active if self.full_charge_required => {
+                BmcControlFlags::CALIBRATION_CYCLE | BmcControlFlags::SUSPEND_CHARGE_LIMITING
+            }
+            CalibrationState::Requested | CalibrationState::Active => BmcControlFlags::CALIBRATION_CYCLE,
+            _ => BmcControlFlags::empty(),
+        };
+        Bmc {
+            maintenance_control_flags,
+        }
+    }
+}
+
+/// BCT: Battery Charge Time.
+///
+/// Represents a request to estimate the time required to charge the battery
+/// to a specified percentage of its Last Full Charge Capacity.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Bct {
+    /// Target charge level as a percentage of Last Full Charge Capacity (1–100).
+    ///
+    /// For example, `96` means 96% of full charge.
+    pub charge_level_percent: u32,
+}
+
+/// Result of a _BCT query.
+///
+/// This enum represents the possible return values from the `_BCT` method.
+#[repr(u32)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BctReturnResult {
+    /// The requested charge level is invalid (less than current or greater than 100%).
+    InvalidTarget = 0x00000000,
+
+    /// Estimated time in seconds to reach the target charge level.
+    ///
+    /// `EstimatedTime(0)` and `EstimatedTime(0xFFFFFFFF)` are not representable on the wire, as they serialize
+    /// to `InvalidTarget` and `Unknown` respectively. Use `BctReturnResult::estimated()` to construct this variant.
     EstimatedTime(u32),
 
     /// Charging time is unknown.
@@ -737,6 +1617,18 @@ pub enum BctReturnResult {
     Unknown = 0xFFFFFFFF,
 }
 
+impl BctReturnResult {
+    /// Creates an `EstimatedTime` result, clamping `seconds` to `1..=0xFFFFFFFE` so it can't collide with
+    /// `InvalidTarget` or `Unknown` when serialized.
+    pub const fn estimated(seconds: u32) -> Self {
+        match seconds {
+            0 => Self::EstimatedTime(1),
+            0xFFFFFFFF => Self::EstimatedTime(0xFFFFFFFE),
+            seconds => Self::EstimatedTime(seconds),
+        }
+    }
+}
+
 /// Size of BctReturnResult in bytes
 pub const BCT_RETURN_SIZE_BYTES: usize = 4;
 
@@ -791,6 +1683,10 @@ pub enum BtmReturnResult {
     RateTooHighOrBatteryCritical = 0x00000000,
 
     /// Estimated runtime in seconds.
+    ///
+    /// `EstimatedRuntime(0)` and `EstimatedRuntime(0xFFFFFFFF)` are not representable on the wire, as they
+    /// serialize to `RateTooHighOrBatteryCritical` and `Unknown` respectively. Use `BtmReturnResult::runtime()`
+    /// to construct this variant.
     EstimatedRuntime(u32),
 
     /// Runtime is unknown.
@@ -798,6 +1694,21 @@ pub enum BtmReturnResult {
     Unknown = 0xFFFFFFFF,
 }
 
+impl BtmReturnResult {
+    /// Creates a runtime result from an estimate in seconds.
+    ///
+    /// A runtime of 0 seconds means the battery can't sustain the rate, so per the spec it maps to
+    /// `RateTooHighOrBatteryCritical`. Estimates of 0xFFFFFFFF seconds are clamped to 0xFFFFFFFE so they
+    /// aren't mistaken for `Unknown`.
+    pub const fn runtime(seconds: u32) -> Self {
+        match seconds {
+            0 => Self::RateTooHighOrBatteryCritical,
+            0xFFFFFFFF => Self::EstimatedRuntime(0xFFFFFFFE),
+            seconds => Self::EstimatedRuntime(seconds),
+        }
+    }
+}
+
 /// Size of BtmReturnResult in bytes
 pub const BTM_RETURN_SIZE_BYTES: usize = 4;
 
@@ -926,3 +1837,1414 @@ bitflags! {
 
 /// Size of StaReturn in bytes
 pub const STA_RETURN_SIZE_BYTES: usize = 4;
+
+impl From<[u8; STA_RETURN_SIZE_BYTES]> for StaReturn {
+    fn from(value: [u8; STA_RETURN_SIZE_BYTES]) -> Self {
+        zerocopy::transmute!(value)
+    }
+}
+
+impl TryFrom<&[u8]> for StaReturn {
+    type Error = AcpiSliceError;
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Ok(Self::from(slice_to_array::<STA_RETURN_SIZE_BYTES>(value)?))
+    }
+}
+
+/// Converts an ACPI `u32` capacity, rate or voltage into an SBS `u16` value, saturating at `u16::MAX`.
+///
+/// Returns the converted value and `true` if the input did not fit and was saturated. On overflow,
+/// callers bridging ACPI values back to SBS should either select a coarser unit or scaling (e.g.
+/// 10mWh capacity mode or SpecificationInfo() IPScale) or report the overflow, rather than forwarding
+/// the saturated value as if it were exact.
+pub const fn acpi_u32_to_sbs_u16(value: u32) -> (u16, bool) {
+    if value > u16::MAX as u32 {
+        (u16::MAX, true)
+    } else {
+        (value as u16, false)
+    }
+}
+
+/// Bitflags identifying the ACPI battery control methods a battery implements.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AcpiMethodSupport(u32);
+bitflags! {
+    impl AcpiMethodSupport: u32 {
+        /// _BCT: Battery Charge Time.
+        const BCT = 1 << 0;
+
+        /// _BIF: Battery Information.
+        const BIF = 1 << 1;
+
+        /// _BIX: Battery Information Extended.
+        const BIX = 1 << 2;
+
+        /// _BMA: Battery Measurement Averaging Interval.
+        const BMA = 1 << 3;
+
+        /// _BMC: Battery Maintenance Control.
+        const BMC = 1 << 4;
+
+        /// _BMD: Battery Maintenance Data.
+        const BMD = 1 << 5;
+
+        /// _BMS: Battery Measurement Sampling Time.
+        const BMS = 1 << 6;
+
+        /// _BPC: Battery Power Characteristics.
+        const BPC = 1 << 7;
+
+        /// _BPS: Battery Power State.
+        const BPS = 1 << 8;
+
+        /// _BPT: Battery Power Threshold.
+        const BPT = 1 << 9;
+
+        /// _BST: Battery Status.
+        const BST = 1 << 10;
+
+        /// _BTM: Battery Time.
+        const BTM = 1 << 11;
+
+        /// _BTP: Battery Trip Point.
+        const BTP = 1 << 12;
+
+        /// _STA: Device Status.
+        const STA = 1 << 13;
+    }
+}
+
+impl AcpiMethodSupport {
+    /// Methods every ACPI battery must implement: _BST, _BIX and _STA.
+    pub const MANDATORY: Self = Self::BST.union(Self::BIX).union(Self::STA);
+}
+
+/// ACPI battery device.
+///
+/// An ACPI battery may only implement a subset of the battery control methods. This trait lets
+/// the implementation advertise which ones are available, so a host can avoid calling unsupported
+/// methods, and evaluates the mandatory _BST and _BIX methods.
+pub trait AcpiBattery {
+    /// Error returned when a control method can't be evaluated.
+    type Error: core::fmt::Debug;
+
+    /// Returns the set of ACPI battery control methods this battery supports.
+    ///
+    /// Defaults to `AcpiMethodSupport::MANDATORY`. Implementations supporting optional methods
+    /// should override this to include them.
+    fn supported_methods(&self) -> AcpiMethodSupport {
+        AcpiMethodSupport::MANDATORY
+    }
+
+    /// Evaluates _BST, returning the battery's dynamic status.
+    fn bst(&mut self) -> Result<BstReturn, Self::Error>;
+
+    /// Evaluates _BIX, returning the battery's static information.
+    ///
+    /// The strings may borrow from the implementation, e.g. a buffer the values were just read into.
+    fn bix(&mut self) -> Result<BixReturn<'_>, Self::Error>;
+
+    /// Evaluates _PSR of the power source feeding this battery, or returns `None` if it isn't known.
+    ///
+    /// _PSR belongs to the power source device rather than the battery, so the default returns `None`.
+    fn psr(&mut self) -> Result<Option<PsrReturn>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Evaluates _PIF of the power source feeding this battery, or returns `None` if it isn't known.
+    ///
+    /// _PIF belongs to the power source device rather than the battery, so the default returns `None`.
+    fn pif(&mut self) -> Result<Option<Pif<'_>>, Self::Error> {
+        Ok(None)
+    }
+}
+
+impl<T: AcpiBattery + ?Sized> AcpiBattery for &mut T {
+    type Error = T::Error;
+
+    #[inline]
+    fn supported_methods(&self) -> AcpiMethodSupport {
+        T::supported_methods(self)
+    }
+
+    #[inline]
+    fn bst(&mut self) -> Result<BstReturn, Self::Error> {
+        T::bst(self)
+    }
+
+    #[inline]
+    fn bix(&mut self) -> Result<BixReturn<'_>, Self::Error> {
+        T::bix(self)
+    }
+
+    #[inline]
+    fn psr(&mut self) -> Result<Option<PsrReturn>, Self::Error> {
+        T::psr(self)
+    }
+
+    #[inline]
+    fn pif(&mut self) -> Result<Option<Pif<'_>>, Self::Error> {
+        T::pif(self)
+    }
+}
+
+/// Builds a NUL-padded ASCIIZ field of exactly `N` bytes from `s`, suitable for the string fields of
+/// `BixReturn` and `Pif` passed to `to_bytes()`.
+///
+/// If `s` is longer than `N - 1` bytes it is truncated so the field always ends with a NUL terminator.
+/// `N` must be at least 1.
+#[cfg(feature = "heapless")]
+pub fn asciiz_field<const N: usize>(s: &str) -> heapless::Vec<u8, N> {
+    let mut field = heapless::Vec::new();
+    let len = s.len().min(N.saturating_sub(1));
+    // Capacity is N and len < N, so neither call can fail.
+    let _ = field.extend_from_slice(&s.as_bytes()[..len]);
+    let _ = field.resize(N, 0);
+    field
+}
+
+/// Owned aggregate of a battery's ACPI readings, for host tooling that wants a self-contained snapshot.
+///
+/// Built by `collect()`, which copies the BIX and PIF strings into owned buffers.
+#[cfg(feature = "alloc")]
+#[derive(Debug, PartialEq, Eq)]
+pub struct AcpiReport {
+    /// Control methods supported by the battery.
+    pub supported_methods: AcpiMethodSupport,
+    /// Dynamic battery status.
+    pub bst: BstReturn,
+    /// Power source status, if known.
+    pub psr: Option<PsrReturn>,
+    bix: BixReturn<'static>,
+    bix_strings: [alloc::vec::Vec<u8>; 4],
+    pif: Option<(Pif<'static>, [alloc::vec::Vec<u8>; 3])>,
+}
+
+#[cfg(feature = "alloc")]
+impl AcpiReport {
+    /// Evaluates _BST, _BIX, _PSR and _PIF on `batt`, in that order, and copies the results into a report.
+    ///
+    /// Stops at the first method that fails and returns its error.
+    pub fn collect<A: AcpiBattery>(batt: &mut A) -> Result<AcpiReport, A::Error> {
+        let supported_methods = batt.supported_methods();
+        let bst = batt.bst()?;
+        let (bix, bix_strings) = {
+            let bix = batt.bix()?;
+            (
+                BixReturn {
+                    model_number: &[],
+                    serial_number: &[],
+                    battery_type: &[],
+                    oem_info: &[],
+                    ..bix
+                },
+                [
+                    bix.model_number.to_vec(),
+                    bix.serial_number.to_vec(),
+                    bix.battery_type.to_vec(),
+                    bix.oem_info.to_vec(),
+                ],
+            )
+        };
+        let psr = batt.psr()?;
+        let pif = batt.pif()?.map(|pif| {
+            (
+                Pif {
+                    model_number: &[],
+                    serial_number: &[],
+                    oem_info: &[],
+                    ..pif
+                },
+                [
+                    pif.model_number.to_vec(),
+                    pif.serial_number.to_vec(),
+                    pif.oem_info.to_vec(),
+                ],
+            )
+        });
+
+        Ok(Self {
+            supported_methods,
+            bst,
+            psr,
+            bix,
+            bix_strings,
+            pif,
+        })
+    }
+
+    /// Returns the static battery information, borrowing the owned strings.
+    pub fn bix(&self) -> BixReturn<'_> {
+        let [model_number, serial_number, battery_type, oem_info] = &self.bix_strings;
+        BixReturn {
+            model_number,
+            serial_number,
+            battery_type,
+            oem_info,
+            ..self.bix
+        }
+    }
+
+    /// Returns the power source information, if any, borrowing the owned strings.
+    pub fn pif(&self) -> Option<Pif<'_>> {
+        self.pif
+            .as_ref()
+            .map(|(pif, [model_number, serial_number, oem_info])| Pif {
+                model_number,
+                serial_number,
+                oem_info,
+                ..*pif
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn acpi_u32_to_sbs_u16_in_range() {
+        assert_eq!(acpi_u32_to_sbs_u16(0), (0, false));
+        assert_eq!(acpi_u32_to_sbs_u16(4200), (4200, false));
+    }
+
+    #[test]
+    fn acpi_u32_to_sbs_u16_at_limit() {
+        assert_eq!(acpi_u32_to_sbs_u16(65535), (u16::MAX, false));
+    }
+
+    #[test]
+    fn acpi_u32_to_sbs_u16_over_range() {
+        assert_eq!(acpi_u32_to_sbs_u16(65536), (u16::MAX, true));
+        assert_eq!(acpi_u32_to_sbs_u16(u32::MAX), (u16::MAX, true));
+    }
+
+    #[cfg(feature = "heapless")]
+    #[test]
+    fn asciiz_field_exact_fit() {
+        assert_eq!(asciiz_field::<5>("LION").as_slice(), b"LION\0");
+    }
+
+    #[cfg(feature = "heapless")]
+    #[test]
+    fn asciiz_field_pads_short_strings() {
+        assert_eq!(asciiz_field::<8>("LION").as_slice(), b"LION\0\0\0\0");
+        assert_eq!(asciiz_field::<3>("").as_slice(), b"\0\0\0");
+    }
+
+    #[cfg(feature = "heapless")]
+    #[test]
+    fn asciiz_field_truncates_overlong_strings() {
+        assert_eq!(asciiz_field::<4>("LION").as_slice(), b"LIO\0");
+        assert_eq!(asciiz_field::<1>("LION").as_slice(), b"\0");
+    }
+
+    #[test]
+    fn btp_valid_trip_point() {
+        assert_eq!(Btp { trip_point: 500 }.validate(3000, 4200), Ok(()));
+        assert_eq!(Btp { trip_point: 4200 }.validate(3000, 4200), Ok(()));
+    }
+
+    #[test]
+    fn btp_below_current_capacity_is_accepted() {
+        // Fires once the battery discharges past it, not immediately.
+        assert_eq!(Btp { trip_point: 2999 }.validate(3000, 4200), Ok(()));
+        assert_eq!(
+            Btp { trip_point: 3000 }.validate(3000, 4200),
+            Err(BtpError::AtCurrentCapacity)
+        );
+        assert_eq!(Btp { trip_point: 3000 }.validate(ACPI_UNKNOWN, 4200), Ok(()));
+    }
+
+    #[test]
+    fn btp_above_design_capacity() {
+        assert_eq!(
+            Btp { trip_point: 4201 }.validate(3000, 4200),
+            Err(BtpError::AboveDesignCapacity)
+        );
+        assert_eq!(
+            Btp { trip_point: 0x80000000 }.validate(3000, u32::MAX),
+            Err(BtpError::Reserved)
+        );
+    }
+
+    #[test]
+    fn btp_clear_always_validates() {
+        assert_eq!(Btp { trip_point: 0 }.validate(0, 4200), Ok(()));
+        assert_eq!(Btp { trip_point: 0 }.validate(3000, 0), Ok(()));
+    }
+
+    fn bix() -> BixReturn<'static> {
+        BixReturn {
+            revision: BixReturn::REVISION,
+            power_unit: PowerUnit::MilliAmps,
+            design_capacity: 4200,
+            last_full_charge_capacity: 4000,
+            battery_technology: BatteryTechnology::Secondary,
+            design_voltage: 11_100,
+            design_cap_of_warning: 420,
+            design_cap_of_low: 210,
+            cycle_count: 12,
+            measurement_accuracy: 98_000,
+            max_sampling_time: 1000,
+            min_sampling_time: 250,
+            max_averaging_interval: 60_000,
+            min_averaging_interval: 1000,
+            battery_capacity_granularity_1: 10,
+            battery_capacity_granularity_2: 10,
+            model_number: b"PACK-3S1P\0",
+            serial_number: b"4711\0",
+            battery_type: b"LION\0",
+            oem_info: b"ACME\0",
+            battery_swapping_capability: BatterySwapCapability::NonSwappable,
+        }
+    }
+
+    #[test]
+    fn bix_to_bytes_rejects_overflowing_sizes() {
+        let mut buffer = [0u8; 128];
+        assert_eq!(
+            bix().to_bytes(&mut buffer, usize::MAX, 5, 5, 5),
+            Err(BixReturnSerializeErr::InputSliceTooSmall)
+        );
+        assert_eq!(
+            bix().to_bytes(&mut buffer, 10, 5, 5, usize::MAX - 64),
+            Err(BixReturnSerializeErr::InputSliceTooSmall)
+        );
+        assert_eq!(
+            bix().to_bytes_checked(&mut buffer, 10, usize::MAX - 70, 5, 5),
+            Err(BixReturnSerializeErr::InputSliceTooSmall)
+        );
+    }
+
+    #[test]
+    fn pif_to_bytes_rejects_overflowing_sizes() {
+        let pif = || Pif {
+            model_number: b"PSU\0",
+            serial_number: b"1\0",
+            oem_info: b"ACME\0",
+            ..Default::default()
+        };
+        let mut buffer = [0u8; 64];
+        assert_eq!(
+            pif().to_bytes(&mut buffer, usize::MAX, 2, 5),
+            Err(PifSerializeErr::InputSliceTooSmall)
+        );
+        assert_eq!(
+            pif().to_bytes(&mut buffer, 4, usize::MAX - 12, 5),
+            Err(PifSerializeErr::InputSliceTooSmall)
+        );
+    }
+
+    #[test]
+    fn bix_to_bytes_exact_sizes() {
+        let mut buffer = [0u8; bix_serialized_size(10, 5, 5, 5)];
+        assert_eq!(bix().to_bytes(&mut buffer, 10, 5, 5, 5), Ok(()));
+        assert_eq!(&buffer[64..74], b"PACK-3S1P\0");
+        assert_eq!(
+            bix().to_bytes(&mut buffer, 10, 5, 5, 4),
+            Err(BixReturnSerializeErr::StringSizeMismatch)
+        );
+    }
+
+    // The documented example size must stay usable as an array length.
+    const _: [u8; bix_serialized_size(16, 16, 8, 16)] = [0; 124];
+
+    #[test]
+    fn bix_serialized_size_matches_to_bytes() {
+        let padded = || BixReturn {
+            model_number: b"PACK-3S1P\0\0\0\0\0\0\0",
+            serial_number: b"4711\0\0\0\0\0\0\0\0\0\0\0\0",
+            battery_type: b"LION\0\0\0\0",
+            oem_info: b"ACME\0\0\0\0\0\0\0\0\0\0\0\0",
+            battery_swapping_capability: BatterySwapCapability::HotSwappable,
+            ..bix()
+        };
+        let len = bix_serialized_size(16, 16, 8, 16);
+        assert_eq!(len, 124);
+
+        // The swapping capability is the last field, and bytes past it are left untouched.
+        let mut buffer = [0xAAu8; 128];
+        assert_eq!(padded().to_bytes(&mut buffer, 16, 16, 8, 16), Ok(()));
+        assert_eq!(&buffer[len - 4..len], &u32::to_le_bytes(2));
+        assert!(buffer[len..].iter().all(|&b| b == 0xAA));
+
+        assert_eq!(
+            padded().to_bytes(&mut buffer[..len - 1], 16, 16, 8, 16),
+            Err(BixReturnSerializeErr::InputSliceTooSmall)
+        );
+    }
+
+    /// ACPI battery whose BIX strings live in the implementation, like an EC mailbox buffer.
+    #[cfg(feature = "alloc")]
+    struct MockAcpiBattery {
+        model_number: [u8; 10],
+        fail_bix: bool,
+        on_ac: bool,
+    }
+
+    #[cfg(feature = "alloc")]
+    impl AcpiBattery for MockAcpiBattery {
+        type Error = ();
+
+        fn supported_methods(&self) -> AcpiMethodSupport {
+            AcpiMethodSupport::MANDATORY | AcpiMethodSupport::BTP
+        }
+
+        fn bst(&mut self) -> Result<BstReturn, Self::Error> {
+            Ok(BstReturn {
+                battery_state: BatteryState::DISCHARGING,
+                battery_present_rate: 850,
+                battery_remaining_capacity: 3000,
+                battery_present_voltage: 11_400,
+            })
+        }
+
+        fn bix(&mut self) -> Result<BixReturn<'_>, Self::Error> {
+            if self.fail_bix {
+                return Err(());
+            }
+            Ok(BixReturn {
+                model_number: &self.model_number,
+                ..bix()
+            })
+        }
+
+        fn psr(&mut self) -> Result<Option<PsrReturn>, Self::Error> {
+            Ok(self.on_ac.then_some(PsrReturn {
+                power_source: PowerSource::Online,
+            }))
+        }
+
+        fn pif(&mut self) -> Result<Option<Pif<'_>>, Self::Error> {
+            Ok(self.on_ac.then_some(Pif {
+                max_output_power: 65_000,
+                model_number: b"PSU\0",
+                serial_number: b"1\0",
+                oem_info: b"ACME\0",
+                ..Default::default()
+            }))
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn acpi_report_collects_owned_copies() {
+        // The report owns its strings, so it outlives the temporary battery.
+        let report = AcpiReport::collect(&mut MockAcpiBattery {
+            model_number: *b"PACK-3S1P\0",
+            fail_bix: false,
+            on_ac: true,
+        })
+        .unwrap();
+
+        assert_eq!(
+            report.supported_methods,
+            AcpiMethodSupport::MANDATORY | AcpiMethodSupport::BTP
+        );
+        assert_eq!(report.bst.battery_remaining_capacity, 3000);
+        assert_eq!(report.bix(), bix());
+        assert_eq!(
+            report.psr,
+            Some(PsrReturn {
+                power_source: PowerSource::Online
+            })
+        );
+        let pif = report.pif().unwrap();
+        assert_eq!(pif.max_output_power, 65_000);
+        assert_eq!(pif.oem_info, b"ACME\0");
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn acpi_report_without_power_source() {
+        let mut batt = MockAcpiBattery {
+            model_number: *b"PACK-3S1P\0",
+            fail_bix: false,
+            on_ac: false,
+        };
+        let report = AcpiReport::collect(&mut batt).unwrap();
+        assert_eq!(report.psr, None);
+        assert_eq!(report.pif(), None);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn acpi_report_propagates_errors() {
+        let mut batt = MockAcpiBattery {
+            model_number: *b"PACK-3S1P\0",
+            fail_bix: true,
+            on_ac: true,
+        };
+        assert_eq!(AcpiReport::collect(&mut batt), Err(()));
+    }
+
+    #[test]
+    fn battery_state_charger_delivering_current() {
+        use crate::charger::Charger;
+        use crate::mock::{MockCharger, RegisterFileBattery};
+        use crate::smart_battery::Command;
+
+        let mut batt = RegisterFileBattery::new();
+        batt.set(Command::Current, 1500);
+        let mut charger = MockCharger::new(2000, 12600);
+        charger.charging_current(1500).unwrap();
+        charger.delivered = Some(1480);
+        assert_eq!(
+            BatteryState::from_battery_and_charger(&mut batt, &mut charger),
+            Ok(BatteryState::CHARGING)
+        );
+    }
+
+    #[test]
+    fn battery_state_charger_delivering_nothing() {
+        use crate::charger::Charger;
+        use crate::mock::{MockCharger, RegisterFileBattery};
+        use crate::smart_battery::Command;
+
+        let mut batt = RegisterFileBattery::new();
+        let mut charger = MockCharger::new(2000, 12600);
+        charger.delivered = Some(0);
+        assert_eq!(
+            BatteryState::from_battery_and_charger(&mut batt, &mut charger),
+            Ok(BatteryState::empty())
+        );
+
+        // An acknowledged setpoint doesn't count while the charger measures no output.
+        charger.charging_current(1500).unwrap();
+        batt.set(Command::Current, 1200);
+        assert_eq!(
+            BatteryState::from_battery_and_charger(&mut batt, &mut charger),
+            Ok(BatteryState::empty())
+        );
+    }
+
+    #[test]
+    fn battery_state_charger_inhibited() {
+        use crate::charger::{Charger, InhibitReason};
+        use crate::mock::{MockCharger, RegisterFileBattery};
+        use crate::smart_battery::Command;
+
+        let mut batt = RegisterFileBattery::new();
+        batt.set(Command::Current, 1200);
+        let mut charger = MockCharger::new(2000, 12600);
+        charger.charging_current(1500).unwrap();
+        charger.inhibit = Some(InhibitReason::ThermalHot);
+        assert_eq!(
+            BatteryState::from_battery_and_charger(&mut batt, &mut charger),
+            Ok(BatteryState::empty())
+        );
+
+        // Inhibition takes precedence over a stale delivered current reading.
+        charger.delivered = Some(1480);
+        assert_eq!(
+            BatteryState::from_battery_and_charger(&mut batt, &mut charger),
+            Ok(BatteryState::empty())
+        );
+    }
+
+    #[test]
+    fn battery_state_charger_without_measurement_uses_battery_current() {
+        use crate::mock::{MockCharger, RegisterFileBattery};
+        use crate::smart_battery::Command;
+
+        let mut batt = RegisterFileBattery::new();
+        let mut charger = MockCharger::new(2000, 12600);
+        assert_eq!(
+            BatteryState::from_battery_and_charger(&mut batt, &mut charger),
+            Ok(BatteryState::empty())
+        );
+        batt.set(Command::Current, 1200);
+        assert_eq!(
+            BatteryState::from_battery_and_charger(&mut batt, &mut charger),
+            Ok(BatteryState::CHARGING)
+        );
+    }
+
+    #[test]
+    fn battery_state_discharging_ignores_charger() {
+        use crate::mock::{MockCharger, RegisterFileBattery};
+        use crate::smart_battery::Command;
+
+        let mut batt = RegisterFileBattery::new();
+        let status = BatteryStatusFields::new()
+            .with_initialized(true)
+            .with_discharging(true)
+            .with_fully_discharged(true);
+        batt.set(Command::BatteryStatus, status.into_bits());
+        let mut charger = MockCharger::new(2000, 12600);
+        charger.delivered = Some(500);
+        assert_eq!(
+            BatteryState::from_battery_and_charger(&mut batt, &mut charger),
+            Ok(BatteryState::DISCHARGING | BatteryState::CRITICAL)
+        );
+    }
+
+    #[test]
+    fn bix_checked_round_trips_padded_strings() {
+        let padded = BixReturn {
+            model_number: b"PACK-3S1P\0\0\0\0\0\0\0",
+            serial_number: b"4711\0\0\0\0",
+            ..bix()
+        };
+        let mut buffer = [0u8; 64 + 16 + 8 + 5 + 5 + 4 + 1];
+        padded.to_bytes_checked(&mut buffer, 16, 8, 5, 5).unwrap();
+        let decoded = BixReturn::from_bytes_checked(&buffer, 16, 8, 5, 5).unwrap();
+        assert_eq!(decoded.model_number, b"PACK-3S1P\0\0\0\0\0\0\0");
+        assert_eq!(decoded.serial_number, b"4711\0\0\0\0");
+        assert_eq!(
+            decoded,
+            BixReturn {
+                model_number: b"PACK-3S1P\0\0\0\0\0\0\0",
+                serial_number: b"4711\0\0\0\0",
+                ..bix()
+            }
+        );
+    }
+
+    #[test]
+    fn bix_checked_detects_corrupted_bytes() {
+        let mut buffer = [0u8; 64 + 10 + 5 + 5 + 5 + 4 + 1];
+        bix().to_bytes_checked(&mut buffer, 10, 5, 5, 5).unwrap();
+        buffer[70] ^= 0x01;
+        assert_eq!(
+            BixReturn::from_bytes_checked(&buffer, 10, 5, 5, 5),
+            Err(BixReturnDeserializeErr::ChecksumMismatch)
+        );
+        assert_eq!(
+            BixReturn::from_bytes_checked(&buffer[..buffer.len() - 1], 10, 5, 5, 5),
+            Err(BixReturnDeserializeErr::InputSliceTooSmall)
+        );
+    }
+
+    fn calibration_bmd(status_flags: BmdStatusFlags) -> Bmd {
+        Bmd {
+            status_flags,
+            capability_flags: BmdCapabilityFlags::AML_CALIBRATION_SUPPORTED
+                | BmdCapabilityFlags::FULL_CHARGE_BEFORE_CALIBRATION
+                | BmdCapabilityFlags::CHARGE_LIMIT_SUSPEND_SUPPORTED,
+            ..Default::default()
+        }
+    }
+
+    const CALIBRATING: BmcControlFlags =
+        BmcControlFlags::CALIBRATION_CYCLE.union(BmcControlFlags::SUSPEND_CHARGE_LIMITING);
+
+    #[test]
+    fn calibration_flow_full_cycle() {
+        let mut flow = CalibrationFlow::new();
+        assert_eq!(flow.state(), CalibrationState::Idle);
+        assert_eq!(flow.command().maintenance_control_flags, BmcControlFlags::empty());
+
+        flow.request(&calibration_bmd(BmdStatusFlags::RECALIBRATION_NEEDED));
+        assert_eq!(flow.state(), CalibrationState::Requested);
+        assert_eq!(flow.command().maintenance_control_flags, CALIBRATING);
+
+        // AML hasn't picked up the request yet.
+        assert_eq!(
+            flow.step(&calibration_bmd(BmdStatusFlags::RECALIBRATION_NEEDED)),
+            CalibrationState::Requested
+        );
+        assert_eq!(flow.command().maintenance_control_flags, CALIBRATING);
+
+        let active = BmdStatusFlags::AML_CALIBRATION_ACTIVE | BmdStatusFlags::RECALIBRATION_NEEDED;
+        assert_eq!(flow.step(&calibration_bmd(active)), CalibrationState::Active);
+        assert_eq!(flow.command().maintenance_control_flags, CALIBRATING);
+        assert_eq!(flow.step(&calibration_bmd(active)), CalibrationState::Active);
+
+        assert_eq!(
+            flow.step(&calibration_bmd(BmdStatusFlags::empty())),
+            CalibrationState::Complete
+        );
+        assert_eq!(flow.command().maintenance_control_flags, BmcControlFlags::empty());
+
+        flow.reset();
+        assert_eq!(flow.state(), CalibrationState::Idle);
+    }
+
+    #[test]
+    fn calibration_flow_aborts_on_lock_while_requested() {
+        for lock in [
+            BmdStatusFlags::CHARGE_LIMIT_THERMAL_LOCK,
+            BmdStatusFlags::CHARGE_LIMIT_PROTECTION_LOCK,
+        ] {
+            let mut flow = CalibrationFlow::new();
+            flow.request(&calibration_bmd(BmdStatusFlags::RECALIBRATION_NEEDED));
+            assert_eq!(flow.step(&calibration_bmd(lock)), CalibrationState::Failed);
+            assert_eq!(flow.command().maintenance_control_flags, BmcControlFlags::empty());
+        }
+    }
+
+    #[test]
+    fn calibration_flow_aborts_on_lock_while_active() {
+        let mut flow = CalibrationFlow::new();
+        flow.request(&calibration_bmd(BmdStatusFlags::RECALIBRATION_NEEDED));
+        let active = BmdStatusFlags::AML_CALIBRATION_ACTIVE | BmdStatusFlags::RECALIBRATION_NEEDED;
+        assert_eq!(flow.step(&calibration_bmd(active)), CalibrationState::Active);
+
+        assert_eq!(
+            flow.step(&calibration_bmd(active | BmdStatusFlags::CHARGE_LIMIT_THERMAL_LOCK)),
+            CalibrationState::Failed
+        );
+        assert_eq!(flow.command().maintenance_control_flags, BmcControlFlags::empty());
+    }
+
+    #[test]
+    fn calibration_flow_ignores_lock_without_full_charge() {
+        let bmd = |status_flags| Bmd {
+            status_flags,
+            capability_flags: BmdCapabilityFlags::AML_CALIBRATION_SUPPORTED,
+            ..Default::default()
+        };
+        let mut flow = CalibrationFlow::new();
+        flow.request(&bmd(BmdStatusFlags::RECALIBRATION_NEEDED));
+        assert_eq!(
+            flow.command().maintenance_control_flags,
+            BmcControlFlags::CALIBRATION_CYCLE
+        );
+        let active = BmdStatusFlags::AML_CALIBRATION_ACTIVE | BmdStatusFlags::CHARGE_LIMIT_THERMAL_LOCK;
+        assert_eq!(flow.step(&bmd(active)), CalibrationState::Active);
+        assert_eq!(flow.step(&bmd(active)), CalibrationState::Active);
+    }
+
+    /// ACPI battery advertising the power delivery methods _BPT and _BPC on top of the mandatory ones.
+    struct PowerDeliveryBattery;
+
+    impl AcpiBattery for PowerDeliveryBattery {
+        type Error = ();
+
+        fn supported_methods(&self) -> AcpiMethodSupport {
+            AcpiMethodSupport::MANDATORY | AcpiMethodSupport::BPT | AcpiMethodSupport::BPC
+        }
+
+        fn bst(&mut self) -> Result<BstReturn, Self::Error> {
+            Err(())
+        }
+
+        fn bix(&mut self) -> Result<BixReturn<'_>, Self::Error> {
+            Err(())
+        }
+    }
+
+    #[test]
+    fn supported_methods_advertises_overrides() {
+        let methods = PowerDeliveryBattery.supported_methods();
+        assert!(methods.contains(AcpiMethodSupport::BPT | AcpiMethodSupport::BPC));
+        assert!(methods.contains(AcpiMethodSupport::MANDATORY));
+        for absent in [
+            AcpiMethodSupport::BPS,
+            AcpiMethodSupport::BTP,
+            AcpiMethodSupport::BMC,
+            AcpiMethodSupport::BIF,
+        ] {
+            assert!(!methods.contains(absent));
+        }
+        // Forwarded through `&mut T`.
+        let mut battery = PowerDeliveryBattery;
+        assert_eq!(
+            <&mut PowerDeliveryBattery as AcpiBattery>::supported_methods(&&mut battery),
+            methods
+        );
+    }
+
+    #[test]
+    fn supported_methods_defaults_to_mandatory() {
+        struct MandatoryOnly;
+
+        impl AcpiBattery for MandatoryOnly {
+            type Error = ();
+
+            fn bst(&mut self) -> Result<BstReturn, Self::Error> {
+                Err(())
+            }
+
+            fn bix(&mut self) -> Result<BixReturn<'_>, Self::Error> {
+                Err(())
+            }
+        }
+
+        let methods = MandatoryOnly.supported_methods();
+        assert_eq!(
+            methods,
+            AcpiMethodSupport::BST | AcpiMethodSupport::BIX | AcpiMethodSupport::STA
+        );
+        assert!(!methods.intersects(AcpiMethodSupport::BPT | AcpiMethodSupport::BPC));
+    }
+
+    #[test]
+    fn bix_owned_round_trips() {
+        const LEN: usize = bix_serialized_size(10, 5, 5, 5);
+        let mut expected = [0u8; LEN];
+        bix().to_bytes(&mut expected, 10, 5, 5, 5).unwrap();
+
+        // Exactly large enough, and with spare room.
+        let owned = BixReturnOwned::<25>::try_from(&bix()).unwrap();
+        assert_eq!(owned.as_bix(), bix());
+        let mut bytes = [0u8; LEN];
+        owned.as_bix().to_bytes(&mut bytes, 10, 5, 5, 5).unwrap();
+        assert_eq!(bytes, expected);
+
+        let owned = BixReturnOwned::<64>::try_from(&bix()).unwrap();
+        let mut bytes = [0u8; LEN];
+        BixReturn::from(&owned).to_bytes(&mut bytes, 10, 5, 5, 5).unwrap();
+        assert_eq!(bytes, expected);
+    }
+
+    #[test]
+    fn bix_owned_rejects_strings_too_long() {
+        assert_eq!(
+            BixReturnOwned::<24>::try_from(&bix()),
+            Err(BixReturnSerializeErr::InputSliceTooSmall)
+        );
+        assert_eq!(
+            BixReturnOwned::<0>::try_from(&bix()),
+            Err(BixReturnSerializeErr::InputSliceTooSmall)
+        );
+    }
+
+    #[test]
+    fn bix_validate_accepts_valid_bix() {
+        assert_eq!(bix().validate(), Ok(()));
+        // Thresholds and granularities that are unknown aren't compared.
+        let unknown = BixReturn {
+            design_cap_of_warning: ACPI_UNKNOWN,
+            battery_capacity_granularity_2: ACPI_UNKNOWN,
+            ..bix()
+        };
+        assert_eq!(unknown.validate(), Ok(()));
+    }
+
+    #[test]
+    fn bix_validate_rejects_unsupported_revision() {
+        let bix = BixReturn { revision: 0, ..bix() };
+        assert_eq!(bix.validate(), Err(BixValidationError::UnsupportedRevision));
+    }
+
+    #[test]
+    fn bix_validate_rejects_low_above_warning() {
+        let bix = BixReturn {
+            design_cap_of_warning: 200,
+            design_cap_of_low: 201,
+            ..bix()
+        };
+        assert_eq!(bix.validate(), Err(BixValidationError::LowAboveWarning));
+    }
+
+    #[test]
+    fn bix_validate_rejects_inverted_granularity() {
+        let bix = BixReturn {
+            battery_capacity_granularity_1: 20,
+            battery_capacity_granularity_2: 10,
+            ..bix()
+        };
+        assert_eq!(bix.validate(), Err(BixValidationError::GranularityInverted));
+    }
+
+    #[test]
+    fn bix_alarm_percents() {
+        let thresholds = BixReturn {
+            design_capacity: 5000,
+            design_cap_of_warning: 500,
+            design_cap_of_low: 200,
+            ..bix()
+        };
+        assert_eq!(thresholds.warning_percent(), Some(10));
+        assert_eq!(thresholds.low_percent(), Some(4));
+
+        let mut set = BixReturn {
+            design_capacity: 5000,
+            ..bix()
+        };
+        set.set_warning_percent(10);
+        set.set_low_percent(4);
+        assert_eq!((set.design_cap_of_warning, set.design_cap_of_low), (500, 200));
+    }
+
+    #[test]
+    fn bix_alarm_percents_unknown_or_zero_design() {
+        let unknown_design = BixReturn {
+            design_capacity: ACPI_UNKNOWN,
+            ..bix()
+        };
+        assert_eq!(unknown_design.warning_percent(), None);
+        assert_eq!(unknown_design.low_percent(), None);
+
+        let unknown_threshold = BixReturn {
+            design_cap_of_warning: ACPI_UNKNOWN,
+            ..bix()
+        };
+        assert_eq!(unknown_threshold.warning_percent(), None);
+
+        let zero_design = BixReturn {
+            design_capacity: 0,
+            ..bix()
+        };
+        assert_eq!(zero_design.warning_percent(), None);
+        assert_eq!(zero_design.low_percent(), None);
+
+        let mut unknown_design = unknown_design;
+        unknown_design.set_warning_percent(10);
+        assert_eq!(unknown_design.design_cap_of_warning, ACPI_UNKNOWN);
+    }
+
+    #[test]
+    fn bix_alarm_percents_round_and_saturate() {
+        // 499 / 4999 is 9.98%, so a 10% threshold set on an odd capacity reads back as 10%.
+        let mut bix = BixReturn {
+            design_capacity: 4999,
+            ..bix()
+        };
+        bix.set_warning_percent(10);
+        assert_eq!(bix.design_cap_of_warning, 499);
+        assert_eq!(bix.warning_percent(), Some(10));
+
+        bix.design_cap_of_warning = 4999 * 3;
+        assert_eq!(bix.warning_percent(), None);
+    }
+
+    #[test]
+    fn bix_with_cycle_count_updates_only_cycle_count() {
+        assert_eq!(
+            bix().with_cycle_count(345),
+            BixReturn {
+                cycle_count: 345,
+                ..bix()
+            }
+        );
+        assert_eq!(bix().with_cycle_count(0).cycle_count, 0);
+        assert_eq!(bix().with_cycle_count(0xFFFE).cycle_count, 0xFFFE);
+    }
+
+    #[test]
+    fn bix_with_cycle_count_maps_0xffff_to_unknown() {
+        assert_eq!(
+            bix().with_cycle_count(0xFFFF),
+            BixReturn {
+                cycle_count: ACPI_UNKNOWN,
+                ..bix()
+            }
+        );
+    }
+
+    #[test]
+    fn technology_voltage_plausible_secondary_li_ion() {
+        // 3S Li-ion at 3.6V or 3.7V per cell.
+        for design_voltage in [10_800, 11_100] {
+            let bix = BixReturn {
+                battery_technology: BatteryTechnology::Secondary,
+                design_voltage,
+                ..bix()
+            };
+            assert!(bix.technology_voltage_plausible(), "{design_voltage}");
+        }
+    }
+
+    #[test]
+    fn technology_voltage_implausible() {
+        // A primary battery reporting the 3S Li-ion voltage, which is no multiple of 1.5V or 3.0V cells.
+        let primary = BixReturn {
+            battery_technology: BatteryTechnology::Primary,
+            design_voltage: 11_100,
+            ..bix()
+        };
+        assert!(!primary.technology_voltage_plausible());
+        // While 4 alkaline cells are fine.
+        let alkaline = BixReturn {
+            design_voltage: 6000,
+            ..primary
+        };
+        assert!(alkaline.technology_voltage_plausible());
+    }
+
+    #[test]
+    fn technology_voltage_unknown() {
+        for design_voltage in [ACPI_UNKNOWN, 0] {
+            let bix = BixReturn {
+                battery_technology: BatteryTechnology::Primary,
+                design_voltage,
+                ..bix()
+            };
+            assert!(bix.technology_voltage_plausible());
+        }
+        // Technologies other than primary and secondary can't be represented, so they never reach the check.
+        assert!(BatteryTechnology::try_from(2u32).is_err());
+    }
+
+    #[test]
+    fn bst_try_from_slice_checks_length() {
+        let bytes = [0u8; BST_RETURN_SIZE_BYTES + 1];
+        assert_eq!(
+            BstReturn::try_from(&bytes[..BST_RETURN_SIZE_BYTES - 1]),
+            Err(AcpiSliceError::WrongLength {
+                expected: BST_RETURN_SIZE_BYTES,
+                got: BST_RETURN_SIZE_BYTES - 1
+            })
+        );
+        assert_eq!(
+            BstReturn::try_from(&bytes[..]),
+            Err(AcpiSliceError::WrongLength {
+                expected: BST_RETURN_SIZE_BYTES,
+                got: BST_RETURN_SIZE_BYTES + 1
+            })
+        );
+        assert_eq!(
+            BstReturn::try_from(&bytes[..BST_RETURN_SIZE_BYTES]),
+            Ok(BstReturn::default())
+        );
+    }
+
+    #[test]
+    fn psr_try_from_slice_checks_length_and_value() {
+        assert_eq!(
+            PsrReturn::try_from(&[1u8, 0, 0][..]),
+            Err(AcpiSliceError::WrongLength { expected: 4, got: 3 })
+        );
+        assert_eq!(
+            PsrReturn::try_from(&[1u8, 0, 0, 0, 0][..]),
+            Err(AcpiSliceError::WrongLength { expected: 4, got: 5 })
+        );
+        assert_eq!(
+            PsrReturn::try_from(&[2u8, 0, 0, 0][..]),
+            Err(AcpiSliceError::InvalidValue)
+        );
+        assert_eq!(
+            PsrReturn::try_from(&[1u8, 0, 0, 0][..]),
+            Ok(PsrReturn {
+                power_source: PowerSource::Online
+            })
+        );
+    }
+
+    #[test]
+    fn fixed_size_try_from_slice_reports_sizes() {
+        let bytes = [0u8; 32];
+        let wrong = |expected| AcpiSliceError::WrongLength { expected, got: 32 };
+        assert_eq!(Bps::try_from(&bytes[..]), Err(wrong(BPS_RETURN_SIZE_BYTES)));
+        assert_eq!(Bpc::try_from(&bytes[..]), Err(wrong(BPC_RETURN_SIZE_BYTES)));
+        assert_eq!(Bmd::try_from(&bytes[..]), Err(wrong(BMD_RETURN_SIZE_BYTES)));
+        assert_eq!(StaReturn::try_from(&bytes[..]), Err(wrong(STA_RETURN_SIZE_BYTES)));
+    }
+
+    /// Serializes `value`, checks the layout against `expected`, and parses it back through both conversions.
+    fn assert_bytes_round_trip<T, const N: usize>(value: T, expected: [u8; N])
+    where
+        T: IntoBytes + Immutable + From<[u8; N]> + for<'a> TryFrom<&'a [u8], Error = AcpiSliceError> + PartialEq,
+        T: core::fmt::Debug + Copy,
+    {
+        let bytes: [u8; N] = value.as_bytes().try_into().unwrap();
+        assert_eq!(bytes, expected);
+        assert_eq!(T::from(bytes), value);
+        assert_eq!(T::try_from(&bytes[..]), Ok(value));
+    }
+
+    #[test]
+    fn fixed_size_returns_round_trip_through_bytes() {
+        assert_bytes_round_trip(
+            BstReturn {
+                battery_state: BatteryState::CHARGING,
+                battery_present_rate: 0x0102,
+                battery_remaining_capacity: 3000,
+                battery_present_voltage: ACPI_UNKNOWN,
+            },
+            [2, 0, 0, 0, 2, 1, 0, 0, 0xB8, 0x0B, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF],
+        );
+        assert_bytes_round_trip(
+            Bps {
+                revision: 1,
+                instantaneous_peak_power_level: 45_000,
+                instantaneous_peak_power_period: 10,
+                sustainable_peak_power_level: 30_000,
+                sustainable_peak_power_period: 1000,
+            },
+            [
+                1, 0, 0, 0, 0xC8, 0xAF, 0, 0, 10, 0, 0, 0, 0x30, 0x75, 0, 0, 0xE8, 0x03, 0, 0,
+            ],
+        );
+        assert_bytes_round_trip(
+            Bpc {
+                revision: 1,
+                power_threshold_support: PowerThresholdSupport::INSTANTANEOUS | PowerThresholdSupport::SUSTAINABLE,
+                max_instantaneous_peak_power_threshold: 45_000,
+                max_sustainable_peak_power_threshold: 30_000,
+            },
+            [1, 0, 0, 0, 3, 0, 0, 0, 0xC8, 0xAF, 0, 0, 0x30, 0x75, 0, 0],
+        );
+        assert_bytes_round_trip(
+            Bmd {
+                status_flags: BmdStatusFlags::RECALIBRATION_NEEDED,
+                capability_flags: BmdCapabilityFlags::AML_CALIBRATION_SUPPORTED,
+                recalibrate_count: 500,
+                quick_recalibrate_time: 0,
+                slow_recalibrate_time: ACPI_UNKNOWN,
+            },
+            [
+                8, 0, 0, 0, 1, 0, 0, 0, 0xF4, 0x01, 0, 0, 0, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF,
+            ],
+        );
+        assert_bytes_round_trip(StaReturn::DEVICE_PRESENT | StaReturn::DEVICE_ENABLED, [3, 0, 0, 0]);
+    }
+
+    #[test]
+    fn battery_state_from_status_charging() {
+        assert_eq!(BatteryState::from(BatteryStatusFields::new()), BatteryState::CHARGING);
+        assert_eq!(
+            BatteryState::from(BatteryStatusFields::new().with_fully_charged(true)),
+            BatteryState::empty()
+        );
+    }
+
+    #[test]
+    fn battery_state_from_status_discharging() {
+        assert_eq!(
+            BatteryState::from(BatteryStatusFields::new().with_discharging(true)),
+            BatteryState::DISCHARGING
+        );
+    }
+
+    #[test]
+    fn battery_state_from_status_critical() {
+        let discharging = BatteryStatusFields::new().with_discharging(true);
+        assert_eq!(
+            BatteryState::from(discharging.with_fully_discharged(true)),
+            BatteryState::DISCHARGING | BatteryState::CRITICAL
+        );
+        assert_eq!(
+            BatteryState::from(discharging.with_terminate_discharge_alarm(true)),
+            BatteryState::DISCHARGING | BatteryState::CRITICAL
+        );
+    }
+
+    #[test]
+    fn battery_status_from_state() {
+        let status = BatteryStatusFields::from(BatteryState::CHARGING);
+        assert!(!status.discharging() && !status.fully_charged() && !status.fully_discharged());
+
+        let status = BatteryStatusFields::from(BatteryState::DISCHARGING);
+        assert!(status.discharging() && !status.fully_charged() && !status.fully_discharged());
+
+        let status = BatteryStatusFields::from(BatteryState::DISCHARGING | BatteryState::CRITICAL);
+        assert!(status.discharging() && status.fully_discharged());
+        assert!(!status.terminate_discharge_alarm());
+
+        let status = BatteryStatusFields::from(BatteryState::empty());
+        assert!(status.fully_charged() && !status.discharging());
+    }
+
+    #[test]
+    fn battery_state_round_trips_through_status() {
+        for state in [
+            BatteryState::CHARGING,
+            BatteryState::DISCHARGING,
+            BatteryState::DISCHARGING | BatteryState::CRITICAL,
+            BatteryState::empty(),
+        ] {
+            assert_eq!(BatteryState::from(BatteryStatusFields::from(state)), state);
+        }
+    }
+
+    #[test]
+    fn battery_state_charging_and_discharging_is_invalid() {
+        let both = BatteryState::CHARGING | BatteryState::DISCHARGING;
+        assert!(!both.is_valid());
+        assert!(!(both | BatteryState::CRITICAL).is_valid());
+        assert_eq!(BatteryState::from_bits_validated(both.bits()), None);
+    }
+
+    #[test]
+    fn battery_state_reserved_bits_are_rejected() {
+        assert_eq!(BatteryState::from_bits_validated(1 << 4), None);
+        assert_eq!(BatteryState::from_bits_validated(0b0010 | (1 << 31)), None);
+    }
+
+    #[test]
+    fn battery_state_valid_states_are_accepted() {
+        for state in [
+            BatteryState::empty(),
+            BatteryState::DISCHARGING,
+            BatteryState::CHARGING,
+            BatteryState::CRITICAL,
+            BatteryState::CHARGE_LIMITING,
+            BatteryState::DISCHARGING | BatteryState::CRITICAL,
+            BatteryState::CHARGING | BatteryState::CHARGE_LIMITING,
+        ] {
+            assert!(state.is_valid());
+            assert_eq!(BatteryState::from_bits_validated(state.bits()), Some(state));
+        }
+    }
+
+    #[test]
+    fn bpt_constructors_set_revision_and_threshold() {
+        let clear = Bpt::clear_all();
+        assert_eq!(clear.revision, Bpt::REVISION);
+        assert_eq!(clear.threshold_id, ThresholdId::ClearAll);
+        assert_eq!(clear.threshold_value, 0);
+        assert!(clear.is_valid());
+
+        let instantaneous = Bpt::instantaneous(45_000);
+        assert_eq!(instantaneous.revision, Bpt::REVISION);
+        assert_eq!(instantaneous.threshold_id, ThresholdId::InstantaneousPeakPower);
+        assert_eq!(instantaneous.threshold_value, 45_000);
+        assert!(instantaneous.is_valid());
+
+        let sustainable = Bpt::sustainable(30_000);
+        assert_eq!(sustainable.revision, Bpt::REVISION);
+        assert_eq!(sustainable.threshold_id, ThresholdId::SustainablePeakPower);
+        assert_eq!(sustainable.threshold_value, 30_000);
+        assert!(sustainable.is_valid());
+    }
+
+    #[test]
+    fn bpt_clear_all_requires_zero_value() {
+        let clear = Bpt {
+            threshold_value: 1,
+            ..Bpt::clear_all()
+        };
+        assert!(!clear.is_valid());
+
+        let wrong_revision = Bpt {
+            revision: 2,
+            ..Bpt::instantaneous(45_000)
+        };
+        assert!(!wrong_revision.is_valid());
+    }
+
+    #[test]
+    fn bct_estimated_avoids_sentinels() {
+        assert_eq!(BctReturnResult::estimated(0), BctReturnResult::EstimatedTime(1));
+        assert_eq!(
+            BctReturnResult::estimated(0xFFFFFFFF),
+            BctReturnResult::EstimatedTime(0xFFFFFFFE)
+        );
+        assert_eq!(
+            <[u8; BCT_RETURN_SIZE_BYTES]>::from(BctReturnResult::estimated(3600)),
+            3600u32.to_le_bytes()
+        );
+        assert_eq!(u32::from(BctReturnResult::estimated(0)), 1);
+        assert_eq!(u32::from(BctReturnResult::estimated(0xFFFFFFFF)), 0xFFFFFFFE);
+    }
+
+    #[test]
+    fn bct_sentinels_encode_to_acpi_values() {
+        assert_eq!(
+            <[u8; BCT_RETURN_SIZE_BYTES]>::from(BctReturnResult::InvalidTarget),
+            [0; 4]
+        );
+        assert_eq!(<[u8; BCT_RETURN_SIZE_BYTES]>::from(BctReturnResult::Unknown), [0xFF; 4]);
+        assert_eq!(BctReturnResult::from(0), BctReturnResult::InvalidTarget);
+        assert_eq!(BctReturnResult::from(ACPI_UNKNOWN), BctReturnResult::Unknown);
+    }
+
+    #[test]
+    fn btm_runtime_maps_zero_and_clamps_unknown() {
+        assert_eq!(
+            BtmReturnResult::runtime(0),
+            BtmReturnResult::RateTooHighOrBatteryCritical
+        );
+        assert_eq!(
+            BtmReturnResult::runtime(0xFFFFFFFF),
+            BtmReturnResult::EstimatedRuntime(0xFFFFFFFE)
+        );
+        assert_eq!(BtmReturnResult::runtime(5400), BtmReturnResult::EstimatedRuntime(5400));
+        assert_eq!(
+            <[u8; BTM_RETURN_SIZE_BYTES]>::from(BtmReturnResult::runtime(5400)),
+            5400u32.to_le_bytes()
+        );
+        assert_eq!(<[u8; BTM_RETURN_SIZE_BYTES]>::from(BtmReturnResult::runtime(0)), [0; 4]);
+        assert_eq!(
+            <[u8; BTM_RETURN_SIZE_BYTES]>::from(BtmReturnResult::runtime(0xFFFFFFFF)),
+            0xFFFFFFFEu32.to_le_bytes()
+        );
+        assert_eq!(<[u8; BTM_RETURN_SIZE_BYTES]>::from(BtmReturnResult::Unknown), [0xFF; 4]);
+    }
+
+    #[test]
+    fn bmd_sentinels_decode_and_serialize() {
+        let sentinels = Bmd {
+            recalibrate_count: 0,
+            quick_recalibrate_time: 0,
+            slow_recalibrate_time: ACPI_UNKNOWN,
+            ..Default::default()
+        };
+        assert_eq!(sentinels.recalibrate_after_cycles(), None);
+        assert_eq!(sentinels.quick_recalibrate(), RecalibrateTime::NotSupported);
+        assert_eq!(sentinels.slow_recalibrate(), RecalibrateTime::Unknown);
+        let mut expected = [0u8; BMD_RETURN_SIZE_BYTES];
+        expected[16..].copy_from_slice(&[0xFF; 4]);
+        assert_bytes_round_trip(sentinels, expected);
+
+        let timed = Bmd {
+            recalibrate_count: 300,
+            quick_recalibrate_time: ACPI_UNKNOWN,
+            slow_recalibrate_time: 0,
+            ..Default::default()
+        };
+        assert_eq!(timed.recalibrate_after_cycles(), Some(300));
+        assert_eq!(timed.quick_recalibrate(), RecalibrateTime::Unknown);
+        assert_eq!(timed.slow_recalibrate(), RecalibrateTime::NotSupported);
+
+        let seconds = Bmd {
+            quick_recalibrate_time: 1800,
+            slow_recalibrate_time: 0xFFFFFFFE,
+            ..Default::default()
+        };
+        assert_eq!(seconds.quick_recalibrate(), RecalibrateTime::Seconds(1800));
+        assert_eq!(seconds.slow_recalibrate(), RecalibrateTime::Seconds(0xFFFFFFFE));
+    }
+
+    #[test]
+    fn bmc_recommended_for_each_charge_alarm() {
+        let alarms = [
+            BatteryStatusFields::new().with_over_charged_alarm(true),
+            BatteryStatusFields::new().with_over_temp_alarm(true),
+            BatteryStatusFields::new().with_terminate_charge_alarm(true),
+        ];
+        for status in alarms {
+            assert_eq!(
+                BmcControlFlags::recommended_for(status),
+                BmcControlFlags::DISABLE_CHARGING
+            );
+        }
+    }
+
+    #[test]
+    fn bmc_recommended_for_other_status() {
+        assert_eq!(
+            BmcControlFlags::recommended_for(BatteryStatusFields::new()),
+            BmcControlFlags::empty()
+        );
+        let status = BatteryStatusFields::new()
+            .with_terminate_discharge_alarm(true)
+            .with_remaining_capacity_alarm(true)
+            .with_fully_discharged(true);
+        assert_eq!(BmcControlFlags::recommended_for(status), BmcControlFlags::empty());
+    }
+
+    #[test]
+    fn bst_rate_instantaneous_vs_averaged() {
+        use crate::mock::RegisterFileBattery;
+        use crate::smart_battery::Command;
+
+        let mut batt = RegisterFileBattery::new();
+        batt.set(Command::Current, (-1500i16) as u16);
+        batt.set(Command::AverageCurrent, (-900i16) as u16);
+
+        let instantaneous = bst_from_battery(&mut batt).unwrap();
+        let averaged = bst_from_battery_averaged(&mut batt).unwrap();
+        assert_eq!(instantaneous.battery_present_rate, 1500);
+        assert_eq!(averaged.battery_present_rate, 900);
+        assert_eq!(
+            instantaneous.battery_remaining_capacity,
+            averaged.battery_remaining_capacity
+        );
+        assert_eq!(instantaneous.battery_present_voltage, averaged.battery_present_voltage);
+
+        // In 10mWh mode the rate is converted with the present voltage (11.4V).
+        batt.set(Command::BatteryMode, 1 << 15);
+        assert_eq!(bst_from_battery(&mut batt).unwrap().battery_present_rate, 17_100);
+        assert_eq!(
+            bst_from_battery_averaged(&mut batt).unwrap().battery_present_rate,
+            10_260
+        );
+    }
+}
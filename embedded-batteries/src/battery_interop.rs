@@ -0,0 +1,75 @@
+//! Conversions to the `battery` crate's types, for reusing desktop tooling built on it.
+
+use crate::acpi::BatteryState;
+use crate::smart_battery::Chemistry;
+
+impl From<BatteryState> for battery::State {
+    /// Maps an ACPI battery state to a `battery` crate state.
+    ///
+    /// A battery that is neither charging nor discharging is reported as `Full`, since ACPI batteries typically
+    /// go idle once charged, or as `Empty` if it is also CRITICAL. An invalid state with both CHARGING and
+    /// DISCHARGING set is reported as `Unknown`.
+    fn from(state: BatteryState) -> Self {
+        let charging = state.contains(BatteryState::CHARGING);
+        let discharging = state.contains(BatteryState::DISCHARGING);
+        match (charging, discharging) {
+            (true, true) => Self::Unknown,
+            (true, false) => Self::Charging,
+            (false, true) => Self::Discharging,
+            (false, false) if state.contains(BatteryState::CRITICAL) => Self::Empty,
+            (false, false) => Self::Full,
+        }
+    }
+}
+
+impl From<Chemistry> for battery::Technology {
+    /// Maps a cell chemistry to a `battery` crate technology. Zinc air has no equivalent and maps to `Unknown`.
+    fn from(chemistry: Chemistry) -> Self {
+        match chemistry {
+            Chemistry::LeadAcid => Self::LeadAcid,
+            Chemistry::LithiumIon => Self::LithiumIon,
+            Chemistry::LithiumPolymer => Self::LithiumPolymer,
+            Chemistry::NickelCadmium => Self::NickelCadmium,
+            Chemistry::NickelMetalHydride => Self::NickelMetalHydride,
+            Chemistry::NickelZinc => Self::NickelZinc,
+            Chemistry::RechargeableAlkalineManganese => Self::RechargeableAlkalineManganese,
+            Chemistry::ZincAir => Self::Unknown,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_mappings() {
+        assert_eq!(battery::State::from(BatteryState::CHARGING), battery::State::Charging);
+        assert_eq!(
+            battery::State::from(BatteryState::DISCHARGING | BatteryState::CRITICAL),
+            battery::State::Discharging
+        );
+        assert_eq!(battery::State::from(BatteryState::empty()), battery::State::Full);
+        assert_eq!(battery::State::from(BatteryState::CRITICAL), battery::State::Empty);
+        assert_eq!(
+            battery::State::from(BatteryState::CHARGING | BatteryState::DISCHARGING),
+            battery::State::Unknown
+        );
+    }
+
+    #[test]
+    fn technology_mappings() {
+        assert_eq!(
+            battery::Technology::from(Chemistry::LithiumIon),
+            battery::Technology::LithiumIon
+        );
+        assert_eq!(
+            battery::Technology::from(Chemistry::NickelMetalHydride),
+            battery::Technology::NickelMetalHydride
+        );
+        assert_eq!(
+            battery::Technology::from(Chemistry::ZincAir),
+            battery::Technology::Unknown
+        );
+    }
+}
@@ -1,5 +1,38 @@
+use crate::smart_battery::{CapacityModeValue, SmartBattery};
 use crate::{MilliAmps, MilliVolts};
 
+/// Converts whole amps to `MilliAmps`, saturating at `MilliAmps::MAX` (65.535A).
+pub const fn amps(a: u16) -> MilliAmps {
+    a.saturating_mul(1000)
+}
+
+/// Converts amps plus a milliamp remainder (e.g. `amps_milli(1, 500)` for 1.5A) to `MilliAmps`, saturating at
+/// `MilliAmps::MAX`.
+pub const fn amps_milli(a: u16, ma: u16) -> MilliAmps {
+    amps(a).saturating_add(ma)
+}
+
+/// Converts centiamps (10mA) to `MilliAmps`, saturating at `MilliAmps::MAX`.
+pub const fn centiamps(ca: u16) -> MilliAmps {
+    ca.saturating_mul(10)
+}
+
+/// Converts whole volts to `MilliVolts`, saturating at `MilliVolts::MAX` (65.535V).
+pub const fn volts(v: u16) -> MilliVolts {
+    v.saturating_mul(1000)
+}
+
+/// Converts volts plus a millivolt remainder (e.g. `volts_milli(12, 600)` for 12.6V) to `MilliVolts`,
+/// saturating at `MilliVolts::MAX`.
+pub const fn volts_milli(v: u16, mv: u16) -> MilliVolts {
+    volts(v).saturating_add(mv)
+}
+
+/// Converts centivolts (10mV) to `MilliVolts`, saturating at `MilliVolts::MAX`.
+pub const fn centivolts(cv: u16) -> MilliVolts {
+    cv.saturating_mul(10)
+}
+
 /// Charger error.
 pub trait Error: core::fmt::Debug {
     /// Convert error to a generic charger error kind.
@@ -30,6 +63,9 @@ pub enum ErrorKind {
     /// e.g. An I2C bus error occurs for an I2C enabled smart charger.
     /// The original error may contain more information.
     CommError,
+    /// The charger is refusing to charge (e.g. thermal, watchdog or input fault), even if it acknowledges
+    /// charging setpoints.
+    ChargeInhibited,
     /// A different error occurred. The original error may contain more information.
     Other,
 }
@@ -46,6 +82,7 @@ impl core::fmt::Display for ErrorKind {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Self::CommError => write!(f, "Error communicating with charger"),
+            Self::ChargeInhibited => write!(f, "Charging is inhibited by the charger"),
             Self::Other => write!(
                 f,
                 "A different error occurred. The original error may contain more information"
@@ -66,6 +103,119 @@ impl<T: ErrorType + ?Sized> ErrorType for &mut T {
     type Error = T::Error;
 }
 
+/// Type of the input power source currently feeding the charger.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum InputSource {
+    /// No input source is connected.
+    None,
+    /// USB without Power Delivery negotiation (e.g. BC1.2 or Type-C current).
+    Usb,
+    /// USB Power Delivery.
+    UsbPd,
+    /// Barrel jack adapter.
+    Barrel,
+    /// Wireless charging pad.
+    Wireless,
+    /// Another source, or the charger does not report its input source.
+    Other,
+}
+
+/// Reason a charger is refusing to charge, as derived from its status bits.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum InhibitReason {
+    /// The battery or charger is too hot to charge.
+    ThermalHot,
+    /// The battery is too cold to charge.
+    ThermalCold,
+    /// The charger's watchdog expired because the host stopped refreshing its setpoints.
+    Watchdog,
+    /// The battery or charger output voltage is above its limit.
+    Overvoltage,
+    /// The input source is missing, out of range or current limited.
+    InputFault,
+    /// Another reason, or the charger doesn't report why it is inhibited.
+    Other,
+}
+
+/// Error returned by helpers that drive a charger from Smart Battery readings.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ChargerBatteryError<C, B> {
+    /// The charger returned an error.
+    Charger(C),
+    /// The Smart Battery returned an error.
+    Battery(B),
+    /// The Smart Battery reports capacities in 10mWh (CAPACITY_MODE bit set), but the helper requires mAh.
+    CapacityMode,
+}
+
+/// A charging setpoint as requested by the caller and as acknowledged by the charger.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Setpoint<T> {
+    /// Value sent to the charger.
+    pub requested: T,
+    /// Value reported back by the charger.
+    pub acknowledged: T,
+}
+
+impl<T: PartialEq> Setpoint<T> {
+    /// Returns `true` if the charger acknowledged a different value than was requested (e.g. it clamped it).
+    pub fn is_clamped(&self) -> bool {
+        self.requested != self.acknowledged
+    }
+}
+
+/// Charging setpoints negotiated between a Smart Battery and a charger by `negotiate_charge()`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ChargingProfile {
+    /// Charging current requested by the battery and acknowledged by the charger.
+    pub current: Setpoint<MilliAmps>,
+    /// Charging voltage requested by the battery and acknowledged by the charger, or `None` if the battery
+    /// requested a charging current of 0 and charging was stopped without writing the voltage.
+    pub voltage: Option<Setpoint<MilliVolts>>,
+}
+
+impl ChargingProfile {
+    /// Returns `true` if the charger acknowledged a charging current of 0, i.e. it isn't charging.
+    pub const fn is_stopped(&self) -> bool {
+        self.current.acknowledged == 0
+    }
+}
+
+/// Charging current command that keeps "charge off" distinct from a charging current.
+///
+/// SBS chargers are turned off by a ChargingCurrent() of 0. This type makes that intent explicit.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ChargeCommand {
+    /// Charging is turned off (a charging current of 0).
+    Off,
+    /// Charge with the given current, in mA. A current of 0 is treated as `Off`.
+    Charge(MilliAmps),
+}
+
+impl From<MilliAmps> for ChargeCommand {
+    fn from(current: MilliAmps) -> Self {
+        match current {
+            0 => Self::Off,
+            current => Self::Charge(current),
+        }
+    }
+}
+
+impl From<ChargeCommand> for MilliAmps {
+    fn from(command: ChargeCommand) -> Self {
+        match command {
+            ChargeCommand::Off => 0,
+            ChargeCommand::Charge(current) => current,
+        }
+    }
+}
+
 /// Blocking Smart Battery Charger methods
 pub trait Charger: ErrorType {
     /// Sets the maximum current that a Smart Battery Charger may deliver to
@@ -85,6 +235,167 @@ pub trait Charger: ErrorType {
     /// charge. The Smart Battery can effectively turn off the Smart Battery Charger by returning a value of 0 for
     /// this function.
     fn charging_voltage(&mut self, voltage: MilliVolts) -> Result<MilliVolts, Self::Error>;
+
+    /// Returns the highest charging current the charger can deliver, so callers can clamp setpoints before
+    /// commanding them.
+    ///
+    /// Chargers that don't report their ceiling return `MilliAmps::MAX`.
+    fn max_charging_current(&mut self) -> Result<MilliAmps, Self::Error> {
+        Ok(MilliAmps::MAX)
+    }
+
+    /// Returns the highest charging voltage the charger can deliver, so callers can clamp setpoints before
+    /// commanding them.
+    ///
+    /// Chargers that don't report their ceiling return `MilliVolts::MAX`.
+    fn max_charging_voltage(&mut self) -> Result<MilliVolts, Self::Error> {
+        Ok(MilliVolts::MAX)
+    }
+
+    /// Returns the type of the input power source currently feeding the charger, for use in power policy
+    /// decisions such as input current limits.
+    ///
+    /// Chargers that don't report their input source return `InputSource::Other`.
+    fn input_source(&mut self) -> Result<InputSource, Self::Error> {
+        Ok(InputSource::Other)
+    }
+
+    /// Receives a ChargingCurrent() (0x14) broadcast from the Smart Battery.
+    ///
+    /// In SBS, the battery acts as an SMBus master and periodically broadcasts its desired charging current to
+    /// the charger. This models that battery-to-charger path, as opposed to the host setting the current with
+    /// `charging_current()`. The default forwards to `charging_current()`, discarding the acknowledged value.
+    fn receive_charging_current(&mut self, current: MilliAmps) -> Result<(), Self::Error> {
+        self.charging_current(current).map(|_| ())
+    }
+
+    /// Receives a ChargingVoltage() (0x15) broadcast from the Smart Battery.
+    ///
+    /// In SBS, the battery acts as an SMBus master and periodically broadcasts its desired charging voltage to
+    /// the charger. This models that battery-to-charger path, as opposed to the host setting the voltage with
+    /// `charging_voltage()`. The default forwards to `charging_voltage()`, discarding the acknowledged value.
+    fn receive_charging_voltage(&mut self, voltage: MilliVolts) -> Result<(), Self::Error> {
+        self.charging_voltage(voltage).map(|_| ())
+    }
+
+    /// Sets the charging current to a C-rate fraction of the battery's full charge capacity, returning the
+    /// current acknowledged by the charger.
+    ///
+    /// `c_rate_milli` is the C-rate in thousandths (e.g. 500 for 0.5C), so the requested current is
+    /// `full_charge_capacity() * c_rate_milli / 1000` mA, clamped to `max_charging_current()`. The battery must
+    /// report capacities in mAh (CAPACITY_MODE bit clear), otherwise `ChargerBatteryError::CapacityMode` is
+    /// returned.
+    fn set_charge_c_rate<B: SmartBattery>(
+        &mut self,
+        batt: &mut B,
+        c_rate_milli: u16,
+    ) -> Result<MilliAmps, ChargerBatteryError<Self::Error, B::Error>> {
+        let capacity = match batt.full_charge_capacity().map_err(ChargerBatteryError::Battery)? {
+            CapacityModeValue::MilliAmpUnsigned(capacity) => capacity,
+            CapacityModeValue::CentiWattUnsigned(_) => return Err(ChargerBatteryError::CapacityMode),
+        };
+        let current = u32::from(capacity) * u32::from(c_rate_milli) / 1000;
+        let max_current = self.max_charging_current().map_err(ChargerBatteryError::Charger)?;
+        let current = MilliAmps::try_from(current).unwrap_or(MilliAmps::MAX).min(max_current);
+        self.charging_current(current).map_err(ChargerBatteryError::Charger)
+    }
+
+    /// Sets the charging current from a `ChargeCommand`, returning the command acknowledged by the charger.
+    ///
+    /// `ChargeCommand::Off` is sent as a charging current of 0, and an acknowledged current of 0 is returned as
+    /// `ChargeCommand::Off`.
+    fn set_charge(&mut self, command: ChargeCommand) -> Result<ChargeCommand, Self::Error> {
+        self.charging_current(command.into()).map(ChargeCommand::from)
+    }
+
+    /// Returns `true` if the charger is currently refusing to charge (e.g. thermal, watchdog or input fault).
+    ///
+    /// An inhibited charger may still acknowledge charging setpoints, typically as 0. The default reports
+    /// whether `charge_inhibit_reason()` returns a reason.
+    fn charge_inhibited(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.charge_inhibit_reason()?.is_some())
+    }
+
+    /// Returns why the charger is currently refusing to charge, or `None` if charging is allowed.
+    ///
+    /// Chargers that can't report inhibition return `None`.
+    fn charge_inhibit_reason(&mut self) -> Result<Option<InhibitReason>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Returns the current the charger is presently delivering to the battery in mA, or `None` if the charger can't
+    /// measure its output.
+    ///
+    /// Unlike the setpoint acknowledged by `charging_current()`, this is the measured output current, which stays 0
+    /// while the charger is inhibited or has no input power. Chargers without an output current monitor return
+    /// `None`.
+    fn delivered_current(&mut self) -> Result<Option<MilliAmps>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Sets the charging current like `charging_current()`, returning both the requested and the acknowledged
+    /// value so callers can detect clamping.
+    fn apply_charging_current(&mut self, current: MilliAmps) -> Result<Setpoint<MilliAmps>, Self::Error> {
+        Ok(Setpoint {
+            requested: current,
+            acknowledged: self.charging_current(current)?,
+        })
+    }
+
+    /// Sets the charging voltage like `charging_voltage()`, returning both the requested and the acknowledged
+    /// value so callers can detect clamping.
+    fn apply_charging_voltage(&mut self, voltage: MilliVolts) -> Result<Setpoint<MilliVolts>, Self::Error> {
+        Ok(Setpoint {
+            requested: voltage,
+            acknowledged: self.charging_voltage(voltage)?,
+        })
+    }
+
+    /// Returns an error of kind `ErrorKind::ChargeInhibited` if `charge_inhibited()` reports that the charger
+    /// is refusing to charge, turning a silently acknowledged 0 into an actionable error.
+    fn ensure_charging_allowed(&mut self) -> Result<(), Self::Error>
+    where
+        Self::Error: From<ErrorKind>,
+    {
+        if self.charge_inhibited()? {
+            Err(ErrorKind::ChargeInhibited.into())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Applies the Smart Battery's charging request to the charger, following the SBS charging algorithm, and returns
+/// the negotiated profile.
+///
+/// The battery's desired ChargingVoltage() (0x15) and ChargingCurrent() (0x14) are read and written to the
+/// charger, voltage first so the current is never applied against a stale voltage limit. If the battery
+/// requests a current of 0, charging is stopped by writing a current of 0 and the voltage is left untouched.
+/// Hosts should call this periodically, since the battery updates its request as it charges.
+pub fn negotiate_charge<B: SmartBattery, C: Charger>(
+    batt: &mut B,
+    charger: &mut C,
+) -> Result<ChargingProfile, ChargerBatteryError<C::Error, B::Error>> {
+    let current = batt.charging_current().map_err(ChargerBatteryError::Battery)?;
+    if current == 0 {
+        return Ok(ChargingProfile {
+            current: charger
+                .apply_charging_current(0)
+                .map_err(ChargerBatteryError::Charger)?,
+            voltage: None,
+        });
+    }
+    let voltage = batt.charging_voltage().map_err(ChargerBatteryError::Battery)?;
+    let voltage = charger
+        .apply_charging_voltage(voltage)
+        .map_err(ChargerBatteryError::Charger)?;
+    let current = charger
+        .apply_charging_current(current)
+        .map_err(ChargerBatteryError::Charger)?;
+    Ok(ChargingProfile {
+        current,
+        voltage: Some(voltage),
+    })
 }
 
 impl<T: Charger + ?Sized> Charger for &mut T {
@@ -97,4 +408,397 @@ impl<T: Charger + ?Sized> Charger for &mut T {
     fn charging_voltage(&mut self, voltage: MilliVolts) -> Result<MilliVolts, Self::Error> {
         T::charging_voltage(self, voltage)
     }
+
+    #[inline]
+    fn max_charging_current(&mut self) -> Result<MilliAmps, Self::Error> {
+        T::max_charging_current(self)
+    }
+
+    #[inline]
+    fn max_charging_voltage(&mut self) -> Result<MilliVolts, Self::Error> {
+        T::max_charging_voltage(self)
+    }
+
+    #[inline]
+    fn input_source(&mut self) -> Result<InputSource, Self::Error> {
+        T::input_source(self)
+    }
+
+    #[inline]
+    fn receive_charging_current(&mut self, current: MilliAmps) -> Result<(), Self::Error> {
+        T::receive_charging_current(self, current)
+    }
+
+    #[inline]
+    fn receive_charging_voltage(&mut self, voltage: MilliVolts) -> Result<(), Self::Error> {
+        T::receive_charging_voltage(self, voltage)
+    }
+
+    #[inline]
+    fn charge_inhibited(&mut self) -> Result<bool, Self::Error> {
+        T::charge_inhibited(self)
+    }
+
+    #[inline]
+    fn charge_inhibit_reason(&mut self) -> Result<Option<InhibitReason>, Self::Error> {
+        T::charge_inhibit_reason(self)
+    }
+
+    #[inline]
+    fn delivered_current(&mut self) -> Result<Option<MilliAmps>, Self::Error> {
+        T::delivered_current(self)
+    }
+}
+
+/// Charger adapter that records the last requested and acknowledged charging current and voltage.
+///
+/// Since `charging_current()` and `charging_voltage()` return the value acknowledged by the charger,
+/// comparing it against the value that was requested helps diagnose chargers that silently clamp
+/// their setpoints during negotiation.
+#[derive(Debug, Clone)]
+pub struct ChargerHistory<C> {
+    charger: C,
+    current: Option<(MilliAmps, MilliAmps)>,
+    voltage: Option<(MilliVolts, MilliVolts)>,
+}
+
+impl<C> ChargerHistory<C> {
+    /// Wrap a charger, starting with an empty history.
+    pub const fn new(charger: C) -> Self {
+        Self {
+            charger,
+            current: None,
+            voltage: None,
+        }
+    }
+
+    /// Returns the last `(requested, acknowledged)` charging current pair,
+    /// or `None` if no charging current has been successfully set yet.
+    pub const fn last_current(&self) -> Option<(MilliAmps, MilliAmps)> {
+        self.current
+    }
+
+    /// Returns the last `(requested, acknowledged)` charging voltage pair,
+    /// or `None` if no charging voltage has been successfully set yet.
+    pub const fn last_voltage(&self) -> Option<(MilliVolts, MilliVolts)> {
+        self.voltage
+    }
+
+    /// Returns a reference to the wrapped charger.
+    pub const fn inner(&self) -> &C {
+        &self.charger
+    }
+
+    /// Returns a mutable reference to the wrapped charger.
+    ///
+    /// Setpoints changed directly through the wrapped charger are not recorded.
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.charger
+    }
+
+    /// Consumes the adapter, returning the wrapped charger.
+    pub fn into_inner(self) -> C {
+        self.charger
+    }
+}
+
+impl<C: ErrorType> ErrorType for ChargerHistory<C> {
+    type Error = C::Error;
+}
+
+impl<C: Charger> Charger for ChargerHistory<C> {
+    fn charging_current(&mut self, current: MilliAmps) -> Result<MilliAmps, Self::Error> {
+        let acknowledged = self.charger.charging_current(current)?;
+        self.current = Some((current, acknowledged));
+        Ok(acknowledged)
+    }
+
+    fn charging_voltage(&mut self, voltage: MilliVolts) -> Result<MilliVolts, Self::Error> {
+        let acknowledged = self.charger.charging_voltage(voltage)?;
+        self.voltage = Some((voltage, acknowledged));
+        Ok(acknowledged)
+    }
+
+    fn max_charging_current(&mut self) -> Result<MilliAmps, Self::Error> {
+        self.charger.max_charging_current()
+    }
+
+    fn max_charging_voltage(&mut self) -> Result<MilliVolts, Self::Error> {
+        self.charger.max_charging_voltage()
+    }
+
+    fn input_source(&mut self) -> Result<InputSource, Self::Error> {
+        self.charger.input_source()
+    }
+
+    fn receive_charging_current(&mut self, current: MilliAmps) -> Result<(), Self::Error> {
+        self.charger.receive_charging_current(current)
+    }
+
+    fn receive_charging_voltage(&mut self, voltage: MilliVolts) -> Result<(), Self::Error> {
+        self.charger.receive_charging_voltage(voltage)
+    }
+
+    fn charge_inhibited(&mut self) -> Result<bool, Self::Error> {
+        self.charger.charge_inhibited()
+    }
+
+    fn charge_inhibit_reason(&mut self) -> Result<Option<InhibitReason>, Self::Error> {
+        self.charger.charge_inhibit_reason()
+    }
+
+    fn delivered_current(&mut self) -> Result<Option<MilliAmps>, Self::Error> {
+        self.charger.delivered_current()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{BareCharger, ChargerWrite, MockCharger, RegisterFileBattery};
+    use crate::smart_battery::Command;
+
+    #[test]
+    fn history_starts_empty() {
+        let history = ChargerHistory::new(MockCharger::new(2000, 12600));
+        assert_eq!(history.last_current(), None);
+        assert_eq!(history.last_voltage(), None);
+    }
+
+    #[test]
+    fn history_records_requested_and_acknowledged() {
+        let mut history = ChargerHistory::new(MockCharger::new(2000, 12600));
+
+        assert_eq!(history.charging_current(1500), Ok(1500));
+        assert_eq!(history.last_current(), Some((1500, 1500)));
+
+        assert_eq!(history.charging_current(3000), Ok(2000));
+        assert_eq!(history.last_current(), Some((3000, 2000)));
+
+        assert_eq!(history.charging_voltage(13000), Ok(12600));
+        assert_eq!(history.last_voltage(), Some((13000, 12600)));
+        assert_eq!(history.inner().current, 2000);
+    }
+
+    #[test]
+    fn history_records_through_defaults() {
+        let mut history = ChargerHistory::new(MockCharger::new(2000, 12600));
+
+        let setpoint = history.apply_charging_current(2500).unwrap();
+        assert!(setpoint.is_clamped());
+        assert_eq!(history.last_current(), Some((2500, 2000)));
+
+        assert_eq!(history.set_charge(ChargeCommand::Off), Ok(ChargeCommand::Off));
+        assert_eq!(history.last_current(), Some((0, 0)));
+    }
+
+    #[test]
+    fn history_ignores_inner_mut_writes() {
+        let mut history = ChargerHistory::new(MockCharger::new(2000, 12600));
+        history.charging_current(1000).unwrap();
+        history.inner_mut().charging_current(500).unwrap();
+        assert_eq!(history.last_current(), Some((1000, 1000)));
+        assert_eq!(history.into_inner().current, 500);
+    }
+
+    #[test]
+    fn delivered_current_is_forwarded() {
+        let mut charger = MockCharger::new(2000, 12600);
+        assert_eq!(ChargerHistory::new(&mut charger).delivered_current(), Ok(None));
+        charger.delivered = Some(1200);
+        assert_eq!(ChargerHistory::new(&mut charger).delivered_current(), Ok(Some(1200)));
+    }
+
+    #[test]
+    fn negotiate_charge_writes_voltage_before_current() {
+        let mut battery = RegisterFileBattery::new();
+        let mut charger = MockCharger::new(1500, 13000);
+
+        let profile = negotiate_charge(&mut battery, &mut charger).unwrap();
+        assert_eq!(
+            charger.writes,
+            [ChargerWrite::Voltage(12_600), ChargerWrite::Current(2000)]
+        );
+        assert_eq!(
+            profile.current,
+            Setpoint {
+                requested: 2000,
+                acknowledged: 1500
+            }
+        );
+        assert!(profile.current.is_clamped());
+        let voltage = profile.voltage.unwrap();
+        assert_eq!(voltage.acknowledged, 12_600);
+        assert!(!voltage.is_clamped());
+        assert!(!profile.is_stopped());
+    }
+
+    #[test]
+    fn negotiate_charge_stops_without_writing_voltage() {
+        let mut battery = RegisterFileBattery::new();
+        battery.set(Command::ChargingCurrent, 0);
+        let mut charger = MockCharger::new(1500, 13000);
+
+        let profile = negotiate_charge(&mut battery, &mut charger).unwrap();
+        assert_eq!(charger.writes, [ChargerWrite::Current(0)]);
+        assert_eq!(profile.voltage, None);
+        assert!(profile.is_stopped());
+    }
+
+    #[test]
+    fn set_charge_c_rate_uses_full_charge_capacity() {
+        let mut battery = RegisterFileBattery::new();
+        let mut charger = MockCharger::new(5000, 13000);
+        assert_eq!(charger.set_charge_c_rate(&mut battery, 500), Ok(2000));
+        assert_eq!(charger.writes, [ChargerWrite::Current(2000)]);
+    }
+
+    #[test]
+    fn set_charge_c_rate_clamps_to_max_charging_current() {
+        let mut battery = RegisterFileBattery::new();
+        let mut charger = MockCharger::new(1500, 13000);
+        assert_eq!(charger.set_charge_c_rate(&mut battery, 500), Ok(1500));
+        assert_eq!(charger.writes, [ChargerWrite::Current(1500)]);
+
+        // 20C of 4000mAh overflows MilliAmps before clamping.
+        charger.max_current = MilliAmps::MAX;
+        assert_eq!(charger.set_charge_c_rate(&mut battery, 20_000), Ok(MilliAmps::MAX));
+    }
+
+    #[test]
+    fn set_charge_c_rate_requires_milliamp_mode() {
+        let mut battery = RegisterFileBattery::new();
+        battery.set(Command::BatteryMode, 1 << 15);
+        let mut charger = MockCharger::new(5000, 13000);
+        assert_eq!(
+            charger.set_charge_c_rate(&mut battery, 500),
+            Err(ChargerBatteryError::CapacityMode)
+        );
+        assert!(charger.writes.is_empty());
+    }
+
+    #[test]
+    fn max_charging_defaults_are_unbounded() {
+        let mut charger = BareCharger::default();
+        assert_eq!(charger.max_charging_current(), Ok(MilliAmps::MAX));
+        assert_eq!(charger.max_charging_voltage(), Ok(MilliVolts::MAX));
+    }
+
+    #[test]
+    fn max_charging_overrides_are_honored() {
+        let mut charger = MockCharger::new(3000, 12600);
+        assert_eq!(charger.max_charging_current(), Ok(3000));
+        assert_eq!(charger.max_charging_voltage(), Ok(12600));
+    }
+
+    #[test]
+    fn charge_command_maps_zero_to_off() {
+        assert_eq!(ChargeCommand::from(0), ChargeCommand::Off);
+        assert_eq!(ChargeCommand::from(1500), ChargeCommand::Charge(1500));
+        for current in [0, 1, 1500, MilliAmps::MAX] {
+            assert_eq!(MilliAmps::from(ChargeCommand::from(current)), current);
+        }
+        assert_eq!(MilliAmps::from(ChargeCommand::Off), 0);
+    }
+
+    #[test]
+    fn set_charge_maps_acknowledged_zero_to_off() {
+        let mut charger = MockCharger::new(2000, 12600);
+        assert_eq!(
+            charger.set_charge(ChargeCommand::Charge(3000)),
+            Ok(ChargeCommand::Charge(2000))
+        );
+        assert_eq!(charger.set_charge(ChargeCommand::Off), Ok(ChargeCommand::Off));
+
+        charger.max_current = 0;
+        assert_eq!(charger.set_charge(ChargeCommand::Charge(1000)), Ok(ChargeCommand::Off));
+        assert_eq!(
+            charger.writes,
+            [
+                ChargerWrite::Current(3000),
+                ChargerWrite::Current(0),
+                ChargerWrite::Current(1000)
+            ]
+        );
+    }
+
+    #[test]
+    fn setpoint_is_clamped_when_acknowledged_differs() {
+        let mut charger = MockCharger::new(2000, 12600);
+        assert!(charger.apply_charging_current(2500).unwrap().is_clamped());
+        assert!(charger.apply_charging_voltage(13000).unwrap().is_clamped());
+        assert!(!charger.apply_charging_current(1500).unwrap().is_clamped());
+        assert!(!charger.apply_charging_voltage(12600).unwrap().is_clamped());
+
+        let mut bare = BareCharger::default();
+        assert!(!bare.apply_charging_current(MilliAmps::MAX).unwrap().is_clamped());
+        assert!(!bare.apply_charging_voltage(MilliVolts::MAX).unwrap().is_clamped());
+    }
+
+    #[test]
+    fn ensure_charging_allowed_reports_inhibition() {
+        let mut charger = MockCharger::new(2000, 12600);
+        assert_eq!(charger.ensure_charging_allowed(), Ok(()));
+
+        charger.inhibit = Some(InhibitReason::Watchdog);
+        assert_eq!(charger.ensure_charging_allowed(), Err(ErrorKind::ChargeInhibited));
+
+        assert_eq!(BareCharger::default().ensure_charging_allowed(), Ok(()));
+    }
+
+    #[test]
+    fn charge_inhibited_follows_inhibit_reason() {
+        let mut charger = MockCharger::new(2000, 12600);
+        assert_eq!(charger.charge_inhibit_reason(), Ok(None));
+        assert_eq!(charger.charge_inhibited(), Ok(false));
+
+        charger.inhibit = Some(InhibitReason::ThermalHot);
+        assert_eq!(charger.charge_inhibit_reason(), Ok(Some(InhibitReason::ThermalHot)));
+        assert_eq!(charger.charge_inhibited(), Ok(true));
+    }
+
+    #[test]
+    fn charge_inhibit_reason_defaults_to_none() {
+        let mut charger = BareCharger::default();
+        assert_eq!(charger.charge_inhibit_reason(), Ok(None));
+        assert_eq!(charger.charge_inhibited(), Ok(false));
+    }
+
+    #[test]
+    fn receive_charging_defaults_forward_broadcasts() {
+        let mut charger = BareCharger::default();
+        assert_eq!(charger.receive_charging_voltage(12_600), Ok(()));
+        assert_eq!(charger.receive_charging_current(2000), Ok(()));
+        assert_eq!(
+            charger.writes,
+            [ChargerWrite::Voltage(12_600), ChargerWrite::Current(2000)]
+        );
+    }
+
+    #[test]
+    fn input_source_defaults_to_other() {
+        assert_eq!(BareCharger::default().input_source(), Ok(InputSource::Other));
+    }
+
+    #[test]
+    fn input_source_override_is_honored() {
+        let mut charger = MockCharger::new(2000, 12600);
+        charger.input = InputSource::UsbPd;
+        assert_eq!(charger.input_source(), Ok(InputSource::UsbPd));
+        assert_eq!(ChargerHistory::new(&mut charger).input_source(), Ok(InputSource::UsbPd));
+    }
+
+    #[test]
+    fn unit_helpers_convert_and_saturate() {
+        assert_eq!(volts(12), 12_000);
+        assert_eq!(volts_milli(12, 600), 12_600);
+        assert_eq!(amps_milli(1, 500), 1500);
+        assert_eq!(centiamps(150), 1500);
+        assert_eq!(centivolts(1260), 12_600);
+
+        assert_eq!(volts(66), MilliVolts::MAX);
+        assert_eq!(amps(66), MilliAmps::MAX);
+        assert_eq!(amps_milli(65, 600), MilliAmps::MAX);
+        assert_eq!(centiamps(6554), MilliAmps::MAX);
+    }
 }
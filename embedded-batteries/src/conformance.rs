@@ -0,0 +1,222 @@
+use crate::smart_battery::{CapacityModeValue, ErrorCode, SmartBattery};
+
+/// Plausibility check failed by a `SmartBattery` implementation.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum Violation {
+    /// At least one read returned an error, so the checks depending on it were skipped.
+    ReadFailed,
+    /// relative_state_of_charge() (0x0D) is above 100%.
+    RelativeStateOfChargeAbove100,
+    /// max_error() (0x0C) is above 100%.
+    MaxErrorAbove100,
+    /// remaining_capacity() (0x0F), full_charge_capacity() (0x10) and design_capacity() (0x18) are not all
+    /// in the same unit.
+    CapacityUnitMismatch,
+    /// remaining_capacity() (0x0F) is above full_charge_capacity() (0x10).
+    RemainingAboveFull,
+    /// full_charge_capacity() (0x10) is above design_capacity() (0x18).
+    FullAboveDesign,
+    /// temperature() (0x08) is outside `PLAUSIBLE_TEMPERATURE`.
+    TemperatureImplausible,
+    /// battery_status() (0x16) reports an error code even though every read succeeded.
+    StatusErrorCode,
+    /// relative_state_of_charge() (0x0D) differs from remaining_capacity() (0x0F) as a percentage of
+    /// full_charge_capacity() (0x10) by more than max_error() (0x0C) plus 1% for rounding.
+    StateOfChargeMismatch,
+}
+
+impl Violation {
+    const ALL: [Self; 9] = [
+        Self::ReadFailed,
+        Self::RelativeStateOfChargeAbove100,
+        Self::MaxErrorAbove100,
+        Self::CapacityUnitMismatch,
+        Self::RemainingAboveFull,
+        Self::FullAboveDesign,
+        Self::TemperatureImplausible,
+        Self::StatusErrorCode,
+        Self::StateOfChargeMismatch,
+    ];
+
+    const fn bit(self) -> u16 {
+        1 << self as u8
+    }
+}
+
+/// Plausible temperature range of a battery pack, in decikelvins (-40 °C to 85 °C).
+pub const PLAUSIBLE_TEMPERATURE: core::ops::RangeInclusive<u16> = 2331..=3581;
+
+/// Set of violations found by `check_conformance()`.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ConformanceReport {
+    violations: u16,
+}
+
+impl ConformanceReport {
+    /// Returns `true` if no violations were found.
+    pub const fn is_conformant(&self) -> bool {
+        self.violations == 0
+    }
+
+    /// Returns `true` if `violation` was found.
+    pub const fn contains(&self, violation: Violation) -> bool {
+        self.violations & violation.bit() != 0
+    }
+
+    /// Returns an iterator over the violations found, in declaration order of `Violation`.
+    pub fn iter(&self) -> impl Iterator<Item = Violation> + '_ {
+        Violation::ALL.into_iter().filter(|&violation| self.contains(violation))
+    }
+
+    fn flag(&mut self, violation: Violation, violated: bool) {
+        if violated {
+            self.violations |= violation.bit();
+        }
+    }
+}
+
+/// Reads the registers of a Smart Battery and reports values that are implausible or inconsistent with each
+/// other, for validating a driver against real hardware or a simulator.
+///
+/// Violations are collected into the report rather than returned as errors. A failing read is reported as
+/// `Violation::ReadFailed` and skips the checks that need its value. Note that absolute_state_of_charge() (0x0E)
+/// may legitimately exceed 100% and isn't checked.
+pub fn check_conformance<B: SmartBattery>(batt: &mut B) -> ConformanceReport {
+    let mut report = ConformanceReport::default();
+    let mut read_failed = false;
+
+    let relative_state_of_charge = read(batt.relative_state_of_charge(), &mut read_failed);
+    let max_error = read(batt.max_error(), &mut read_failed);
+    let temperature = read(batt.temperature(), &mut read_failed);
+    let remaining = read(batt.remaining_capacity(), &mut read_failed);
+    let full = read(batt.full_charge_capacity(), &mut read_failed);
+    let design = read(batt.design_capacity(), &mut read_failed);
+    let status = read(batt.battery_status(), &mut read_failed);
+
+    if let Some(soc) = relative_state_of_charge {
+        report.flag(Violation::RelativeStateOfChargeAbove100, soc > 100);
+    }
+    if let Some(max_error) = max_error {
+        report.flag(Violation::MaxErrorAbove100, max_error > 100);
+    }
+    if let Some(temperature) = temperature {
+        report.flag(
+            Violation::TemperatureImplausible,
+            !PLAUSIBLE_TEMPERATURE.contains(&temperature),
+        );
+    }
+    if let (Some(remaining), Some(full), Some(design)) = (remaining, full, design) {
+        match (remaining, full, design) {
+            (
+                CapacityModeValue::MilliAmpUnsigned(remaining),
+                CapacityModeValue::MilliAmpUnsigned(full),
+                CapacityModeValue::MilliAmpUnsigned(design),
+            )
+            | (
+                CapacityModeValue::CentiWattUnsigned(remaining),
+                CapacityModeValue::CentiWattUnsigned(full),
+                CapacityModeValue::CentiWattUnsigned(design),
+            ) => {
+                report.flag(Violation::RemainingAboveFull, remaining > full);
+                report.flag(Violation::FullAboveDesign, full > design);
+                if let (Some(soc), Some(max_error)) = (relative_state_of_charge, max_error) {
+                    if full != 0 {
+                        let expected = (u32::from(remaining) * 100 + u32::from(full) / 2) / u32::from(full);
+                        report.flag(
+                            Violation::StateOfChargeMismatch,
+                            expected.abs_diff(u32::from(soc)) > u32::from(max_error) + 1,
+                        );
+                    }
+                }
+            }
+            _ => report.flag(Violation::CapacityUnitMismatch, true),
+        }
+    }
+    if let Some(status) = status {
+        report.flag(
+            Violation::StatusErrorCode,
+            !read_failed && status.error_code() != ErrorCode::Ok,
+        );
+    }
+    report.flag(Violation::ReadFailed, read_failed);
+    report
+}
+
+fn read<T, E>(result: Result<T, E>, failed: &mut bool) -> Option<T> {
+    *failed |= result.is_err();
+    result.ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::RegisterFileBattery;
+    use crate::smart_battery::Command;
+
+    #[test]
+    fn consistent_battery_is_conformant() {
+        let report = check_conformance(&mut RegisterFileBattery::new());
+        assert!(report.is_conformant());
+        assert_eq!(report.iter().count(), 0);
+    }
+
+    #[test]
+    fn contradictory_capacities_are_reported() {
+        let mut battery = RegisterFileBattery::new();
+        // remaining > full > design, and 75% doesn't match 4500 / 4400.
+        battery.set(Command::RemainingCapacity, 4500);
+        battery.set(Command::FullChargeCapacity, 4400);
+        battery.set(Command::DesignCapacity, 4300);
+
+        let report = check_conformance(&mut battery);
+        assert!(report.contains(Violation::RemainingAboveFull));
+        assert!(report.contains(Violation::FullAboveDesign));
+        assert!(report.contains(Violation::StateOfChargeMismatch));
+        assert!(!report.contains(Violation::ReadFailed));
+        assert_eq!(report.iter().count(), 3);
+    }
+
+    #[test]
+    fn state_of_charge_mismatch_allows_max_error() {
+        let mut battery = RegisterFileBattery::new();
+        battery.set(Command::RelativeStateOfCharge, 80);
+        assert!(check_conformance(&mut battery).contains(Violation::StateOfChargeMismatch));
+
+        battery.set(Command::MaxError, 5);
+        assert!(check_conformance(&mut battery).is_conformant());
+    }
+
+    #[test]
+    fn out_of_range_values_are_reported() {
+        let mut battery = RegisterFileBattery::new();
+        battery.set(Command::RelativeStateOfCharge, 101);
+        battery.set(Command::MaxError, 101);
+        battery.set(Command::Temperature, 2000);
+
+        let report = check_conformance(&mut battery);
+        assert!(report.iter().eq([
+            Violation::RelativeStateOfChargeAbove100,
+            Violation::MaxErrorAbove100,
+            Violation::TemperatureImplausible,
+        ]));
+    }
+
+    #[test]
+    fn status_error_code_is_reported() {
+        let mut battery = RegisterFileBattery::new();
+        battery.set(Command::BatteryStatus, 0x0080 | ErrorCode::UnsupportedCmd as u16);
+        assert!(check_conformance(&mut battery).iter().eq([Violation::StatusErrorCode]));
+    }
+
+    #[test]
+    fn failed_read_skips_dependent_checks() {
+        let mut battery = RegisterFileBattery::new();
+        battery.set(Command::BatteryStatus, 0x0080 | ErrorCode::UnsupportedCmd as u16);
+        battery.set(Command::Temperature, 2000);
+        battery.failing = Some(Command::Temperature);
+        assert!(check_conformance(&mut battery).iter().eq([Violation::ReadFailed]));
+    }
+}
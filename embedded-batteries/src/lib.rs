@@ -1,7 +1,10 @@
 #![doc = include_str!("../README.md")]
-#![no_std]
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 // Shared types
 
 /// Charging current is measured in milliamps, where 1mA is 1
@@ -16,6 +19,42 @@ pub type MilliAmpsSigned = i16;
 /// Charging voltage is measured in millivolts, where 1mV is 1
 pub type MilliVoltsSigned = i16;
 
+// Shared math
+
+/// Returns `part` as a percentage of `whole`, rounded to the nearest percent.
+///
+/// The intermediate is widened to `u32` so it can't overflow. Results above `u8::MAX` and a `whole` of 0
+/// saturate at `u8::MAX`.
+pub(crate) const fn percent_u16(part: u16, whole: u16) -> u8 {
+    if whole == 0 {
+        return u8::MAX;
+    }
+    let whole = whole as u32;
+    let percent = (part as u32 * 100 + whole / 2) / whole;
+    if percent > u8::MAX as u32 {
+        u8::MAX
+    } else {
+        percent as u8
+    }
+}
+
+/// Returns `part` in thousandths of `whole`, rounded to the nearest thousandth.
+///
+/// The intermediate is widened to `u64` so it can't overflow. Results above `u16::MAX` and a `whole` of 0
+/// saturate at `u16::MAX`.
+pub(crate) const fn permille_u32(part: u32, whole: u32) -> u16 {
+    if whole == 0 {
+        return u16::MAX;
+    }
+    let whole = whole as u64;
+    let permille = (part as u64 * 1000 + whole / 2) / whole;
+    if permille > u16::MAX as u64 {
+        u16::MAX
+    } else {
+        permille as u16
+    }
+}
+
 /// Blocking Smart Battery Charger module
 pub mod charger;
 
@@ -25,3 +64,66 @@ pub mod smart_battery;
 /// Advanced Configuration and Power Interface (ACPI)
 /// Power Source and Power Meter Devices module
 pub mod acpi;
+
+/// Host-side plausibility checks for Smart Battery implementations
+pub mod conformance;
+
+#[cfg(feature = "battery-interop")]
+mod battery_interop;
+
+#[cfg(test)]
+mod mock;
+
+// Lets the unit tests expand the exported macros, which refer to the crate by name.
+#[cfg(test)]
+extern crate self as embedded_batteries;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_u16_rounds_to_nearest() {
+        assert_eq!(percent_u16(50, 200), 25);
+        assert_eq!(percent_u16(1, 3), 33);
+        assert_eq!(percent_u16(2, 3), 67);
+        assert_eq!(percent_u16(0, 1), 0);
+    }
+
+    #[test]
+    fn percent_u16_widens_and_saturates() {
+        // 65535 * 100 overflows u16 and 65535 * 100 / 1 doesn't fit in u8.
+        assert_eq!(percent_u16(u16::MAX, u16::MAX), 100);
+        assert_eq!(percent_u16(u16::MAX - 1, u16::MAX), 100);
+        assert_eq!(percent_u16(255, 100), 255);
+        assert_eq!(percent_u16(256, 100), u8::MAX);
+        assert_eq!(percent_u16(u16::MAX, 1), u8::MAX);
+    }
+
+    #[test]
+    fn percent_u16_zero_whole_saturates() {
+        assert_eq!(percent_u16(0, 0), u8::MAX);
+        assert_eq!(percent_u16(10, 0), u8::MAX);
+    }
+
+    #[test]
+    fn permille_u32_rounds_to_nearest() {
+        assert_eq!(permille_u32(500, 5000), 100);
+        assert_eq!(permille_u32(1, 3), 333);
+        assert_eq!(permille_u32(2, 3), 667);
+    }
+
+    #[test]
+    fn permille_u32_widens_and_saturates() {
+        // u32::MAX * 1000 overflows u32.
+        assert_eq!(permille_u32(u32::MAX, u32::MAX), 1000);
+        assert_eq!(permille_u32(65_535, 1000), u16::MAX);
+        assert_eq!(permille_u32(u32::MAX, 1), u16::MAX);
+    }
+
+    #[test]
+    fn permille_u32_zero_whole_saturates() {
+        assert_eq!(permille_u32(0, 0), u16::MAX);
+        assert_eq!(permille_u32(10, 0), u16::MAX);
+    }
+}
@@ -1,5 +1,7 @@
 use bitfield_struct::bitfield;
+use bitflags::bitflags;
 
+use crate::acpi::PowerUnit;
 use crate::{MilliAmps, MilliAmpsSigned, MilliVolts};
 
 /// Smart Battery error.
@@ -34,6 +36,14 @@ pub enum ErrorKind {
     CommError,
     /// An error occured and was reported by a read from the BatteryStatus (0x16) register.
     BatteryStatus(ErrorCode),
+    /// The battery reported an SBS specification version older than the caller requires.
+    UnsupportedVersion(Version),
+    /// A read only bit of BatteryMode() (0x03) changed across a write, indicating a gauge fault.
+    ReadOnlyModeChanged,
+    /// The function is optional in the SBS spec and isn't implemented by this driver.
+    ///
+    /// Unlike `BatteryStatus(ErrorCode::UnsupportedCmd)`, no command was sent to the battery.
+    Unsupported,
     /// A different error occurred. The original error may contain more information.
     Other,
 }
@@ -54,6 +64,9 @@ impl core::fmt::Display for ErrorKind {
                 f,
                 "Error reported by BatteryService (0x16) register. The original error may contain more information"
             ),
+            Self::UnsupportedVersion(_) => write!(f, "Smart Battery reports an unsupported SBS specification version"),
+            Self::ReadOnlyModeChanged => write!(f, "A read only BatteryMode (0x03) bit changed on write"),
+            Self::Unsupported => write!(f, "Optional Smart Battery function not implemented by this driver"),
             Self::Other => write!(
                 f,
                 "A different error occurred. The original error may contain more information"
@@ -74,6 +87,56 @@ impl<T: ErrorType + ?Sized> ErrorType for &mut T {
     type Error = T::Error;
 }
 
+/// Energy in units of 10mWh, as used by capacities when the CAPACITY_MODE bit is set.
+///
+/// The SBS spec calls this unit a "centiwatt-hour", but one unit is 10mWh (a hundredth of a watt-hour), not a
+/// hundredth of a milliwatt-hour. Use `as_mwh()` and `from_mwh()` instead of scaling the raw value by hand.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TenMilliWattHours(pub u16);
+
+impl TenMilliWattHours {
+    /// Returns the energy in mWh.
+    pub const fn as_mwh(self) -> u32 {
+        self.0 as u32 * 10
+    }
+
+    /// Converts an energy in mWh, rounding down and saturating at `u16::MAX` units (655350mWh).
+    pub const fn from_mwh(mwh: u32) -> Self {
+        let units = mwh / 10;
+        Self(if units > u16::MAX as u32 {
+            u16::MAX
+        } else {
+            units as u16
+        })
+    }
+}
+
+/// Power in units of 10mW, as used by rates when the CAPACITY_MODE bit is set.
+///
+/// The SBS spec calls this unit a "centiwatt", but one unit is 10mW (a hundredth of a watt), not a hundredth of
+/// a milliwatt. Use `as_mw()` and `from_mw()` instead of scaling the raw value by hand.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TenMilliWatts(pub u16);
+
+impl TenMilliWatts {
+    /// Returns the power in mW.
+    pub const fn as_mw(self) -> u32 {
+        self.0 as u32 * 10
+    }
+
+    /// Converts a power in mW, rounding down and saturating at `u16::MAX` units (655350mW).
+    pub const fn from_mw(mw: u32) -> Self {
+        let units = mw / 10;
+        Self(if units > u16::MAX as u32 {
+            u16::MAX
+        } else {
+            units as u16
+        })
+    }
+}
+
 /// Depending on the value of the CapacityMode bit, the Smart Battery will use milliamps or centiwatts.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -84,9 +147,122 @@ pub enum CapacityModeValue {
     CentiWattUnsigned(u16),
 }
 
+impl CapacityModeValue {
+    /// Returns the value as a `TenMilliWattHours` energy, or `None` if it is in mAh.
+    pub const fn ten_milliwatt_hours(self) -> Option<TenMilliWattHours> {
+        match self {
+            Self::CentiWattUnsigned(value) => Some(TenMilliWattHours(value)),
+            Self::MilliAmpUnsigned(_) => None,
+        }
+    }
+
+    /// Returns the value as a `TenMilliWatts` power, or `None` if it is in mA.
+    pub const fn ten_milliwatts(self) -> Option<TenMilliWatts> {
+        match self {
+            Self::CentiWattUnsigned(value) => Some(TenMilliWatts(value)),
+            Self::MilliAmpUnsigned(_) => None,
+        }
+    }
+
+    /// Adds two values of the same unit, saturating at `u16::MAX`.
+    ///
+    /// Returns `None` if the values are expressed in different units (one in mA/mAh, the other in 10mW/10mWh).
+    pub const fn checked_add(self, other: Self) -> Option<Self> {
+        match (self, other) {
+            (Self::MilliAmpUnsigned(a), Self::MilliAmpUnsigned(b)) => Some(Self::MilliAmpUnsigned(a.saturating_add(b))),
+            (Self::CentiWattUnsigned(a), Self::CentiWattUnsigned(b)) => {
+                Some(Self::CentiWattUnsigned(a.saturating_add(b)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Subtracts `other` from a value of the same unit, saturating at 0.
+    ///
+    /// Returns `None` if the values are expressed in different units (one in mA/mAh, the other in 10mW/10mWh).
+    pub const fn checked_sub(self, other: Self) -> Option<Self> {
+        match (self, other) {
+            (Self::MilliAmpUnsigned(a), Self::MilliAmpUnsigned(b)) => Some(Self::MilliAmpUnsigned(a.saturating_sub(b))),
+            (Self::CentiWattUnsigned(a), Self::CentiWattUnsigned(b)) => {
+                Some(Self::CentiWattUnsigned(a.saturating_sub(b)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Converts a value to 10mWh at the given pack voltage (in mV).
+    ///
+    /// A value already in 10mWh is returned unchanged. Packs with a high voltage and capacity (e.g. 20Ah at 36V,
+    /// 72000mWh) don't fit in a u16 once converted, so `CapacityOverflow` is returned instead of truncating.
+    pub const fn try_to_centiwatt_hours(self, voltage_mv: u16) -> Result<CapacityModeValue, CapacityOverflow> {
+        match self {
+            Self::CentiWattUnsigned(_) => Ok(self),
+            Self::MilliAmpUnsigned(capacity) => {
+                let centiwatt_hours = capacity as u32 * voltage_mv as u32 / 10_000;
+                if centiwatt_hours > u16::MAX as u32 {
+                    Err(CapacityOverflow)
+                } else {
+                    Ok(Self::CentiWattUnsigned(centiwatt_hours as u16))
+                }
+            }
+        }
+    }
+}
+
+impl From<TenMilliWattHours> for CapacityModeValue {
+    fn from(value: TenMilliWattHours) -> Self {
+        Self::CentiWattUnsigned(value.0)
+    }
+}
+
+impl From<TenMilliWatts> for CapacityModeValue {
+    fn from(value: TenMilliWatts) -> Self {
+        Self::CentiWattUnsigned(value.0)
+    }
+}
+
+impl core::fmt::Display for CapacityModeValue {
+    /// Formats the value with its unit: `1500 mAh` or `300 (10mWh)`.
+    ///
+    /// The same units are used for rates and alarm thresholds stored in a `CapacityModeValue`, so a mA or 10mW
+    /// value is shown with the capacity unit as well.
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::MilliAmpUnsigned(value) => write!(f, "{value} mAh"),
+            Self::CentiWattUnsigned(value) => write!(f, "{value} (10mWh)"),
+        }
+    }
+}
+
+/// The converted capacity doesn't fit in a `CapacityModeValue`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CapacityOverflow;
+
 /// Time is measured in minutes, where 1 minute is 1
 pub type Minutes = u16;
 
+/// Time returned by the time functions (0x05, 0x06, 0x11 - 0x13), with the 65535 sentinel made explicit.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TimeEstimate {
+    /// The battery returned 65535: the estimate doesn't apply (e.g. time to empty while charging) or is
+    /// over-range.
+    NotApplicable,
+    /// Estimated time in minutes.
+    Minutes(Minutes),
+}
+
+impl From<Minutes> for TimeEstimate {
+    fn from(value: Minutes) -> Self {
+        match value {
+            Minutes::MAX => Self::NotApplicable,
+            minutes => Self::Minutes(minutes),
+        }
+    }
+}
+
 /// Depending on the value of the CapacityMode bit, the Smart Battery will use milliamps or centiwatts.
 /// Signed to represent negative currents and capacities.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
@@ -98,15 +274,119 @@ pub enum CapacityModeSignedValue {
     CentiWattSigned(i16),
 }
 
+impl core::fmt::Display for CapacityModeSignedValue {
+    /// Formats the value with its unit: `-1500 mA` or `-300 (10mW)`.
+    ///
+    /// Signed values are rates (e.g. AtRate()), so they are shown in mA or 10mW.
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::MilliAmpSigned(value) => write!(f, "{value} mA"),
+            Self::CentiWattSigned(value) => write!(f, "{value} (10mW)"),
+        }
+    }
+}
+
 /// Temperature is measured in decikelvins, where 0.1 Kelvin is 1.
 pub type DeciKelvin = u16;
 
+/// Temperature is measured in decidegrees Celsius, where 0.1 °C is 1.
+pub type DeciCelsius = i16;
+
+/// Offset between the Kelvin and Celsius scales, in decikelvins.
+const DECIKELVIN_AT_ZERO_CELSIUS: i32 = 2731;
+
+/// Converts a temperature in decikelvins to decidegrees Celsius.
+///
+/// The conversion uses a signed intermediate, so temperatures below freezing (below 2731 dK)
+/// correctly yield negative values rather than underflowing. `DeciKelvin` can represent 0 to 6553.5 K,
+/// i.e. -273.1 °C to 6280.4 °C; results above `DeciCelsius::MAX` (3276.7 °C) saturate.
+///
+/// ```
+/// use embedded_batteries::smart_battery::decikelvin_to_decicelsius;
+///
+/// assert_eq!(decikelvin_to_decicelsius(2600), -131);
+/// assert_eq!(decikelvin_to_decicelsius(2731), 0);
+/// assert_eq!(decikelvin_to_decicelsius(0), -2731);
+/// ```
+pub const fn decikelvin_to_decicelsius(temperature: DeciKelvin) -> DeciCelsius {
+    let celsius = temperature as i32 - DECIKELVIN_AT_ZERO_CELSIUS;
+    if celsius > DeciCelsius::MAX as i32 {
+        DeciCelsius::MAX
+    } else {
+        celsius as DeciCelsius
+    }
+}
+
 /// Percent, 1% is 1.
 pub type Percent = u8;
 
+/// Largest discharge current magnitude (mA) for which a battery is considered idle
+/// by `SmartBattery::self_discharge_estimate()`.
+pub const IDLE_CURRENT_THRESHOLD: MilliAmps = 10;
+
 /// Cycles, 1 cycle is 1.
 pub type Cycles = u16;
 
+/// Unit alias value that defmt logs with its unit, see `log_minutes()` and friends.
+#[cfg(feature = "defmt")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum UnitValue {
+    /// Logged as `45 min`.
+    Minutes(Minutes),
+    /// Logged as `120 cycles`.
+    Cycles(Cycles),
+    /// Logged as `87 %`.
+    Percent(Percent),
+    /// Logged as `298.2 K`.
+    DeciKelvin(DeciKelvin),
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for UnitValue {
+    fn format(&self, f: defmt::Formatter) {
+        match *self {
+            Self::Minutes(value) => defmt::write!(f, "{=u16} min", value),
+            Self::Cycles(value) => defmt::write!(f, "{=u16} cycles", value),
+            Self::Percent(value) => defmt::write!(f, "{=u8} %", value),
+            Self::DeciKelvin(value) => {
+                let (kelvin, tenths) = split_decikelvin(value);
+                defmt::write!(f, "{=u16}.{=u16} K", kelvin, tenths)
+            }
+        }
+    }
+}
+
+/// Splits a `DeciKelvin` into whole Kelvin and tenths, e.g. 2982 into (298, 2).
+#[cfg(feature = "defmt")]
+const fn split_decikelvin(value: DeciKelvin) -> (u16, u16) {
+    (value / 10, value % 10)
+}
+
+/// Wraps `Minutes` so that defmt logs it with its unit, e.g. `defmt::info!("{}", log_minutes(45))`.
+#[cfg(feature = "defmt")]
+pub const fn log_minutes(value: Minutes) -> UnitValue {
+    UnitValue::Minutes(value)
+}
+
+/// Wraps `Cycles` so that defmt logs it with its unit.
+#[cfg(feature = "defmt")]
+pub const fn log_cycles(value: Cycles) -> UnitValue {
+    UnitValue::Cycles(value)
+}
+
+/// Wraps `Percent` so that defmt logs it with its unit.
+#[cfg(feature = "defmt")]
+pub const fn log_percent(value: Percent) -> UnitValue {
+    UnitValue::Percent(value)
+}
+
+/// Wraps `DeciKelvin` so that defmt logs it in Kelvin with one decimal.
+#[cfg(feature = "defmt")]
+pub const fn log_decikelvin(value: DeciKelvin) -> UnitValue {
+    UnitValue::DeciKelvin(value)
+}
+
 /// Error codes that must be supported by the Smart Battery.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -173,11 +453,56 @@ impl From<u8> for ErrorCode {
     }
 }
 
+impl ErrorCode {
+    /// Classifies the error code by how a host should react to it, or `None` for `ErrorCode::Ok`.
+    ///
+    /// The derived `Ord` on `ErrorCode` follows the register encoding, not severity, so retry logic
+    /// should use this classification instead.
+    pub const fn severity(&self) -> Option<Severity> {
+        match self {
+            Self::Ok => None,
+            Self::Busy => Some(Severity::Transient),
+            Self::ReservedCmd | Self::UnsupportedCmd | Self::AccessDenied => Some(Severity::Permanent),
+            Self::UnderOverFlow | Self::BadSize | Self::UnknownError => Some(Severity::Fatal),
+        }
+    }
+
+    /// Extracts the error code from bits 0 - 3 of a raw battery_status() (0x16) word.
+    pub const fn from_status_word(raw: u16) -> Self {
+        Self::from_bits((raw & 0x000F) as u8)
+    }
+}
+
+impl From<BatteryStatusFields> for ErrorCode {
+    fn from(value: BatteryStatusFields) -> Self {
+        value.error_code()
+    }
+}
+
+/// Severity of an `ErrorCode`, ordered from least to most severe.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Severity {
+    /// The Smart Battery is temporarily unable to process the request. Retrying later may succeed.
+    Transient,
+
+    /// The request can never succeed as issued (e.g. unsupported or read only function code).
+    /// Retrying the same request is pointless.
+    Permanent,
+
+    /// The Smart Battery detected a data or internal error. The request failed and any returned
+    /// data should not be trusted.
+    Fatal,
+}
+
 /// Revision of SBS Spec, used in specification_info().
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum Revision {
+    /// Reserved. Any revision other than 1 reported by the battery is decoded as `Reserved`.
+    Reserved = 0,
+
     /// Version 1.0 and 1.1.
     Version1And1Dot1 = 1,
 }
@@ -190,7 +515,7 @@ impl Revision {
     const fn from_bits(value: u8) -> Self {
         match value {
             1 => Self::Version1And1Dot1,
-            _ => unreachable!(),
+            _ => Self::Reserved,
         }
     }
 }
@@ -207,6 +532,9 @@ impl TryFrom<u8> for Revision {
 }
 
 /// Version of SBS Spec, used in specification_info().
+///
+/// Versions are ordered by release: `Reserved < Version1 < Version1Dot1 < Version1Dot1Pec`, so they can be compared
+/// directly to check for a minimum version.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
@@ -354,6 +682,19 @@ pub struct BatteryModeFields {
     pub capacity_mode: bool,
 }
 
+impl BatteryModeFields {
+    /// Mask of the host writable bits: CHARGE_CONTROLLER_ENABLED, PRIMARY_BATTERY, ALARM_MODE, CHARGER_MODE and
+    /// CAPACITY_MODE. All other bits are read only (INTERNAL_CHARGE_CONTROLLER, PRIMARY_BATTERY_SUPPORT and
+    /// CONDITION_FLAG) or reserved.
+    pub const WRITABLE_MASK: u16 = 0xE300;
+
+    /// Returns a word that is safe to write with set_battery_mode(): the writable bits are taken from `self`,
+    /// the read only and reserved bits from `base` (typically the value last read from battery_mode()).
+    pub const fn with_writable_from(&self, base: BatteryModeFields) -> BatteryModeFields {
+        Self::from_bits((self.into_bits() & Self::WRITABLE_MASK) | (base.into_bits() & !Self::WRITABLE_MASK))
+    }
+}
+
 /// Return value of the battery_status() function (0x16). See the SBS spec for more information.
 #[bitfield(u16, defmt = cfg(feature = "defmt"))]
 #[derive(Eq, PartialEq, Ord, PartialOrd, Hash)]
@@ -456,6 +797,75 @@ pub struct BatteryStatusFields {
     pub over_charged_alarm: bool,
 }
 
+/// Alarm condition reported in battery_status() (0x16), ordered from least to most severe.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Alarm {
+    /// REMAINING_TIME_ALARM.
+    RemainingTime,
+    /// REMAINING_CAPACITY_ALARM.
+    RemainingCapacity,
+    /// FULLY_DISCHARGED (a status bit, but just as actionable as an alarm).
+    FullyDischarged,
+    /// TERMINATE_DISCHARGE_ALARM.
+    TerminateDischarge,
+    /// TERMINATE_CHARGE_ALARM.
+    TerminateCharge,
+    /// OVER_TEMP_ALARM.
+    OverTemp,
+    /// OVER_CHARGED_ALARM.
+    OverCharged,
+}
+
+impl BatteryStatusFields {
+    /// Returns the most severe active alarm, or `None` if no alarm is set.
+    ///
+    /// The priority order, from most to least severe, is OVER_CHARGED, OVER_TEMP, TERMINATE_CHARGE,
+    /// TERMINATE_DISCHARGE, FULLY_DISCHARGED, REMAINING_CAPACITY and REMAINING_TIME, matching the ordering of
+    /// `Alarm`. Conditions that can damage the battery come before those that only warn about running low.
+    pub const fn highest_priority_alarm(&self) -> Option<Alarm> {
+        if self.over_charged_alarm() {
+            Some(Alarm::OverCharged)
+        } else if self.over_temp_alarm() {
+            Some(Alarm::OverTemp)
+        } else if self.terminate_charge_alarm() {
+            Some(Alarm::TerminateCharge)
+        } else if self.terminate_discharge_alarm() {
+            Some(Alarm::TerminateDischarge)
+        } else if self.fully_discharged() {
+            Some(Alarm::FullyDischarged)
+        } else if self.remaining_capacity_alarm() {
+            Some(Alarm::RemainingCapacity)
+        } else if self.remaining_time_alarm() {
+            Some(Alarm::RemainingTime)
+        } else {
+            None
+        }
+    }
+
+    /// Returns `Err` with the error code if it isn't `ErrorCode::Ok`, or the status itself otherwise.
+    pub const fn result(self) -> Result<Self, ErrorCode> {
+        match self.error_code() {
+            ErrorCode::Ok => Ok(self),
+            code => Err(code),
+        }
+    }
+
+    /// Returns `true` if any alarm bit is set: OVER_CHARGED, TERMINATE_CHARGE, OVER_TEMP, TERMINATE_DISCHARGE,
+    /// REMAINING_CAPACITY or REMAINING_TIME.
+    ///
+    /// FULLY_DISCHARGED is a status bit rather than an alarm and isn't considered, unlike in
+    /// `highest_priority_alarm()`.
+    pub const fn any_alarm(&self) -> bool {
+        self.over_charged_alarm()
+            || self.terminate_charge_alarm()
+            || self.over_temp_alarm()
+            || self.terminate_discharge_alarm()
+            || self.remaining_capacity_alarm()
+            || self.remaining_time_alarm()
+    }
+}
+
 /// Return value of the specification_info() function (0x1a). See the SBS spec for more information.
 #[bitfield(u16, defmt = cfg(feature = "defmt"))]
 pub struct SpecificationInfoFields {
@@ -478,118 +888,1072 @@ pub struct SpecificationInfoFields {
     pub ip_scale: u8,
 }
 
-/// Blocking Smart Battery methods.
-pub trait SmartBattery: ErrorType {
-    /// 0x01
-    ///
-    /// Gets the Low Capacity alarm threshold value. Whenever the RemainingCapacity() falls below the
-    /// Low Capacity value, the Smart Battery sends AlarmWarning() messages to the SMBus Host with the
-    /// REMAINING_CAPACITY_ALARM bit set. A Low Capacity value of 0 disables this alarm.
-    /// (If the ALARM_MODE bit is set in BatteryMode() then the AlarmWarning() message is disabled for a set
-    /// period of time. See the BatteryMode() function for further information.)
-    ///
-    /// The Low Capacity value is set to 10% of design capacity at time of manufacture. The Low Capacity value
-    /// will remain unchanged until altered by the RemainingCapacityAlarm() function. The Low Capacity value
-    /// may be expressed in either capacity (mAh) or power (10mWh) depending on the setting of the
-    /// BatteryMode()'s CAPACITY_MODE bit (see BatteryMode()).
-    fn remaining_capacity_alarm(&mut self) -> Result<CapacityModeValue, Self::Error>;
+impl SpecificationInfoFields {
+    /// Largest scale exponent defined by the SBS spec for VScale and IPScale.
+    pub const MAX_SCALE: u8 = 3;
 
-    /// 0x01
-    ///
-    /// Sets the Low Capacity alarm threshold value. Whenever the RemainingCapacity() falls below the
-    /// Low Capacity value, the Smart Battery sends AlarmWarning() messages to the SMBus Host with the
-    /// REMAINING_CAPACITY_ALARM bit set. A Low Capacity value of 0 disables this alarm.
-    /// (If the ALARM_MODE bit is set in BatteryMode() then the AlarmWarning() message is disabled for a set
-    /// period of time. See the BatteryMode() function for further information.)
+    /// Packs a specification_info() word from its components.
     ///
-    /// The Low Capacity value is set to 10% of design capacity at time of manufacture. The Low Capacity value
-    /// will remain unchanged until altered by the RemainingCapacityAlarm() function. The Low Capacity value
-    /// may be expressed in either capacity (mAh) or power (10mWh) depending on the setting of the
-    /// BatteryMode()'s CAPACITY_MODE bit (see BatteryMode()).
-    fn set_remaining_capacity_alarm(&mut self, capacity: CapacityModeValue) -> Result<(), Self::Error>;
+    /// `v_scale` and `ip_scale` must be in the range 0 - 3. Use `from_parts_checked()` to
+    /// validate untrusted scales.
+    pub const fn from_parts(revision: Revision, version: Version, v_scale: u8, ip_scale: u8) -> Self {
+        Self::new()
+            .with_revision(revision)
+            .with_version(version)
+            .with_v_scale(v_scale)
+            .with_ip_scale(ip_scale)
+    }
 
-    /// 0x02
-    ///
-    /// Gets the Remaining Time alarm value. Whenever the AverageTimeToEmpty() falls below the
-    /// Remaining Time value, the Smart Battery sends AlarmWarning() messages to the SMBus Host with the
-    /// REMAINING_TIME_ALARM bit set. A Remaining Time value of 0 effectively disables this alarm.
-    /// (If the ALARM_MODE bit is set in BatteryMode() then the AlarmWarning() message is disabled for a set
-    /// period of time. See the BatteryMode() function for further information.)
-    ///
-    /// The Remaining Time value is set to 10 minutes at time of manufacture. The Remaining Time value will
-    /// remain unchanged until altered by the RemainingTimeAlarm() function.
-    fn remaining_time_alarm(&mut self) -> Result<Minutes, Self::Error>;
+    /// Packs a specification_info() word from its components, returning `None` if `v_scale` or
+    /// `ip_scale` is outside the range 0 - 3.
+    pub const fn from_parts_checked(revision: Revision, version: Version, v_scale: u8, ip_scale: u8) -> Option<Self> {
+        if v_scale > Self::MAX_SCALE || ip_scale > Self::MAX_SCALE {
+            None
+        } else {
+            Some(Self::from_parts(revision, version, v_scale, ip_scale))
+        }
+    }
 
-    /// 0x02
-    ///
-    /// Sets the Remaining Time alarm value. Whenever the AverageTimeToEmpty() falls below the
-    /// Remaining Time value, the Smart Battery sends AlarmWarning() messages to the SMBus Host with the
-    /// REMAINING_TIME_ALARM bit set. A Remaining Time value of 0 effectively disables this alarm.
-    /// (If the ALARM_MODE bit is set in BatteryMode() then the AlarmWarning() message is disabled for a set
-    /// period of time. See the BatteryMode() function for further information.)
+    /// Returns the multiplier for power and energy readings, `10^(v_scale + ip_scale)`.
     ///
-    /// The Remaining Time value is set to 10 minutes at time of manufacture. The Remaining Time value will
-    /// remain unchanged until altered by the RemainingTimeAlarm() function.
-    fn set_remaining_time_alarm(&mut self, time: Minutes) -> Result<(), Self::Error>;
+    /// Voltages are multiplied by `10^v_scale` and currents by `10^ip_scale`, and power is voltage times current,
+    /// so power scaling is the product of the two. Scales above 3 aren't defined by the spec and are
+    /// treated as 3, bounding the result to 1000000.
+    pub const fn power_scale(&self) -> u32 {
+        let v_scale = if self.v_scale() > Self::MAX_SCALE {
+            Self::MAX_SCALE
+        } else {
+            self.v_scale()
+        };
+        let ip_scale = if self.ip_scale() > Self::MAX_SCALE {
+            Self::MAX_SCALE
+        } else {
+            self.ip_scale()
+        };
+        10u32.pow((v_scale + ip_scale) as u32)
+    }
+}
 
-    /// 0x03
-    ///
-    /// This function reads the various battery operational modes and reports the battery’s capabilities, modes,
-    /// and flags minor conditions requiring attention.
-    ///
-    /// See the SBS specification for detailed documentation.
-    fn battery_mode(&mut self) -> Result<BatteryModeFields, Self::Error>;
+/// Maximum length of the strings returned by the string functions (0x20 - 0x22), excluding the null terminator.
+///
+/// SMBus block reads are limited to 32 bytes.
+pub const SBS_MAX_STRING_LEN: usize = 32;
 
-    /// 0x03
-    ///
-    /// This function sets the various battery operational modes and reports the battery’s capabilities, modes,
-    /// and flags minor conditions requiring attention. Note that not all fields are writeable.
-    ///
-    /// See the SBS specification for detailed documentation.
-    fn set_battery_mode(&mut self, flags: BatteryModeFields) -> Result<(), Self::Error>;
+/// Maximum length of the string returned by manufacturer_name() (0x20), excluding the null terminator.
+///
+/// SMBus block reads are limited to 32 bytes.
+pub const MAX_MANUFACTURER_NAME_LEN: usize = SBS_MAX_STRING_LEN;
 
-    /// 0x04
-    ///
-    /// The AtRate() function is the first half of a two-function call-set used to set the AtRate value used in
-    /// calculations made by the AtRateTimeToFull(), AtRateTimeToEmpty(), and AtRateOK() functions.
-    ///
-    /// The AtRate value may be expressed in either current (mA) or power (10mW) depending on the setting of the
-    /// BatteryMode()'s CAPACITY_MODE bit. (Configuration of the CAPACITY_MODE bit will alter the
-    /// calculation of AtRate functions. Changing the state of CAPACITY_MODE may require a re-write to the
-    /// AtRate() function using the appropriate units.)
-    fn at_rate(&mut self) -> Result<CapacityModeSignedValue, Self::Error>;
+/// Maximum length of the string returned by device_name() (0x21), excluding the null terminator.
+///
+/// SMBus block reads are limited to 32 bytes.
+pub const MAX_DEVICE_NAME_LEN: usize = SBS_MAX_STRING_LEN;
 
-    /// 0x04
-    ///
-    /// The AtRate() function is the first half of a two-function call-set used to set the AtRate value used in
-    /// calculations made by the AtRateTimeToFull(), AtRateTimeToEmpty(), and AtRateOK() functions.
-    ///
-    /// The AtRate value may be expressed in either current (mA) or power (10mW) depending on the setting of the
-    /// BatteryMode()'s CAPACITY_MODE bit. (Configuration of the CAPACITY_MODE bit will alter the
-    /// calculation of AtRate functions. Changing the state of CAPACITY_MODE may require a re-write to the
-    /// AtRate() function using the appropriate units.)
-    fn set_at_rate(&mut self, rate: CapacityModeSignedValue) -> Result<(), Self::Error>;
+/// Maximum length of the string returned by device_chemistry() (0x22), excluding the null terminator.
+///
+/// SMBus block reads are limited to 32 bytes.
+pub const MAX_DEVICE_CHEMISTRY_LEN: usize = SBS_MAX_STRING_LEN;
 
-    /// 0x05
-    ///
-    /// Returns the predicted remaining time to fully charge the battery at the previously written AtRate value in mA.
-    ///
-    /// Note: This function is only required to return a value when the CAPACITY_MODE bit is cleared and the
-    /// AtRate() value is written in mA units. If the CAPACITY_MODE bit is set, then AtRateTimeToFull() may
-    /// return 65535 to indicate over-range and return an error code indicating overflow. Alternately, this function
-    /// may return a remaining time to full based on a 10 mW value in AtRate().
-    fn at_rate_time_to_full(&mut self) -> Result<Minutes, Self::Error>;
+/// Largest number of bytes written by `SmartBattery::dump_standard_registers()`: 28 words plus three
+/// length-prefixed 32 byte strings.
+pub const STANDARD_REGISTER_DUMP_MAX_LEN: usize = 28 * 2 + 3 * (1 + 32);
 
-    /// 0x06
-    ///
-    /// Returns the predicted remaining operating time if the battery is discharged at the previously written AtRate
-    /// value. (Result will depend on the setting of CAPACITY_MODE bit.)
-    fn at_rate_time_to_empty(&mut self) -> Result<Minutes, Self::Error>;
+/// Returns a zeroed buffer large enough for any of the string functions (0x20 - 0x22): 32 bytes plus the null
+/// terminator.
+pub const fn name_buffer() -> [u8; 33] {
+    [0; 33]
+}
 
-    /// 0x07
-    ///
-    /// Returns a Boolean value that indicates whether or not the battery can deliver the previously written AtRate
-    /// value of additional energy for 10 seconds (Boolean). If the AtRate value is zero or positive, the
+/// Checks that a buffer passed to the string functions (0x20 - 0x22) can hold a string of `needed` bytes
+/// (excluding the null terminator) plus its null terminator.
+///
+/// Intended for implementers of `manufacturer_name()`, `device_name()` and `device_chemistry()`. Returns
+/// `ErrorKind::BatteryStatus(ErrorCode::BadSize)` if the buffer is too short.
+pub fn name_fits(buf: &[u8], needed: usize) -> Result<(), ErrorKind> {
+    if buf.len() > needed {
+        Ok(())
+    } else {
+        Err(ErrorKind::BatteryStatus(ErrorCode::BadSize))
+    }
+}
+
+/// Returns the string filled in by one of the string functions (0x20 - 0x22), up to the first null terminator
+/// or the end of `buf`.
+///
+/// SBS strings are ASCII. If `buf` holds invalid UTF-8, the string is cut at the first invalid byte.
+pub fn sbs_str(buf: &[u8]) -> &str {
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    match core::str::from_utf8(&buf[..len]) {
+        Ok(s) => s,
+        Err(e) => core::str::from_utf8(&buf[..e.valid_up_to()]).unwrap_or_default(),
+    }
+}
+
+/// Cell chemistry, as identified by the device_chemistry() (0x22) string.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Chemistry {
+    /// Lead acid ("PbAc").
+    LeadAcid,
+    /// Lithium ion ("LION").
+    LithiumIon,
+    /// Lithium polymer ("LiP").
+    LithiumPolymer,
+    /// Nickel cadmium ("NiCd").
+    NickelCadmium,
+    /// Nickel metal hydride ("NiMH").
+    NickelMetalHydride,
+    /// Nickel zinc ("NiZn").
+    NickelZinc,
+    /// Rechargeable alkaline-manganese ("RAM").
+    RechargeableAlkalineManganese,
+    /// Zinc air ("ZnAr").
+    ZincAir,
+}
+
+impl Chemistry {
+    /// Identifies the chemistry from a device_chemistry() string, ignoring case and anything after a null
+    /// terminator.
+    ///
+    /// Returns `None` for strings not defined by the SBS spec (plus the common "LiP" for lithium polymer).
+    pub fn from_device_chemistry(name: &[u8]) -> Option<Self> {
+        let len = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+        let name = &name[..len];
+        [
+            (&b"PbAc"[..], Self::LeadAcid),
+            (b"LION", Self::LithiumIon),
+            (b"LiP", Self::LithiumPolymer),
+            (b"NiCd", Self::NickelCadmium),
+            (b"NiMH", Self::NickelMetalHydride),
+            (b"NiZn", Self::NickelZinc),
+            (b"RAM", Self::RechargeableAlkalineManganese),
+            (b"ZnAr", Self::ZincAir),
+        ]
+        .into_iter()
+        .find(|(code, _)| code.eq_ignore_ascii_case(name))
+        .map(|(_, chemistry)| chemistry)
+    }
+
+    /// Returns the nominal voltage of a single cell of this chemistry (mV).
+    pub const fn nominal_cell_voltage(self) -> MilliVolts {
+        match self {
+            Self::LeadAcid => 2000,
+            Self::LithiumIon => 3600,
+            Self::LithiumPolymer => 3700,
+            Self::NickelCadmium | Self::NickelMetalHydride => 1200,
+            Self::NickelZinc => 1600,
+            Self::RechargeableAlkalineManganese => 1500,
+            Self::ZincAir => 1400,
+        }
+    }
+
+    /// Estimates the number of series cells from the pack's design voltage (mV).
+    ///
+    /// The count is rounded to the nearest whole number of cells. Returns `None` if it rounds to 0 or if the
+    /// design voltage is more than 10% away from a whole number of cells, as the chemistry or design voltage is
+    /// then likely misreported.
+    pub const fn plausible_cell_count(self, design_voltage: MilliVolts) -> Option<u8> {
+        let nominal = self.nominal_cell_voltage() as u32;
+        let design_voltage = design_voltage as u32;
+        let count = (design_voltage + nominal / 2) / nominal;
+        if count == 0 || count > u8::MAX as u32 {
+            return None;
+        }
+        let expected = count * nominal;
+        if expected.abs_diff(design_voltage) * 10 > expected {
+            return None;
+        }
+        Some(count as u8)
+    }
+}
+
+/// Framing of block reads (e.g. the string functions 0x20 - 0x22) returned by the underlying bus.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BlockQuirk {
+    /// SMBus block read: the first byte is the length of the data that follows.
+    #[default]
+    LengthPrefixed,
+    /// I2C-style read: the whole buffer is data, with no length prefix.
+    Raw,
+}
+
+impl BlockQuirk {
+    /// Returns the data portion of a block read.
+    ///
+    /// For `BlockQuirk::LengthPrefixed`, the length byte is stripped and an error of kind
+    /// `ErrorKind::BatteryStatus(ErrorCode::BadSize)` is returned if `block` is empty, or if the length byte
+    /// claims more data than `block` holds or than the 32 bytes an SMBus block can carry. A length of 0 decodes to
+    /// an empty slice.
+    pub fn decode_block(self, block: &[u8]) -> Result<&[u8], ErrorKind> {
+        const SMBUS_BLOCK_MAX_LEN: usize = 32;
+
+        match self {
+            Self::Raw => Ok(block),
+            Self::LengthPrefixed => {
+                let (&len, data) = block
+                    .split_first()
+                    .ok_or(ErrorKind::BatteryStatus(ErrorCode::BadSize))?;
+                let len = usize::from(len);
+                if len > SMBUS_BLOCK_MAX_LEN {
+                    return Err(ErrorKind::BatteryStatus(ErrorCode::BadSize));
+                }
+                data.get(..len).ok_or(ErrorKind::BatteryStatus(ErrorCode::BadSize))
+            }
+        }
+    }
+}
+
+/// Computes the SMBus Packet Error Code (PEC) of `data`: a CRC-8 with polynomial x^8 + x^2 + x + 1 (0x07) and
+/// an initial value of 0.
+pub const fn crc8_smbus(data: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    let mut i = 0;
+    while i < data.len() {
+        crc ^= data[i];
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x80 != 0 { (crc << 1) ^ 0x07 } else { crc << 1 };
+            bit += 1;
+        }
+        i += 1;
+    }
+    crc
+}
+
+/// Lifetime extremes recorded by the gauge, as exposed by e.g. bq-series gauges in a manufacturer data block.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LifetimeData {
+    /// Highest voltage seen over the battery's lifetime.
+    pub max_voltage: MilliVolts,
+    /// Lowest voltage seen over the battery's lifetime.
+    pub min_voltage: MilliVolts,
+    /// Highest charge current seen over the battery's lifetime.
+    pub max_charge_current: MilliAmpsSigned,
+    /// Highest discharge current seen over the battery's lifetime, as reported by the gauge (usually negative).
+    pub max_discharge_current: MilliAmpsSigned,
+    /// Highest temperature seen over the battery's lifetime.
+    pub max_temperature: DeciCelsius,
+    /// Lowest temperature seen over the battery's lifetime.
+    pub min_temperature: DeciCelsius,
+}
+
+/// Byte offsets of each `LifetimeData` field within a manufacturer data block.
+///
+/// The layout of lifetime data blocks is vendor and firmware specific, so it is supplied by the implementer.
+/// Each field is a little-endian 16-bit word, with temperatures in decidegrees Celsius.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LifetimeDataLayout {
+    /// Offset of the maximum voltage.
+    pub max_voltage: usize,
+    /// Offset of the minimum voltage.
+    pub min_voltage: usize,
+    /// Offset of the maximum charge current.
+    pub max_charge_current: usize,
+    /// Offset of the maximum discharge current.
+    pub max_discharge_current: usize,
+    /// Offset of the maximum temperature.
+    pub max_temperature: usize,
+    /// Offset of the minimum temperature.
+    pub min_temperature: usize,
+}
+
+impl LifetimeDataLayout {
+    /// Parses a manufacturer data block, returning `None` if any field lies outside of `block`.
+    pub fn parse(&self, block: &[u8]) -> Option<LifetimeData> {
+        let word = |offset: usize| -> Option<[u8; 2]> { block.get(offset..offset.checked_add(2)?)?.try_into().ok() };
+
+        Some(LifetimeData {
+            max_voltage: u16::from_le_bytes(word(self.max_voltage)?),
+            min_voltage: u16::from_le_bytes(word(self.min_voltage)?),
+            max_charge_current: i16::from_le_bytes(word(self.max_charge_current)?),
+            max_discharge_current: i16::from_le_bytes(word(self.max_discharge_current)?),
+            max_temperature: i16::from_le_bytes(word(self.max_temperature)?),
+            min_temperature: i16::from_le_bytes(word(self.min_temperature)?),
+        })
+    }
+}
+
+/// Hysteresis filter for successive relative_state_of_charge() readings.
+///
+/// A new value is only reported once readings have differed from the reported value by at least `hysteresis`
+/// percent, on the same side of it, for `samples` consecutive updates, suppressing jitter that would otherwise
+/// cause a flickering UI or premature alarms.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SocFilter {
+    hysteresis: Percent,
+    samples: u8,
+    reported: Option<Percent>,
+    pending: u8,
+    pending_rising: bool,
+}
+
+impl SocFilter {
+    /// Creates a filter reporting a change once readings differ by at least `hysteresis` percent for
+    /// `samples` consecutive updates. A `samples` value of 0 behaves like 1.
+    pub const fn new(hysteresis: Percent, samples: u8) -> Self {
+        Self {
+            hysteresis,
+            samples,
+            reported: None,
+            pending: 0,
+            pending_rising: false,
+        }
+    }
+
+    /// Feeds a new reading into the filter and returns the filtered value.
+    ///
+    /// The first reading after creation or `reset()` is reported as is. A reading on the other side of the
+    /// reported value than the pending ones restarts the count, so jitter around it is never committed.
+    pub fn update(&mut self, soc: Percent) -> Percent {
+        let Some(reported) = self.reported else {
+            self.reported = Some(soc);
+            return soc;
+        };
+
+        if soc.abs_diff(reported) < self.hysteresis {
+            self.pending = 0;
+            return reported;
+        }
+
+        let rising = soc > reported;
+        if self.pending == 0 || rising != self.pending_rising {
+            self.pending = 1;
+            self.pending_rising = rising;
+        } else {
+            self.pending = self.pending.saturating_add(1);
+        }
+        if self.pending >= self.samples {
+            self.pending = 0;
+            self.reported = Some(soc);
+            soc
+        } else {
+            reported
+        }
+    }
+
+    /// Returns the currently reported value, or `None` if no reading has been fed since creation or `reset()`.
+    pub const fn value(&self) -> Option<Percent> {
+        self.reported
+    }
+
+    /// Discards the reported value and any pending change.
+    pub fn reset(&mut self) {
+        self.reported = None;
+        self.pending = 0;
+    }
+}
+
+/// Host-side coulomb counter integrating current samples over time.
+///
+/// Charge is accumulated exactly as mA × ms (i.e. microcoulombs) in an `i64`, which cannot overflow in
+/// practice, and converted to µAh (1 µAh = 3600 mA·ms) or mAh (1 mAh = 3 600 000 mA·ms) on read.
+/// Positive values indicate net charge into the battery, matching the sign of current().
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CoulombCounter {
+    milliamp_millis: i64,
+}
+
+impl CoulombCounter {
+    /// Creates a counter with no accumulated charge.
+    pub const fn new() -> Self {
+        Self { milliamp_millis: 0 }
+    }
+
+    /// Integrates `current_ma` flowing for `dt_ms` milliseconds.
+    pub fn accumulate(&mut self, current_ma: MilliAmpsSigned, dt_ms: u32) {
+        self.milliamp_millis = self
+            .milliamp_millis
+            .saturating_add(i64::from(current_ma) * i64::from(dt_ms));
+    }
+
+    /// Returns the net accumulated charge in µAh, rounded toward zero.
+    pub const fn net_uah(&self) -> i64 {
+        self.milliamp_millis / 3_600
+    }
+
+    /// Returns the net accumulated charge in mAh, rounded toward zero and saturated to the `i32` range.
+    pub const fn net_mah(&self) -> i32 {
+        let mah = self.milliamp_millis / 3_600_000;
+        if mah > i32::MAX as i64 {
+            i32::MAX
+        } else if mah < i32::MIN as i64 {
+            i32::MIN
+        } else {
+            mah as i32
+        }
+    }
+
+    /// Clears the accumulated charge.
+    pub fn reset(&mut self) {
+        self.milliamp_millis = 0;
+    }
+}
+
+/// Fixed size ring buffer of the last `N` battery_status() (0x16) words, for post-mortem diagnostics.
+///
+/// Timestamps are opaque to the log; the caller picks the clock and units (e.g. ms since boot).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct StatusLog<const N: usize> {
+    entries: [(u64, BatteryStatusFields); N],
+    next: usize,
+    len: usize,
+}
+
+impl<const N: usize> StatusLog<N> {
+    /// Creates an empty log.
+    pub const fn new() -> Self {
+        Self {
+            entries: [(0, BatteryStatusFields::new()); N],
+            next: 0,
+            len: 0,
+        }
+    }
+
+    /// Records `status` read at time `now`, overwriting the oldest entry once the log is full.
+    pub fn record(&mut self, now: u64, status: BatteryStatusFields) {
+        if N == 0 {
+            return;
+        }
+        self.entries[self.next] = (now, status);
+        self.next = (self.next + 1) % N;
+        self.len = (self.len + 1).min(N);
+    }
+
+    /// Returns the number of recorded entries, at most `N`.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no entries have been recorded.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns an iterator over the recorded `(timestamp, status)` entries, newest first.
+    pub fn iter(&self) -> impl Iterator<Item = &(u64, BatteryStatusFields)> + '_ {
+        (1..=self.len).map(move |age| &self.entries[(self.next + N - age) % N])
+    }
+
+    /// Discards all recorded entries.
+    pub fn clear(&mut self) {
+        self.next = 0;
+        self.len = 0;
+    }
+}
+
+impl<const N: usize> Default for StatusLog<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Smart Battery Data command codes, one per `SmartBattery` function.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum Command {
+    /// RemainingCapacityAlarm() (0x01).
+    RemainingCapacityAlarm = 0x01,
+    /// RemainingTimeAlarm() (0x02).
+    RemainingTimeAlarm = 0x02,
+    /// BatteryMode() (0x03).
+    BatteryMode = 0x03,
+    /// AtRate() (0x04).
+    AtRate = 0x04,
+    /// AtRateTimeToFull() (0x05).
+    AtRateTimeToFull = 0x05,
+    /// AtRateTimeToEmpty() (0x06).
+    AtRateTimeToEmpty = 0x06,
+    /// AtRateOK() (0x07).
+    AtRateOk = 0x07,
+    /// Temperature() (0x08).
+    Temperature = 0x08,
+    /// Voltage() (0x09).
+    Voltage = 0x09,
+    /// Current() (0x0A).
+    Current = 0x0A,
+    /// AverageCurrent() (0x0B).
+    AverageCurrent = 0x0B,
+    /// MaxError() (0x0C).
+    MaxError = 0x0C,
+    /// RelativeStateOfCharge() (0x0D).
+    RelativeStateOfCharge = 0x0D,
+    /// AbsoluteStateOfCharge() (0x0E).
+    AbsoluteStateOfCharge = 0x0E,
+    /// RemainingCapacity() (0x0F).
+    RemainingCapacity = 0x0F,
+    /// FullChargeCapacity() (0x10).
+    FullChargeCapacity = 0x10,
+    /// RunTimeToEmpty() (0x11).
+    RunTimeToEmpty = 0x11,
+    /// AverageTimeToEmpty() (0x12).
+    AverageTimeToEmpty = 0x12,
+    /// AverageTimeToFull() (0x13).
+    AverageTimeToFull = 0x13,
+    /// ChargingCurrent() (0x14).
+    ChargingCurrent = 0x14,
+    /// ChargingVoltage() (0x15).
+    ChargingVoltage = 0x15,
+    /// BatteryStatus() (0x16).
+    BatteryStatus = 0x16,
+    /// CycleCount() (0x17).
+    CycleCount = 0x17,
+    /// DesignCapacity() (0x18).
+    DesignCapacity = 0x18,
+    /// DesignVoltage() (0x19).
+    DesignVoltage = 0x19,
+    /// SpecificationInfo() (0x1A).
+    SpecificationInfo = 0x1A,
+    /// ManufactureDate() (0x1B).
+    ManufactureDate = 0x1B,
+    /// SerialNumber() (0x1C).
+    SerialNumber = 0x1C,
+    /// ManufacturerName() (0x20).
+    ManufacturerName = 0x20,
+    /// DeviceName() (0x21).
+    DeviceName = 0x21,
+    /// DeviceChemistry() (0x22).
+    DeviceChemistry = 0x22,
+}
+
+impl From<Command> for u8 {
+    fn from(value: Command) -> Self {
+        value as u8
+    }
+}
+
+impl TryFrom<u8> for Command {
+    type Error = ();
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0x01 => Self::RemainingCapacityAlarm,
+            0x02 => Self::RemainingTimeAlarm,
+            0x03 => Self::BatteryMode,
+            0x04 => Self::AtRate,
+            0x05 => Self::AtRateTimeToFull,
+            0x06 => Self::AtRateTimeToEmpty,
+            0x07 => Self::AtRateOk,
+            0x08 => Self::Temperature,
+            0x09 => Self::Voltage,
+            0x0A => Self::Current,
+            0x0B => Self::AverageCurrent,
+            0x0C => Self::MaxError,
+            0x0D => Self::RelativeStateOfCharge,
+            0x0E => Self::AbsoluteStateOfCharge,
+            0x0F => Self::RemainingCapacity,
+            0x10 => Self::FullChargeCapacity,
+            0x11 => Self::RunTimeToEmpty,
+            0x12 => Self::AverageTimeToEmpty,
+            0x13 => Self::AverageTimeToFull,
+            0x14 => Self::ChargingCurrent,
+            0x15 => Self::ChargingVoltage,
+            0x16 => Self::BatteryStatus,
+            0x17 => Self::CycleCount,
+            0x18 => Self::DesignCapacity,
+            0x19 => Self::DesignVoltage,
+            0x1A => Self::SpecificationInfo,
+            0x1B => Self::ManufactureDate,
+            0x1C => Self::SerialNumber,
+            0x20 => Self::ManufacturerName,
+            0x21 => Self::DeviceName,
+            0x22 => Self::DeviceChemistry,
+            _ => return Err(()),
+        })
+    }
+}
+
+/// Schedule of up to `N` periodically polled commands, each with its own interval.
+///
+/// The schedule doesn't perform any I/O and is independent of the timer: the caller passes the current time (in
+/// ms, from any monotonic clock) to `due()` and issues the reads for the returned commands itself.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PollSchedule<const N: usize> {
+    entries: [(Command, u32, u64); N],
+    len: usize,
+}
+
+impl<const N: usize> PollSchedule<N> {
+    /// Creates an empty schedule.
+    pub const fn new() -> Self {
+        Self {
+            entries: [(Command::BatteryStatus, 0, 0); N],
+            len: 0,
+        }
+    }
+
+    /// Registers `command` to be polled every `interval_ms` milliseconds, starting with the next call to
+    /// `due()`.
+    ///
+    /// Returns the command back as an error if the schedule already holds `N` entries.
+    pub fn register(&mut self, command: Command, interval_ms: u32) -> Result<(), Command> {
+        let entry = self.entries.get_mut(self.len).ok_or(command)?;
+        *entry = (command, interval_ms, 0);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Returns an iterator over the commands due at time `now`, in registration order.
+    ///
+    /// Each command is rescheduled `interval_ms` after `now` as it is yielded, so commands that aren't consumed
+    /// from the iterator stay due.
+    pub fn due(&mut self, now: u64) -> impl Iterator<Item = Command> + '_ {
+        self.entries[..self.len]
+            .iter_mut()
+            .filter(move |(_, _, next_due)| now >= *next_due)
+            .map(move |(command, interval_ms, next_due)| {
+                *next_due = now.saturating_add(u64::from(*interval_ms));
+                *command
+            })
+    }
+
+    /// Returns the number of registered commands, at most `N`.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no commands are registered.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes all registered commands.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl<const N: usize> Default for PollSchedule<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Change of charge direction reported by `ChargeStateTracker`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ChargeTransition {
+    /// Current started flowing into the battery.
+    StartedCharging,
+    /// Current started flowing out of the battery.
+    StartedDischarging,
+    /// Current settled within the dead-band around zero.
+    WentIdle,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+enum ChargeDirection {
+    Charging,
+    Discharging,
+    Idle,
+}
+
+/// Edge detector for the charge direction reported by successive current() readings.
+///
+/// Currents within `dead_band` mA of zero are treated as idle, so tiny fluctuations around zero don't cause
+/// chatter.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ChargeStateTracker {
+    dead_band: MilliAmps,
+    previous: Option<ChargeDirection>,
+}
+
+impl ChargeStateTracker {
+    /// Creates a tracker treating currents with a magnitude of at most `dead_band` mA as idle.
+    pub const fn new(dead_band: MilliAmps) -> Self {
+        Self {
+            dead_band,
+            previous: None,
+        }
+    }
+
+    /// Feeds a new current() reading, returning a transition if the charge direction changed.
+    ///
+    /// The first reading only establishes the initial direction and never reports a transition.
+    pub fn update(&mut self, current: MilliAmpsSigned) -> Option<ChargeTransition> {
+        let direction = if current.unsigned_abs() <= self.dead_band {
+            ChargeDirection::Idle
+        } else if current > 0 {
+            ChargeDirection::Charging
+        } else {
+            ChargeDirection::Discharging
+        };
+
+        let previous = self.previous.replace(direction)?;
+        if previous == direction {
+            return None;
+        }
+        Some(match direction {
+            ChargeDirection::Charging => ChargeTransition::StartedCharging,
+            ChargeDirection::Discharging => ChargeTransition::StartedDischarging,
+            ChargeDirection::Idle => ChargeTransition::WentIdle,
+        })
+    }
+}
+
+/// Last AtRate() (0x04) value written to a Smart Battery, for implementers to embed in their driver.
+///
+/// AtRateOK() always returns true for a zero or positive AtRate, so it only carries information after a
+/// negative (discharge) rate was written. Call `record()` from `set_at_rate()` and consult
+/// `at_rate_ok_meaningful()` before acting on `at_rate_ok()`:
+///
+/// ```
+/// use embedded_batteries::smart_battery::{AtRateState, CapacityModeSignedValue};
+///
+/// let mut state = AtRateState::new();
+/// state.record(CapacityModeSignedValue::MilliAmpSigned(500));
+/// assert!(!state.at_rate_ok_meaningful());
+/// state.record(CapacityModeSignedValue::MilliAmpSigned(-1500));
+/// assert!(state.at_rate_ok_meaningful());
+/// ```
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AtRateState {
+    rate: Option<CapacityModeSignedValue>,
+}
+
+impl AtRateState {
+    /// Creates a state with no recorded write. The battery's AtRate() is 0 after power-on.
+    pub const fn new() -> Self {
+        Self { rate: None }
+    }
+
+    /// Records a rate written to AtRate().
+    pub fn record(&mut self, rate: CapacityModeSignedValue) {
+        self.rate = Some(rate);
+    }
+
+    /// Returns the last recorded rate, or `None` if none was recorded.
+    pub const fn rate(&self) -> Option<CapacityModeSignedValue> {
+        self.rate
+    }
+
+    /// Returns `true` if the last recorded rate was negative, so AtRateOK() reflects whether the battery can
+    /// deliver it for 10 seconds rather than unconditionally returning true.
+    pub const fn at_rate_ok_meaningful(&self) -> bool {
+        matches!(
+            self.rate,
+            Some(CapacityModeSignedValue::MilliAmpSigned(rate) | CapacityModeSignedValue::CentiWattSigned(rate))
+                if rate < 0
+        )
+    }
+}
+
+/// Predictions made by a Smart Battery for a single AtRate() (0x04) value.
+///
+/// AtRateTimeToFull() and AtRateTimeToEmpty() return 65535 when the prediction is over-range or not applicable
+/// (e.g. a time to full for a discharge rate), which is reported as `TimeEstimate::NotApplicable`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AtRateAnalysis {
+    /// AtRate() value the predictions were made for.
+    pub rate: CapacityModeSignedValue,
+    /// AtRateTimeToFull() (0x05).
+    pub time_to_full: TimeEstimate,
+    /// AtRateTimeToEmpty() (0x06).
+    pub time_to_empty: TimeEstimate,
+    /// AtRateOK() (0x07).
+    pub ok: bool,
+}
+
+impl AtRateAnalysis {
+    /// Builds an analysis from the raw reads, mapping the 65535 sentinel to `TimeEstimate::NotApplicable`.
+    pub fn new(rate: CapacityModeSignedValue, time_to_full: Minutes, time_to_empty: Minutes, ok: bool) -> Self {
+        Self {
+            rate,
+            time_to_full: time_to_full.into(),
+            time_to_empty: time_to_empty.into(),
+            ok,
+        }
+    }
+}
+
+/// Commonly polled dynamic values of a Smart Battery, with each read recorded independently.
+///
+/// Returned by `SmartBattery::snapshot_partial()`, so a host gets every value that could be read even if
+/// some reads failed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PartialSnapshot {
+    /// Result of voltage() (0x09).
+    pub voltage: Result<MilliVolts, ErrorKind>,
+    /// Result of current() (0x0A).
+    pub current: Result<MilliAmpsSigned, ErrorKind>,
+    /// Result of average_current() (0x0B).
+    pub average_current: Result<MilliAmpsSigned, ErrorKind>,
+    /// Result of temperature() (0x08).
+    pub temperature: Result<DeciKelvin, ErrorKind>,
+    /// Result of relative_state_of_charge() (0x0D).
+    pub relative_state_of_charge: Result<Percent, ErrorKind>,
+    /// Result of absolute_state_of_charge() (0x0E).
+    pub absolute_state_of_charge: Result<Percent, ErrorKind>,
+    /// Result of remaining_capacity() (0x0F).
+    pub remaining_capacity: Result<CapacityModeValue, ErrorKind>,
+    /// Result of full_charge_capacity() (0x10).
+    pub full_charge_capacity: Result<CapacityModeValue, ErrorKind>,
+    /// Result of battery_status() (0x16).
+    pub battery_status: Result<BatteryStatusFields, ErrorKind>,
+    /// Result of cycle_count() (0x17).
+    pub cycle_count: Result<Cycles, ErrorKind>,
+}
+
+impl core::fmt::Display for PartialSnapshot {
+    /// Formats the snapshot as a human readable report, one `label: value` line per field. Failed reads are
+    /// shown as `unavailable (<error kind>)`, e.g. `unavailable (CommError)`.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        fn line<T: core::fmt::Display>(
+            f: &mut core::fmt::Formatter<'_>,
+            label: &str,
+            value: &Result<T, ErrorKind>,
+            unit: &str,
+        ) -> core::fmt::Result {
+            match value {
+                Ok(value) => writeln!(f, "{label:<20}{value}{unit}"),
+                Err(kind) => writeln!(f, "{label:<20}unavailable ({kind:?})"),
+            }
+        }
+
+        line(f, "voltage:", &self.voltage, " mV")?;
+        line(f, "current:", &self.current, " mA")?;
+        line(f, "average current:", &self.average_current, " mA")?;
+        line(f, "temperature:", &self.temperature, " dK")?;
+        line(f, "relative SOC:", &self.relative_state_of_charge, " %")?;
+        line(f, "absolute SOC:", &self.absolute_state_of_charge, " %")?;
+        line(f, "remaining capacity:", &self.remaining_capacity, "")?;
+        line(f, "full capacity:", &self.full_charge_capacity, "")?;
+        match self.battery_status {
+            Ok(status) => writeln!(f, "{:<20}{:#06x}", "status:", status.into_bits())?,
+            Err(kind) => writeln!(f, "{:<20}unavailable ({kind:?})", "status:")?,
+        }
+        line(f, "cycle count:", &self.cycle_count, "")
+    }
+}
+
+/// Commonly polled dynamic values of a Smart Battery, read together by `SmartBattery::snapshot()`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BatterySnapshot {
+    /// voltage() (0x09).
+    pub voltage: MilliVolts,
+    /// current() (0x0A).
+    pub current: MilliAmpsSigned,
+    /// average_current() (0x0B).
+    pub average_current: MilliAmpsSigned,
+    /// temperature() (0x08).
+    pub temperature: DeciKelvin,
+    /// relative_state_of_charge() (0x0D).
+    pub relative_state_of_charge: Percent,
+    /// absolute_state_of_charge() (0x0E).
+    pub absolute_state_of_charge: Percent,
+    /// remaining_capacity() (0x0F).
+    pub remaining_capacity: CapacityModeValue,
+    /// full_charge_capacity() (0x10).
+    pub full_charge_capacity: CapacityModeValue,
+    /// battery_status() (0x16).
+    pub battery_status: BatteryStatusFields,
+    /// cycle_count() (0x17).
+    pub cycle_count: Cycles,
+}
+
+/// Fields that differ between two `BatterySnapshot`s, as returned by `BatterySnapshot::diff()`.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SnapshotDiff(u16);
+bitflags! {
+    impl SnapshotDiff: u16 {
+        /// `voltage` changed.
+        const VOLTAGE = 1 << 0;
+        /// `current` changed.
+        const CURRENT = 1 << 1;
+        /// `average_current` changed.
+        const AVERAGE_CURRENT = 1 << 2;
+        /// `temperature` changed.
+        const TEMPERATURE = 1 << 3;
+        /// `relative_state_of_charge` changed.
+        const RELATIVE_STATE_OF_CHARGE = 1 << 4;
+        /// `absolute_state_of_charge` changed.
+        const ABSOLUTE_STATE_OF_CHARGE = 1 << 5;
+        /// `remaining_capacity` changed.
+        const REMAINING_CAPACITY = 1 << 6;
+        /// `full_charge_capacity` changed.
+        const FULL_CHARGE_CAPACITY = 1 << 7;
+        /// `battery_status` changed.
+        const BATTERY_STATUS = 1 << 8;
+        /// `cycle_count` changed.
+        const CYCLE_COUNT = 1 << 9;
+    }
+}
+
+impl BatterySnapshot {
+    /// Returns the fields that differ between `self` and `other`, e.g. for change-only logging.
+    pub fn diff(&self, other: &BatterySnapshot) -> SnapshotDiff {
+        let mut diff = SnapshotDiff::empty();
+        diff.set(SnapshotDiff::VOLTAGE, self.voltage != other.voltage);
+        diff.set(SnapshotDiff::CURRENT, self.current != other.current);
+        diff.set(
+            SnapshotDiff::AVERAGE_CURRENT,
+            self.average_current != other.average_current,
+        );
+        diff.set(SnapshotDiff::TEMPERATURE, self.temperature != other.temperature);
+        diff.set(
+            SnapshotDiff::RELATIVE_STATE_OF_CHARGE,
+            self.relative_state_of_charge != other.relative_state_of_charge,
+        );
+        diff.set(
+            SnapshotDiff::ABSOLUTE_STATE_OF_CHARGE,
+            self.absolute_state_of_charge != other.absolute_state_of_charge,
+        );
+        diff.set(
+            SnapshotDiff::REMAINING_CAPACITY,
+            self.remaining_capacity != other.remaining_capacity,
+        );
+        diff.set(
+            SnapshotDiff::FULL_CHARGE_CAPACITY,
+            self.full_charge_capacity != other.full_charge_capacity,
+        );
+        diff.set(
+            SnapshotDiff::BATTERY_STATUS,
+            self.battery_status != other.battery_status,
+        );
+        diff.set(SnapshotDiff::CYCLE_COUNT, self.cycle_count != other.cycle_count);
+        diff
+    }
+}
+
+/// Blocking Smart Battery methods.
+pub trait SmartBattery: ErrorType {
+    /// 0x00
+    ///
+    /// Writes `data` to ManufacturerAccess() and returns the word read back. The meaning of both words is
+    /// vendor specific (e.g. entering sleep mode or reading the device type), see the gauge's datasheet.
+    ///
+    /// This function is optional in the SBS spec, so the default returns an error of kind `ErrorKind::Unsupported`.
+    fn manufacturer_access(&mut self, data: u16) -> Result<u16, Self::Error>
+    where
+        Self::Error: From<ErrorKind>,
+    {
+        let _ = data;
+        Err(ErrorKind::Unsupported.into())
+    }
+
+    /// 0x01
+    ///
+    /// Gets the Low Capacity alarm threshold value. Whenever the RemainingCapacity() falls below the
+    /// Low Capacity value, the Smart Battery sends AlarmWarning() messages to the SMBus Host with the
+    /// REMAINING_CAPACITY_ALARM bit set. A Low Capacity value of 0 disables this alarm.
+    /// (If the ALARM_MODE bit is set in BatteryMode() then the AlarmWarning() message is disabled for a set
+    /// period of time. See the BatteryMode() function for further information.)
+    ///
+    /// The Low Capacity value is set to 10% of design capacity at time of manufacture. The Low Capacity value
+    /// will remain unchanged until altered by the RemainingCapacityAlarm() function. The Low Capacity value
+    /// may be expressed in either capacity (mAh) or power (10mWh) depending on the setting of the
+    /// BatteryMode()'s CAPACITY_MODE bit (see BatteryMode()).
+    fn remaining_capacity_alarm(&mut self) -> Result<CapacityModeValue, Self::Error>;
+
+    /// 0x01
+    ///
+    /// Sets the Low Capacity alarm threshold value. Whenever the RemainingCapacity() falls below the
+    /// Low Capacity value, the Smart Battery sends AlarmWarning() messages to the SMBus Host with the
+    /// REMAINING_CAPACITY_ALARM bit set. A Low Capacity value of 0 disables this alarm.
+    /// (If the ALARM_MODE bit is set in BatteryMode() then the AlarmWarning() message is disabled for a set
+    /// period of time. See the BatteryMode() function for further information.)
+    ///
+    /// The Low Capacity value is set to 10% of design capacity at time of manufacture. The Low Capacity value
+    /// will remain unchanged until altered by the RemainingCapacityAlarm() function. The Low Capacity value
+    /// may be expressed in either capacity (mAh) or power (10mWh) depending on the setting of the
+    /// BatteryMode()'s CAPACITY_MODE bit (see BatteryMode()).
+    fn set_remaining_capacity_alarm(&mut self, capacity: CapacityModeValue) -> Result<(), Self::Error>;
+
+    /// 0x02
+    ///
+    /// Gets the Remaining Time alarm value. Whenever the AverageTimeToEmpty() falls below the
+    /// Remaining Time value, the Smart Battery sends AlarmWarning() messages to the SMBus Host with the
+    /// REMAINING_TIME_ALARM bit set. A Remaining Time value of 0 effectively disables this alarm.
+    /// (If the ALARM_MODE bit is set in BatteryMode() then the AlarmWarning() message is disabled for a set
+    /// period of time. See the BatteryMode() function for further information.)
+    ///
+    /// The Remaining Time value is set to 10 minutes at time of manufacture. The Remaining Time value will
+    /// remain unchanged until altered by the RemainingTimeAlarm() function.
+    fn remaining_time_alarm(&mut self) -> Result<Minutes, Self::Error>;
+
+    /// 0x02
+    ///
+    /// Sets the Remaining Time alarm value. Whenever the AverageTimeToEmpty() falls below the
+    /// Remaining Time value, the Smart Battery sends AlarmWarning() messages to the SMBus Host with the
+    /// REMAINING_TIME_ALARM bit set. A Remaining Time value of 0 effectively disables this alarm.
+    /// (If the ALARM_MODE bit is set in BatteryMode() then the AlarmWarning() message is disabled for a set
+    /// period of time. See the BatteryMode() function for further information.)
+    ///
+    /// The Remaining Time value is set to 10 minutes at time of manufacture. The Remaining Time value will
+    /// remain unchanged until altered by the RemainingTimeAlarm() function.
+    fn set_remaining_time_alarm(&mut self, time: Minutes) -> Result<(), Self::Error>;
+
+    /// 0x03
+    ///
+    /// This function reads the various battery operational modes and reports the battery’s capabilities, modes,
+    /// and flags minor conditions requiring attention.
+    ///
+    /// See the SBS specification for detailed documentation.
+    fn battery_mode(&mut self) -> Result<BatteryModeFields, Self::Error>;
+
+    /// 0x03
+    ///
+    /// This function sets the various battery operational modes and reports the battery’s capabilities, modes,
+    /// and flags minor conditions requiring attention. Note that not all fields are writeable.
+    ///
+    /// See the SBS specification for detailed documentation.
+    fn set_battery_mode(&mut self, flags: BatteryModeFields) -> Result<(), Self::Error>;
+
+    /// 0x04
+    ///
+    /// The AtRate() function is the first half of a two-function call-set used to set the AtRate value used in
+    /// calculations made by the AtRateTimeToFull(), AtRateTimeToEmpty(), and AtRateOK() functions.
+    ///
+    /// The AtRate value may be expressed in either current (mA) or power (10mW) depending on the setting of the
+    /// BatteryMode()'s CAPACITY_MODE bit. (Configuration of the CAPACITY_MODE bit will alter the
+    /// calculation of AtRate functions. Changing the state of CAPACITY_MODE may require a re-write to the
+    /// AtRate() function using the appropriate units.)
+    fn at_rate(&mut self) -> Result<CapacityModeSignedValue, Self::Error>;
+
+    /// 0x04
+    ///
+    /// The AtRate() function is the first half of a two-function call-set used to set the AtRate value used in
+    /// calculations made by the AtRateTimeToFull(), AtRateTimeToEmpty(), and AtRateOK() functions.
+    ///
+    /// The AtRate value may be expressed in either current (mA) or power (10mW) depending on the setting of the
+    /// BatteryMode()'s CAPACITY_MODE bit. (Configuration of the CAPACITY_MODE bit will alter the
+    /// calculation of AtRate functions. Changing the state of CAPACITY_MODE may require a re-write to the
+    /// AtRate() function using the appropriate units.)
+    fn set_at_rate(&mut self, rate: CapacityModeSignedValue) -> Result<(), Self::Error>;
+
+    /// 0x05
+    ///
+    /// Returns the predicted remaining time to fully charge the battery at the previously written AtRate value in mA.
+    ///
+    /// Note: This function is only required to return a value when the CAPACITY_MODE bit is cleared and the
+    /// AtRate() value is written in mA units. If the CAPACITY_MODE bit is set, then AtRateTimeToFull() may
+    /// return 65535 to indicate over-range and return an error code indicating overflow. Alternately, this function
+    /// may return a remaining time to full based on a 10 mW value in AtRate().
+    fn at_rate_time_to_full(&mut self) -> Result<Minutes, Self::Error>;
+
+    /// 0x06
+    ///
+    /// Returns the predicted remaining operating time if the battery is discharged at the previously written AtRate
+    /// value. (Result will depend on the setting of CAPACITY_MODE bit.)
+    fn at_rate_time_to_empty(&mut self) -> Result<Minutes, Self::Error>;
+
+    /// 0x07
+    ///
+    /// Returns a Boolean value that indicates whether or not the battery can deliver the previously written AtRate
+    /// value of additional energy for 10 seconds (Boolean). If the AtRate value is zero or positive, the
     /// AtRateOK() function will ALWAYS return true. Result may depend on the setting of CAPACITY_MODE
     /// bit.
     fn at_rate_ok(&mut self) -> Result<bool, Self::Error>;
@@ -763,6 +2127,15 @@ pub trait SmartBattery: ErrorType {
     /// This function accepts a mutable buffer of u8s and returns it filled with a **null-terminated** character array
     /// containing the battery's manufacturer's name. For example, "MyBattCo\0" would identify the Smart Battery's
     /// manufacturer as MyBattCo.
+    ///
+    /// The SMBus block read returns a length byte before the string. Implementations must strip it (see
+    /// `BlockQuirk::decode_block()`) and write only the string followed by its null terminator. Use `sbs_str()`
+    /// to read the result back as a `&str`.
+    ///
+    /// If the buffer is too short to hold the string plus its null terminator, implementations must return an
+    /// error of kind `ErrorKind::BatteryStatus(ErrorCode::BadSize)`. See `name_fits()`.
+    ///
+    /// The string is at most `MAX_MANUFACTURER_NAME_LEN` bytes long, so a buffer from `name_buffer()` always fits.
     fn manufacturer_name(&mut self, name: &mut [u8]) -> Result<(), Self::Error>;
 
     /// 0x21
@@ -770,14 +2143,723 @@ pub trait SmartBattery: ErrorType {
     /// This function accepts a mutable buffer of u8s and returns it filled with a **null-terminated** character array
     /// that contains the battery's name. For example, a DeviceName() of "MBC101\0" would indicate that
     /// the battery is a model MBC101.
+    ///
+    /// The SMBus block read returns a length byte before the string. Implementations must strip it (see
+    /// `BlockQuirk::decode_block()`) and write only the string followed by its null terminator. Use `sbs_str()`
+    /// to read the result back as a `&str`.
+    ///
+    /// If the buffer is too short to hold the string plus its null terminator, implementations must return an
+    /// error of kind `ErrorKind::BatteryStatus(ErrorCode::BadSize)`. See `name_fits()`.
+    ///
+    /// The string is at most `MAX_DEVICE_NAME_LEN` bytes long, so a buffer from `name_buffer()` always fits.
     fn device_name(&mut self, name: &mut [u8]) -> Result<(), Self::Error>;
 
-    /// 0x22
-    ///
-    /// This function accepts a mutable buffer of u8s and returns it filled with a **null-terminated** character array
-    /// that contains the battery's chemistry. For example, if the DeviceChemistry() function returns "NiMH\0",
-    /// the battery pack would contain nickel metal hydride cells.
-    fn device_chemistry(&mut self, chemistry: &mut [u8]) -> Result<(), Self::Error>;
+    /// 0x22
+    ///
+    /// This function accepts a mutable buffer of u8s and returns it filled with a **null-terminated** character array
+    /// that contains the battery's chemistry. For example, if the DeviceChemistry() function returns "NiMH\0",
+    /// the battery pack would contain nickel metal hydride cells.
+    ///
+    /// The SMBus block read returns a length byte before the string. Implementations must strip it (see
+    /// `BlockQuirk::decode_block()`) and write only the string followed by its null terminator. Use `sbs_str()`
+    /// to read the result back as a `&str`.
+    ///
+    /// If the buffer is too short to hold the string plus its null terminator, implementations must return an
+    /// error of kind `ErrorKind::BatteryStatus(ErrorCode::BadSize)`. See `name_fits()`.
+    ///
+    /// The string is at most `MAX_DEVICE_CHEMISTRY_LEN` bytes long, so a buffer from `name_buffer()` always fits.
+    fn device_chemistry(&mut self, chemistry: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// 0x23
+    ///
+    /// Reads the ManufacturerData() block into `buf` and returns the number of valid bytes written. The block
+    /// length is taken from the SMBus length byte, which isn't copied into `buf`, so at most 32 bytes are written.
+    /// The content is vendor specific, see the gauge's datasheet.
+    ///
+    /// If `buf` is too short to hold the block, implementations must return an error of kind
+    /// `ErrorKind::BatteryStatus(ErrorCode::BadSize)`.
+    ///
+    /// This function is optional in the SBS spec, so the default returns an error of kind `ErrorKind::Unsupported`.
+    fn manufacturer_data(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error>
+    where
+        Self::Error: From<ErrorKind>,
+    {
+        let _ = buf;
+        Err(ErrorKind::Unsupported.into())
+    }
+
+    /// Returns the cell-pack's internal temperature in decidegrees Celsius.
+    ///
+    /// See `decikelvin_to_decicelsius()` for the conversion and its range.
+    fn temperature_celsius(&mut self) -> Result<DeciCelsius, Self::Error> {
+        Ok(decikelvin_to_decicelsius(self.temperature()?))
+    }
+
+    /// Returns an estimate of the battery's self-discharge (quiescent) current in mA.
+    ///
+    /// This is a heuristic based on current(): when the battery reports it is discharging and the
+    /// measured discharge current is no larger than `IDLE_CURRENT_THRESHOLD`, the system is assumed to be
+    /// idle and the measured current is returned as the self-discharge estimate. Otherwise the battery is
+    /// either charging or under load and `None` is returned.
+    fn self_discharge_estimate(&mut self) -> Result<Option<MilliAmps>, Self::Error> {
+        let current = self.current()?;
+        if current > 0 || current.unsigned_abs() > IDLE_CURRENT_THRESHOLD {
+            return Ok(None);
+        }
+
+        if self.battery_status()?.discharging() {
+            Ok(Some(current.unsigned_abs()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Returns the unit capacity and rate values are currently reported in, based on the
+    /// BatteryMode()'s CAPACITY_MODE bit.
+    ///
+    /// `PowerUnit::MilliAmps` means capacities are in mAh and rates in mA (CAPACITY_MODE = 0).
+    /// `PowerUnit::MilliWatts` means capacities are in 10mWh and rates in 10mW (CAPACITY_MODE = 1).
+    fn capacity_unit(&mut self) -> Result<PowerUnit, Self::Error> {
+        if self.battery_mode()?.capacity_mode() {
+            Ok(PowerUnit::MilliWatts)
+        } else {
+            Ok(PowerUnit::MilliAmps)
+        }
+    }
+
+    /// Reads the SBS strings needed to populate an ACPI `BixReturn` in one call.
+    ///
+    /// `model` is filled from `device_name()`, `serial` with the decimal ASCIIZ representation of
+    /// `serial_number()` and `chem` from `device_chemistry()`. BIX's `oem_info` has no SBS source and must be
+    /// supplied by the caller.
+    ///
+    /// Returns an error of kind `ErrorKind::BatteryStatus(ErrorCode::BadSize)` if `serial` is too short to hold
+    /// the serial number digits plus a null terminator (6 bytes always suffice).
+    fn bix_strings(&mut self, model: &mut [u8], serial: &mut [u8], chem: &mut [u8]) -> Result<(), Self::Error>
+    where
+        Self::Error: From<ErrorKind>,
+    {
+        self.device_name(model)?;
+
+        let mut number = self.serial_number()?;
+        let mut digits = [0u8; 5];
+        let mut len = 0;
+        loop {
+            digits[len] = b'0' + (number % 10) as u8;
+            len += 1;
+            number /= 10;
+            if number == 0 {
+                break;
+            }
+        }
+        name_fits(serial, len)?;
+        for (dst, src) in serial.iter_mut().zip(digits[..len].iter().rev()) {
+            *dst = *src;
+        }
+        serial[len] = 0;
+
+        self.device_chemistry(chem)
+    }
+
+    /// Reads the commonly polled dynamic values in one call.
+    ///
+    /// The fields are read sequentially in declaration order, not atomically, so values may come from different
+    /// measurement updates. Reading stops at the first error, which is returned. See `snapshot_partial()` to keep
+    /// the values read before a failure.
+    fn snapshot(&mut self) -> Result<BatterySnapshot, Self::Error> {
+        Ok(BatterySnapshot {
+            voltage: self.voltage()?,
+            current: self.current()?,
+            average_current: self.average_current()?,
+            temperature: self.temperature()?,
+            relative_state_of_charge: self.relative_state_of_charge()?,
+            absolute_state_of_charge: self.absolute_state_of_charge()?,
+            remaining_capacity: self.remaining_capacity()?,
+            full_charge_capacity: self.full_charge_capacity()?,
+            battery_status: self.battery_status()?,
+            cycle_count: self.cycle_count()?,
+        })
+    }
+
+    /// Reads the commonly polled dynamic values, recording each read's result independently.
+    ///
+    /// Unlike chaining the individual reads with `?`, a failing read doesn't discard the values that were read
+    /// successfully. Errors are converted to their `ErrorKind`.
+    fn snapshot_partial(&mut self) -> PartialSnapshot {
+        PartialSnapshot {
+            voltage: self.voltage().map_err(|e| e.kind()),
+            current: self.current().map_err(|e| e.kind()),
+            average_current: self.average_current().map_err(|e| e.kind()),
+            temperature: self.temperature().map_err(|e| e.kind()),
+            relative_state_of_charge: self.relative_state_of_charge().map_err(|e| e.kind()),
+            absolute_state_of_charge: self.absolute_state_of_charge().map_err(|e| e.kind()),
+            remaining_capacity: self.remaining_capacity().map_err(|e| e.kind()),
+            full_charge_capacity: self.full_charge_capacity().map_err(|e| e.kind()),
+            battery_status: self.battery_status().map_err(|e| e.kind()),
+            cycle_count: self.cycle_count().map_err(|e| e.kind()),
+        }
+    }
+
+    /// Writes a human readable report of the commonly polled dynamic values to `out`.
+    ///
+    /// The values are read with `snapshot_partial()`, so a failing read is reported in its line instead of
+    /// aborting the report. See the `Display` implementation of `PartialSnapshot` for the format.
+    fn report<W: core::fmt::Write>(&mut self, out: &mut W) -> core::fmt::Result {
+        write!(out, "{}", self.snapshot_partial())
+    }
+
+    /// Checks that the battery implements at least version `min` of the SBS specification.
+    ///
+    /// Reads specification_info() and returns an error of kind `ErrorKind::UnsupportedVersion` carrying the
+    /// reported version if it is older than `min`.
+    fn require_version(&mut self, min: Version) -> Result<(), Self::Error>
+    where
+        Self::Error: From<ErrorKind>,
+    {
+        let version = self.specification_info()?.version();
+        if version < min {
+            Err(ErrorKind::UnsupportedVersion(version).into())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Estimates pack wear as `cycle_count() * 100 / rated_cycles`, rounded to the nearest percent and
+    /// saturating at `Percent::MAX`.
+    ///
+    /// Rated cycle life is chemistry and vendor specific and is not reported by the battery, so it must be
+    /// supplied by the caller (e.g. from the cell datasheet). A `rated_cycles` of 0 yields `Percent::MAX`.
+    fn cycle_wear_percent(&mut self, rated_cycles: Cycles) -> Result<Percent, Self::Error> {
+        Ok(crate::percent_u16(self.cycle_count()?, rated_cycles))
+    }
+
+    /// Returns the cell-pack's internal temperature, or `None` if the gauge doesn't measure temperature.
+    ///
+    /// Some gauges without a temperature sensor report 0 dK (absolute zero). Since no real pack can be at
+    /// absolute zero, a reading of 0 is treated as "unsupported" rather than reported as -273.1 °C.
+    fn temperature_opt(&mut self) -> Result<Option<DeciKelvin>, Self::Error> {
+        match self.temperature()? {
+            0 => Ok(None),
+            temperature => Ok(Some(temperature)),
+        }
+    }
+
+    /// Estimates the remaining energy in mWh from remaining_capacity().
+    ///
+    /// In 10mWh capacity mode the value is converted directly. In mAh mode it is multiplied by design_voltage(),
+    /// which is an approximation: the actual energy depends on the pack voltage over the rest of the discharge,
+    /// not the nominal design voltage.
+    fn remaining_energy_mwh(&mut self) -> Result<u32, Self::Error> {
+        match self.remaining_capacity()? {
+            CapacityModeValue::CentiWattUnsigned(capacity) => Ok(u32::from(capacity) * 10),
+            CapacityModeValue::MilliAmpUnsigned(capacity) => {
+                let voltage = self.design_voltage()?;
+                Ok((u64::from(capacity) * u64::from(voltage) / 1000) as u32)
+            }
+        }
+    }
+
+    /// Writes BatteryMode() and verifies that its read only bits (INTERNAL_CHARGE_CONTROLLER,
+    /// PRIMARY_BATTERY_SUPPORT and CONDITION_FLAG) are unchanged, returning the mode read back after the write.
+    ///
+    /// Returns an error of kind `ErrorKind::ReadOnlyModeChanged` if any read only bit differs from its value
+    /// before the write, which indicates a gauge fault.
+    fn write_battery_mode_verified(&mut self, fields: BatteryModeFields) -> Result<BatteryModeFields, Self::Error>
+    where
+        Self::Error: From<ErrorKind>,
+    {
+        let before = self.battery_mode()?;
+        self.set_battery_mode(fields)?;
+        let after = self.battery_mode()?;
+
+        if before.internal_charge_controller() != after.internal_charge_controller()
+            || before.primary_battery_support() != after.primary_battery_support()
+            || before.condition_flag() != after.condition_flag()
+        {
+            Err(ErrorKind::ReadOnlyModeChanged.into())
+        } else {
+            Ok(after)
+        }
+    }
+
+    /// Best-effort indication that the gauge has just powered on or been reset and its averaged values (e.g.
+    /// average_current(), average_time_to_empty()) may still be settling.
+    ///
+    /// SBS doesn't expose gauge uptime, so this returns `true` if the INITIALIZED bit of battery_status() is
+    /// clear, or if cycle_count() is 0, i.e. the gauge has no learned history yet. It may report `false` for a
+    /// gauge that restarted with retained calibration data.
+    fn freshly_powered(&mut self) -> Result<bool, Self::Error> {
+        if !self.battery_status()?.initialized() {
+            return Ok(true);
+        }
+        Ok(self.cycle_count()? == 0)
+    }
+
+    /// Reads the gauge's lifetime data, or `None` if the gauge doesn't provide it.
+    ///
+    /// Lifetime data lives in a vendor specific manufacturer block, so the default returns `None`. Gauges that
+    /// support it should read the block and decode it with a `LifetimeDataLayout` matching their firmware.
+    fn lifetime_data(&mut self) -> Result<Option<LifetimeData>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Reads the pack's internal impedance in milliohms, or `None` if the gauge doesn't provide it.
+    ///
+    /// The impedance is used to estimate the voltage sag under load (sag = current * impedance), e.g. before
+    /// allowing a power burst. It isn't an SBS function, so the default returns `None`. Gauges that expose it
+    /// through a manufacturer register should override this to read it.
+    fn internal_impedance_milliohm(&mut self) -> Result<Option<u16>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Reads the gauge's effective measurement interval in milliseconds, or `None` if it isn't known.
+    ///
+    /// Polling faster than this only returns the same values again. SBS has no function for it, so the default
+    /// returns `None`. Gauges that expose it through a manufacturer register should override this to read it.
+    ///
+    /// This is the interval currently in effect, which ACPI `_BMS` sets and which must lie between the BIX
+    /// `min_sampling_time` and `max_sampling_time`.
+    fn measurement_interval_ms(&mut self) -> Result<Option<u32>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Returns the number of series cells in the pack, or `None` if it can't be determined.
+    ///
+    /// Gauges that report the cell count through a manufacturer register should override this to read it. The
+    /// default derives it from DesignVoltage() and DeviceChemistry() with `Chemistry::plausible_cell_count()`, and
+    /// returns `None` for chemistries not defined by the SBS spec.
+    fn cell_count(&mut self) -> Result<Option<u8>, Self::Error> {
+        let mut chemistry = name_buffer();
+        self.device_chemistry(&mut chemistry)?;
+        let Some(chemistry) = Chemistry::from_device_chemistry(&chemistry) else {
+            return Ok(None);
+        };
+        Ok(chemistry.plausible_cell_count(self.design_voltage()?))
+    }
+
+    /// Reads every standard register into `out` for offline analysis, returning the number of bytes written.
+    ///
+    /// The word registers 0x01 - 0x1C are written first, in command order, as little-endian words (56 bytes).
+    /// They are followed by the block registers 0x20 - 0x22, each length-prefixed: one length byte, then that
+    /// many string bytes without the null terminator. The dump is at most `STANDARD_REGISTER_DUMP_MAX_LEN` bytes,
+    /// and an error of kind `ErrorKind::BatteryStatus(ErrorCode::BadSize)` is returned if it doesn't fit in
+    /// `out`. The dump reflects the current BatteryMode() and AtRate() settings, and AtRateOK() is dumped as 1
+    /// or 0.
+    fn dump_standard_registers(&mut self, out: &mut [u8]) -> Result<usize, Self::Error>
+    where
+        Self::Error: From<ErrorKind>,
+    {
+        let capacity = |value: CapacityModeValue| match value {
+            CapacityModeValue::MilliAmpUnsigned(raw) | CapacityModeValue::CentiWattUnsigned(raw) => raw,
+        };
+        let at_rate = match self.at_rate()? {
+            CapacityModeSignedValue::MilliAmpSigned(raw) | CapacityModeSignedValue::CentiWattSigned(raw) => raw as u16,
+        };
+        let words = [
+            capacity(self.remaining_capacity_alarm()?),
+            self.remaining_time_alarm()?,
+            self.battery_mode()?.into_bits(),
+            at_rate,
+            self.at_rate_time_to_full()?,
+            self.at_rate_time_to_empty()?,
+            u16::from(self.at_rate_ok()?),
+            self.temperature()?,
+            self.voltage()?,
+            self.current()? as u16,
+            self.average_current()? as u16,
+            u16::from(self.max_error()?),
+            u16::from(self.relative_state_of_charge()?),
+            u16::from(self.absolute_state_of_charge()?),
+            capacity(self.remaining_capacity()?),
+            capacity(self.full_charge_capacity()?),
+            self.run_time_to_empty()?,
+            self.average_time_to_empty()?,
+            self.average_time_to_full()?,
+            self.charging_current()?,
+            self.charging_voltage()?,
+            self.battery_status()?.into_bits(),
+            self.cycle_count()?,
+            capacity(self.design_capacity()?),
+            self.design_voltage()?,
+            self.specification_info()?.into_bits(),
+            self.manufacture_date()?.into_bits(),
+            self.serial_number()?,
+        ];
+
+        let mut written = 0;
+        for word in words {
+            let dst = out
+                .get_mut(written..written + 2)
+                .ok_or(ErrorKind::BatteryStatus(ErrorCode::BadSize))?;
+            dst.copy_from_slice(&word.to_le_bytes());
+            written += 2;
+        }
+
+        let mut names = [name_buffer(); 3];
+        self.manufacturer_name(&mut names[0])?;
+        self.device_name(&mut names[1])?;
+        self.device_chemistry(&mut names[2])?;
+        for name in names {
+            let len = name.iter().position(|&b| b == 0).unwrap_or(name.len() - 1);
+            let dst = out
+                .get_mut(written..written + 1 + len)
+                .ok_or(ErrorKind::BatteryStatus(ErrorCode::BadSize))?;
+            dst[0] = len as u8;
+            dst[1..].copy_from_slice(&name[..len]);
+            written += 1 + len;
+        }
+        Ok(written)
+    }
+
+    /// Returns the deviation of current() from average_current(), saturating at the `i16` range.
+    ///
+    /// A large magnitude indicates a load transient. A positive value means the instantaneous current is higher
+    /// than the one minute average (more charge, or less discharge, than usual), a negative value means it is
+    /// lower (e.g. a discharge burst).
+    fn current_transient(&mut self) -> Result<i16, Self::Error> {
+        let current = self.current()?;
+        let average = self.average_current()?;
+        Ok(current.saturating_sub(average))
+    }
+
+    /// Returns the instantaneous power flowing into the battery in mW, as `voltage() * current() / 1000`.
+    ///
+    /// The sign follows current(): positive while charging, negative while discharging. The result is
+    /// truncated toward zero, so magnitudes below 1mW read as 0.
+    fn present_power_mw(&mut self) -> Result<i32, Self::Error> {
+        let voltage = self.voltage()?;
+        let current = self.current()?;
+        Ok((i64::from(voltage) * i64::from(current) / 1000) as i32)
+    }
+
+    /// Returns true if current() (0x0A) is positive, i.e. the battery is accepting charge.
+    ///
+    /// A current of 0 (idle or fully charged) reads as not charging. Use battery_status() to tell the two apart.
+    fn is_charging(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.current()? > 0)
+    }
+
+    /// Returns at_rate_time_to_full() (0x05) as a `TimeEstimate`.
+    fn at_rate_time_to_full_estimate(&mut self) -> Result<TimeEstimate, Self::Error> {
+        self.at_rate_time_to_full().map(TimeEstimate::from)
+    }
+
+    /// Returns at_rate_time_to_empty() (0x06) as a `TimeEstimate`.
+    fn at_rate_time_to_empty_estimate(&mut self) -> Result<TimeEstimate, Self::Error> {
+        self.at_rate_time_to_empty().map(TimeEstimate::from)
+    }
+
+    /// Returns run_time_to_empty() (0x11) as a `TimeEstimate`.
+    fn run_time_to_empty_estimate(&mut self) -> Result<TimeEstimate, Self::Error> {
+        self.run_time_to_empty().map(TimeEstimate::from)
+    }
+
+    /// Returns average_time_to_empty() (0x12) as a `TimeEstimate`.
+    fn average_time_to_empty_estimate(&mut self) -> Result<TimeEstimate, Self::Error> {
+        self.average_time_to_empty().map(TimeEstimate::from)
+    }
+
+    /// Returns average_time_to_full() (0x13) as a `TimeEstimate`.
+    fn average_time_to_full_estimate(&mut self) -> Result<TimeEstimate, Self::Error> {
+        self.average_time_to_full().map(TimeEstimate::from)
+    }
+
+    /// Writes `rate` to AtRate() (0x04) then reads AtRateTimeToFull() (0x05), AtRateTimeToEmpty() (0x06) and
+    /// AtRateOK() (0x07), in that order.
+    ///
+    /// The reads follow the write directly so all three predictions are made for the same rate. Callers sharing
+    /// the battery with other hosts must hold it for the whole call.
+    fn at_rate_full_analysis(&mut self, rate: CapacityModeSignedValue) -> Result<AtRateAnalysis, Self::Error> {
+        self.set_at_rate(rate)?;
+        let time_to_full = self.at_rate_time_to_full()?;
+        let time_to_empty = self.at_rate_time_to_empty()?;
+        let ok = self.at_rate_ok()?;
+        Ok(AtRateAnalysis::new(rate, time_to_full, time_to_empty, ok))
+    }
+}
+
+/// Smart Battery adapter that reads static battery data once and serves later reads from memory.
+///
+/// design_capacity(), design_voltage(), specification_info(), manufacture_date(), serial_number(),
+/// manufacturer_name(), device_name() and device_chemistry() don't change while a battery is installed, so
+/// polling hosts can avoid re-reading them. cell_count() isn't forwarded, so its default runs against the
+/// cached design_voltage() and device_chemistry(). All other functions are forwarded unchanged. Call
+/// `invalidate()` after the battery may have been replaced (e.g. a hot-swap).
+///
+/// The cached design_capacity() is discarded whenever set_battery_mode() is called, since its unit depends on
+/// the CAPACITY_MODE bit. Writing BatteryMode directly through `inner_mut()` requires calling `invalidate()`.
+#[derive(Debug, Clone)]
+pub struct CachedStatics<B> {
+    battery: B,
+    design_capacity: Option<CapacityModeValue>,
+    design_voltage: Option<MilliVolts>,
+    specification_info: Option<SpecificationInfoFields>,
+    manufacture_date: Option<ManufactureDate>,
+    serial_number: Option<u16>,
+    manufacturer_name: Option<[u8; MAX_MANUFACTURER_NAME_LEN + 1]>,
+    device_name: Option<[u8; MAX_DEVICE_NAME_LEN + 1]>,
+    device_chemistry: Option<[u8; MAX_DEVICE_CHEMISTRY_LEN + 1]>,
+}
+
+impl<B> CachedStatics<B> {
+    /// Wrap a battery, starting with an empty cache.
+    pub const fn new(battery: B) -> Self {
+        Self {
+            battery,
+            design_capacity: None,
+            design_voltage: None,
+            specification_info: None,
+            manufacture_date: None,
+            serial_number: None,
+            manufacturer_name: None,
+            device_name: None,
+            device_chemistry: None,
+        }
+    }
+
+    /// Discards all cached values so they are re-read from the battery on next access.
+    pub fn invalidate(&mut self) {
+        self.design_capacity = None;
+        self.design_voltage = None;
+        self.specification_info = None;
+        self.manufacture_date = None;
+        self.serial_number = None;
+        self.manufacturer_name = None;
+        self.device_name = None;
+        self.device_chemistry = None;
+    }
+
+    /// Returns a reference to the wrapped battery.
+    pub const fn inner(&self) -> &B {
+        &self.battery
+    }
+
+    /// Returns a mutable reference to the wrapped battery.
+    ///
+    /// Changes made directly through the wrapped battery don't update the cache.
+    pub fn inner_mut(&mut self) -> &mut B {
+        &mut self.battery
+    }
+
+    /// Consumes the adapter, returning the wrapped battery.
+    pub fn into_inner(self) -> B {
+        self.battery
+    }
+}
+
+/// Serves a cached value, or reads and caches it.
+fn cached<T: Copy, E>(cache: &mut Option<T>, read: impl FnOnce() -> Result<T, E>) -> Result<T, E> {
+    match *cache {
+        Some(value) => Ok(value),
+        None => {
+            let value = read()?;
+            *cache = Some(value);
+            Ok(value)
+        }
+    }
+}
+
+/// Serves a cached null-terminated string if it fits in `dst`, or reads it into `dst` and caches it.
+///
+/// If the cached string doesn't fit, the read is forwarded so the battery reports the error.
+fn cached_string<const N: usize, E>(
+    cache: &mut Option<[u8; N]>,
+    dst: &mut [u8],
+    read: impl FnOnce(&mut [u8]) -> Result<(), E>,
+) -> Result<(), E> {
+    if let Some(cached) = cache {
+        if let Some(len) = cached.iter().position(|&b| b == 0) {
+            if let Some(dst) = dst.get_mut(..=len) {
+                dst.copy_from_slice(&cached[..=len]);
+                return Ok(());
+            }
+        }
+    }
+
+    read(dst)?;
+    if let Some(len) = dst.iter().position(|&b| b == 0).filter(|&len| len < N) {
+        let mut value = [0; N];
+        value[..len].copy_from_slice(&dst[..len]);
+        *cache = Some(value);
+    }
+    Ok(())
+}
+
+impl<B: ErrorType> ErrorType for CachedStatics<B> {
+    type Error = B::Error;
+}
+
+impl<B: SmartBattery> SmartBattery for CachedStatics<B> {
+    fn manufacturer_access(&mut self, data: u16) -> Result<u16, Self::Error>
+    where
+        Self::Error: From<ErrorKind>,
+    {
+        self.battery.manufacturer_access(data)
+    }
+
+    fn remaining_capacity_alarm(&mut self) -> Result<CapacityModeValue, Self::Error> {
+        self.battery.remaining_capacity_alarm()
+    }
+
+    fn set_remaining_capacity_alarm(&mut self, capacity: CapacityModeValue) -> Result<(), Self::Error> {
+        self.battery.set_remaining_capacity_alarm(capacity)
+    }
+
+    fn remaining_time_alarm(&mut self) -> Result<Minutes, Self::Error> {
+        self.battery.remaining_time_alarm()
+    }
+
+    fn set_remaining_time_alarm(&mut self, time: Minutes) -> Result<(), Self::Error> {
+        self.battery.set_remaining_time_alarm(time)
+    }
+
+    fn battery_mode(&mut self) -> Result<BatteryModeFields, Self::Error> {
+        self.battery.battery_mode()
+    }
+
+    fn set_battery_mode(&mut self, flags: BatteryModeFields) -> Result<(), Self::Error> {
+        self.design_capacity = None;
+        self.battery.set_battery_mode(flags)
+    }
+
+    fn at_rate(&mut self) -> Result<CapacityModeSignedValue, Self::Error> {
+        self.battery.at_rate()
+    }
+
+    fn set_at_rate(&mut self, rate: CapacityModeSignedValue) -> Result<(), Self::Error> {
+        self.battery.set_at_rate(rate)
+    }
+
+    fn at_rate_time_to_full(&mut self) -> Result<Minutes, Self::Error> {
+        self.battery.at_rate_time_to_full()
+    }
+
+    fn at_rate_time_to_empty(&mut self) -> Result<Minutes, Self::Error> {
+        self.battery.at_rate_time_to_empty()
+    }
+
+    fn at_rate_ok(&mut self) -> Result<bool, Self::Error> {
+        self.battery.at_rate_ok()
+    }
+
+    fn temperature(&mut self) -> Result<DeciKelvin, Self::Error> {
+        self.battery.temperature()
+    }
+
+    fn voltage(&mut self) -> Result<MilliVolts, Self::Error> {
+        self.battery.voltage()
+    }
+
+    fn current(&mut self) -> Result<MilliAmpsSigned, Self::Error> {
+        self.battery.current()
+    }
+
+    fn average_current(&mut self) -> Result<MilliAmpsSigned, Self::Error> {
+        self.battery.average_current()
+    }
+
+    fn max_error(&mut self) -> Result<Percent, Self::Error> {
+        self.battery.max_error()
+    }
+
+    fn relative_state_of_charge(&mut self) -> Result<Percent, Self::Error> {
+        self.battery.relative_state_of_charge()
+    }
+
+    fn absolute_state_of_charge(&mut self) -> Result<Percent, Self::Error> {
+        self.battery.absolute_state_of_charge()
+    }
+
+    fn remaining_capacity(&mut self) -> Result<CapacityModeValue, Self::Error> {
+        self.battery.remaining_capacity()
+    }
+
+    fn full_charge_capacity(&mut self) -> Result<CapacityModeValue, Self::Error> {
+        self.battery.full_charge_capacity()
+    }
+
+    fn run_time_to_empty(&mut self) -> Result<Minutes, Self::Error> {
+        self.battery.run_time_to_empty()
+    }
+
+    fn average_time_to_empty(&mut self) -> Result<Minutes, Self::Error> {
+        self.battery.average_time_to_empty()
+    }
+
+    fn average_time_to_full(&mut self) -> Result<Minutes, Self::Error> {
+        self.battery.average_time_to_full()
+    }
+
+    fn charging_current(&mut self) -> Result<MilliAmps, Self::Error> {
+        self.battery.charging_current()
+    }
+
+    fn charging_voltage(&mut self) -> Result<MilliVolts, Self::Error> {
+        self.battery.charging_voltage()
+    }
+
+    fn battery_status(&mut self) -> Result<BatteryStatusFields, Self::Error> {
+        self.battery.battery_status()
+    }
+
+    fn cycle_count(&mut self) -> Result<Cycles, Self::Error> {
+        self.battery.cycle_count()
+    }
+
+    fn design_capacity(&mut self) -> Result<CapacityModeValue, Self::Error> {
+        cached(&mut self.design_capacity, || self.battery.design_capacity())
+    }
+
+    fn design_voltage(&mut self) -> Result<MilliVolts, Self::Error> {
+        cached(&mut self.design_voltage, || self.battery.design_voltage())
+    }
+
+    fn specification_info(&mut self) -> Result<SpecificationInfoFields, Self::Error> {
+        cached(&mut self.specification_info, || self.battery.specification_info())
+    }
+
+    fn manufacture_date(&mut self) -> Result<ManufactureDate, Self::Error> {
+        cached(&mut self.manufacture_date, || self.battery.manufacture_date())
+    }
+
+    fn serial_number(&mut self) -> Result<u16, Self::Error> {
+        cached(&mut self.serial_number, || self.battery.serial_number())
+    }
+
+    fn manufacturer_name(&mut self, name: &mut [u8]) -> Result<(), Self::Error> {
+        cached_string(&mut self.manufacturer_name, name, |name| {
+            self.battery.manufacturer_name(name)
+        })
+    }
+
+    fn device_name(&mut self, name: &mut [u8]) -> Result<(), Self::Error> {
+        cached_string(&mut self.device_name, name, |name| self.battery.device_name(name))
+    }
+
+    fn device_chemistry(&mut self, chemistry: &mut [u8]) -> Result<(), Self::Error> {
+        cached_string(&mut self.device_chemistry, chemistry, |chemistry| {
+            self.battery.device_chemistry(chemistry)
+        })
+    }
+
+    fn lifetime_data(&mut self) -> Result<Option<LifetimeData>, Self::Error> {
+        self.battery.lifetime_data()
+    }
+
+    fn internal_impedance_milliohm(&mut self) -> Result<Option<u16>, Self::Error> {
+        self.battery.internal_impedance_milliohm()
+    }
+
+    fn manufacturer_data(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error>
+    where
+        Self::Error: From<ErrorKind>,
+    {
+        self.battery.manufacturer_data(buf)
+    }
+
+    fn measurement_interval_ms(&mut self) -> Result<Option<u32>, Self::Error> {
+        self.battery.measurement_interval_ms()
+    }
 }
 
 #[macro_export]
@@ -792,6 +2874,8 @@ pub trait SmartBattery: ErrorType {
 ///
 /// - `From<inner::Error>` must be implemented for the wrapper error type to enable error conversion
 /// - The wrapper error type must implement the `Error` trait
+/// - The inner error type must implement `From<ErrorKind>`, as `manufacturer_access()` and
+///   `manufacturer_data()` are forwarded to it
 ///
 /// # Parameters
 ///
@@ -884,15 +2968,15 @@ macro_rules! impl_smart_battery_for_wrapper_type {
                 Ok(self.$inner.temperature()?)
             }
 
-            fn voltage(&mut self) -> Result<embedded_batteries::charger::MilliVolts, Self::Error> {
+            fn voltage(&mut self) -> Result<embedded_batteries::MilliVolts, Self::Error> {
                 Ok(self.$inner.voltage()?)
             }
 
-            fn current(&mut self) -> Result<embedded_batteries::smart_battery::MilliAmpsSigned, Self::Error> {
+            fn current(&mut self) -> Result<embedded_batteries::MilliAmpsSigned, Self::Error> {
                 Ok(self.$inner.current()?)
             }
 
-            fn average_current(&mut self) -> Result<embedded_batteries::smart_battery::MilliAmpsSigned, Self::Error> {
+            fn average_current(&mut self) -> Result<embedded_batteries::MilliAmpsSigned, Self::Error> {
                 Ok(self.$inner.average_current()?)
             }
 
@@ -932,11 +3016,11 @@ macro_rules! impl_smart_battery_for_wrapper_type {
                 Ok(self.$inner.average_time_to_full()?)
             }
 
-            fn charging_current(&mut self) -> Result<embedded_batteries::charger::MilliAmps, Self::Error> {
+            fn charging_current(&mut self) -> Result<embedded_batteries::MilliAmps, Self::Error> {
                 Ok(self.$inner.charging_current()?)
             }
 
-            fn charging_voltage(&mut self) -> Result<embedded_batteries::charger::MilliVolts, Self::Error> {
+            fn charging_voltage(&mut self) -> Result<embedded_batteries::MilliVolts, Self::Error> {
                 Ok(self.$inner.charging_voltage()?)
             }
 
@@ -954,7 +3038,7 @@ macro_rules! impl_smart_battery_for_wrapper_type {
                 Ok(self.$inner.design_capacity()?)
             }
 
-            fn design_voltage(&mut self) -> Result<embedded_batteries::charger::MilliVolts, Self::Error> {
+            fn design_voltage(&mut self) -> Result<embedded_batteries::MilliVolts, Self::Error> {
                 Ok(self.$inner.design_voltage()?)
             }
 
@@ -983,6 +3067,1224 @@ macro_rules! impl_smart_battery_for_wrapper_type {
             fn device_chemistry(&mut self, chemistry: &mut [u8]) -> Result<(), Self::Error> {
                 Ok(self.$inner.device_chemistry(chemistry)?)
             }
+
+            fn lifetime_data(
+                &mut self,
+            ) -> Result<Option<embedded_batteries::smart_battery::LifetimeData>, Self::Error> {
+                Ok(self.$inner.lifetime_data()?)
+            }
+
+            fn internal_impedance_milliohm(&mut self) -> Result<Option<u16>, Self::Error> {
+                Ok(self.$inner.internal_impedance_milliohm()?)
+            }
+
+            fn manufacturer_access(&mut self, data: u16) -> Result<u16, Self::Error>
+            where
+                Self::Error: From<embedded_batteries::smart_battery::ErrorKind>,
+            {
+                Ok(self.$inner.manufacturer_access(data)?)
+            }
+
+            fn measurement_interval_ms(&mut self) -> Result<Option<u32>, Self::Error> {
+                Ok(self.$inner.measurement_interval_ms()?)
+            }
+
+            fn manufacturer_data(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error>
+            where
+                Self::Error: From<embedded_batteries::smart_battery::ErrorKind>,
+            {
+                Ok(self.$inner.manufacturer_data(buf)?)
+            }
+
+            fn cell_count(&mut self) -> Result<Option<u8>, Self::Error> {
+                Ok(self.$inner.cell_count()?)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{Access, RegisterFileBattery};
+
+    #[test]
+    fn capacity_unit_milliamps() {
+        let mut battery = RegisterFileBattery::new();
+        battery.set(
+            Command::BatteryMode,
+            BatteryModeFields::new().with_capacity_mode(false).into_bits(),
+        );
+        assert_eq!(battery.capacity_unit(), Ok(PowerUnit::MilliAmps));
+    }
+
+    #[test]
+    fn capacity_unit_milliwatts() {
+        let mut battery = RegisterFileBattery::new();
+        battery.set(
+            Command::BatteryMode,
+            BatteryModeFields::new().with_capacity_mode(true).into_bits(),
+        );
+        assert_eq!(battery.capacity_unit(), Ok(PowerUnit::MilliWatts));
+    }
+
+    #[test]
+    fn capacity_unit_propagates_read_error() {
+        let mut battery = RegisterFileBattery::new();
+        battery.failing = Some(Command::BatteryMode);
+        assert_eq!(battery.capacity_unit(), Err(ErrorKind::CommError));
+    }
+
+    #[test]
+    fn severity_classification() {
+        assert_eq!(ErrorCode::Ok.severity(), None);
+        assert_eq!(ErrorCode::Busy.severity(), Some(Severity::Transient));
+        assert_eq!(ErrorCode::UnsupportedCmd.severity(), Some(Severity::Permanent));
+        assert_eq!(ErrorCode::AccessDenied.severity(), Some(Severity::Permanent));
+        assert_eq!(ErrorCode::BadSize.severity(), Some(Severity::Fatal));
+        assert_eq!(ErrorCode::UnknownError.severity(), Some(Severity::Fatal));
+    }
+
+    #[test]
+    fn severity_orders_by_seriousness_not_encoding() {
+        // ReservedCmd (2) and UnsupportedCmd (3) have different encodings but the same severity.
+        assert!(ErrorCode::Busy.severity() < ErrorCode::AccessDenied.severity());
+        assert!(ErrorCode::AccessDenied.severity() < ErrorCode::UnderOverFlow.severity());
+        assert_eq!(ErrorCode::ReservedCmd.severity(), ErrorCode::UnsupportedCmd.severity());
+        assert_eq!(
+            ErrorCode::from_status_word(0x00C1).severity(),
+            Some(Severity::Transient)
+        );
+    }
+
+    #[test]
+    fn decikelvin_below_freezing() {
+        assert_eq!(decikelvin_to_decicelsius(2600), -131);
+        assert_eq!(decikelvin_to_decicelsius(0), -2731);
+    }
+
+    #[test]
+    fn decikelvin_at_freezing() {
+        assert_eq!(decikelvin_to_decicelsius(2731), 0);
+    }
+
+    #[test]
+    fn decikelvin_saturates_above_decicelsius_max() {
+        assert_eq!(decikelvin_to_decicelsius(u16::MAX), DeciCelsius::MAX);
+    }
+
+    #[test]
+    fn temperature_celsius_below_freezing() {
+        let mut battery = RegisterFileBattery::new();
+        battery.set(Command::Temperature, 2600);
+        assert_eq!(battery.temperature_celsius(), Ok(-131));
+        battery.set(Command::Temperature, 2731);
+        assert_eq!(battery.temperature_celsius(), Ok(0));
+    }
+
+    #[test]
+    fn name_fits_requires_room_for_terminator() {
+        assert_eq!(name_fits(&[0; 10], 9), Ok(()));
+        assert_eq!(name_fits(&[0; 9], 9), Err(ErrorKind::BatteryStatus(ErrorCode::BadSize)));
+        assert_eq!(name_fits(&[], 0), Err(ErrorKind::BatteryStatus(ErrorCode::BadSize)));
+    }
+
+    #[test]
+    fn short_name_buffer_is_bad_size() {
+        let mut battery = RegisterFileBattery::new();
+        assert_eq!(battery.device_name.len(), 9);
+
+        let mut name = [0xAA; 3];
+        assert_eq!(
+            battery.device_name(&mut name),
+            Err(ErrorKind::BatteryStatus(ErrorCode::BadSize))
+        );
+        assert_eq!(name, [0xAA; 3]);
+
+        let mut name = [0xAA; 10];
+        assert_eq!(battery.device_name(&mut name), Ok(()));
+        assert_eq!(sbs_str(&name), "PACK-3S1P");
+    }
+
+    #[test]
+    fn specification_info_packs_v1_1() {
+        let info = SpecificationInfoFields::from_parts(Revision::Version1And1Dot1, Version::Version1Dot1, 1, 2);
+        assert_eq!(info.into_bits(), 0x2121);
+        assert_eq!(info.revision(), Revision::Version1And1Dot1);
+        assert_eq!(info.version(), Version::Version1Dot1);
+        assert_eq!(info.v_scale(), 1);
+        assert_eq!(info.ip_scale(), 2);
+    }
+
+    #[test]
+    fn specification_info_checked_rejects_out_of_range_scale() {
+        assert_eq!(
+            SpecificationInfoFields::from_parts_checked(Revision::Version1And1Dot1, Version::Version1Dot1, 3, 3)
+                .map(SpecificationInfoFields::into_bits),
+            Some(0x3321)
+        );
+        assert!(
+            SpecificationInfoFields::from_parts_checked(Revision::Version1And1Dot1, Version::Version1, 4, 0).is_none()
+        );
+        assert!(
+            SpecificationInfoFields::from_parts_checked(Revision::Version1And1Dot1, Version::Version1, 0, 4).is_none()
+        );
+    }
+
+    #[test]
+    fn capacity_checked_add_same_unit() {
+        assert_eq!(
+            CapacityModeValue::MilliAmpUnsigned(1000).checked_add(CapacityModeValue::MilliAmpUnsigned(500)),
+            Some(CapacityModeValue::MilliAmpUnsigned(1500))
+        );
+        assert_eq!(
+            CapacityModeValue::CentiWattUnsigned(1000).checked_add(CapacityModeValue::CentiWattUnsigned(500)),
+            Some(CapacityModeValue::CentiWattUnsigned(1500))
+        );
+    }
+
+    #[test]
+    fn capacity_checked_add_saturates() {
+        assert_eq!(
+            CapacityModeValue::MilliAmpUnsigned(65000).checked_add(CapacityModeValue::MilliAmpUnsigned(1000)),
+            Some(CapacityModeValue::MilliAmpUnsigned(u16::MAX))
+        );
+        assert_eq!(
+            CapacityModeValue::CentiWattUnsigned(100).checked_sub(CapacityModeValue::CentiWattUnsigned(200)),
+            Some(CapacityModeValue::CentiWattUnsigned(0))
+        );
+    }
+
+    #[test]
+    fn capacity_checked_arithmetic_rejects_mixed_units() {
+        let milliamps = CapacityModeValue::MilliAmpUnsigned(1000);
+        let centiwatts = CapacityModeValue::CentiWattUnsigned(1000);
+        assert_eq!(milliamps.checked_add(centiwatts), None);
+        assert_eq!(centiwatts.checked_add(milliamps), None);
+        assert_eq!(milliamps.checked_sub(centiwatts), None);
+    }
+
+    #[test]
+    fn decode_block_correct_length() {
+        let block = [4, b'L', b'I', b'O', b'N', 0xFF];
+        assert_eq!(BlockQuirk::LengthPrefixed.decode_block(&block), Ok(&b"LION"[..]));
+        assert_eq!(BlockQuirk::Raw.decode_block(&block), Ok(&block[..]));
+    }
+
+    #[test]
+    fn decode_block_over_long_length() {
+        let bad_size = Err(ErrorKind::BatteryStatus(ErrorCode::BadSize));
+        assert_eq!(
+            BlockQuirk::LengthPrefixed.decode_block(&[5, b'L', b'I', b'O', b'N']),
+            bad_size
+        );
+        assert_eq!(BlockQuirk::LengthPrefixed.decode_block(&[33; 40]), bad_size);
+    }
+
+    #[test]
+    fn decode_block_zero_length() {
+        assert_eq!(BlockQuirk::LengthPrefixed.decode_block(&[0, 0xFF]), Ok(&[][..]));
+        assert_eq!(
+            BlockQuirk::LengthPrefixed.decode_block(&[]),
+            Err(ErrorKind::BatteryStatus(ErrorCode::BadSize))
+        );
+    }
+
+    struct Wrapper {
+        gauge: crate::mock::VendorGauge,
+    }
+
+    crate::impl_smart_battery_for_wrapper_type!(Wrapper, gauge, ErrorKind);
+
+    #[test]
+    fn wrapper_forwards_lifetime_data() {
+        let mut wrapper = Wrapper {
+            gauge: crate::mock::VendorGauge::new(),
+        };
+        let expected = wrapper.gauge.lifetime;
+        assert!(expected.is_some());
+        assert_eq!(wrapper.lifetime_data(), Ok(expected));
+    }
+
+    #[test]
+    fn report_lists_values_and_failed_reads() {
+        extern crate std;
+        use std::string::String;
+
+        let mut battery = RegisterFileBattery::new();
+        battery.failing = Some(Command::Temperature);
+
+        let mut report = String::new();
+        battery.report(&mut report).unwrap();
+        let lines: std::vec::Vec<&str> = report.lines().collect();
+        assert_eq!(lines.len(), 10);
+        assert_eq!(lines[0], "voltage:            11400 mV");
+        assert_eq!(lines[3], "temperature:        unavailable (CommError)");
+        assert_eq!(lines[6], "remaining capacity: 3000 mAh");
+        assert_eq!(lines[8], "status:             0x0080");
+        assert_eq!(lines[9], "cycle count:        12");
+    }
+
+    #[test]
+    fn internal_impedance_defaults_to_none() {
+        assert_eq!(RegisterFileBattery::new().internal_impedance_milliohm(), Ok(None));
+    }
+
+    #[test]
+    fn wrapper_forwards_internal_impedance() {
+        let mut wrapper = Wrapper {
+            gauge: crate::mock::VendorGauge::new(),
+        };
+        assert_eq!(wrapper.internal_impedance_milliohm(), Ok(Some(85)));
+        wrapper.gauge.impedance = None;
+        assert_eq!(wrapper.internal_impedance_milliohm(), Ok(None));
+    }
+
+    #[test]
+    fn dump_standard_registers_is_deterministic() {
+        let mut battery = RegisterFileBattery::new();
+        battery.set(Command::Current, (-850i16) as u16);
+
+        let mut dump = [0u8; STANDARD_REGISTER_DUMP_MAX_LEN];
+        let len = battery.dump_standard_registers(&mut dump).unwrap();
+        assert_eq!(len, 28 * 2 + (1 + 4) + (1 + 9) + (1 + 4));
+
+        // Word registers 0x01 - 0x1C, little-endian, in command order.
+        for (i, word) in dump[..56].chunks(2).enumerate() {
+            assert_eq!(
+                u16::from_le_bytes([word[0], word[1]]),
+                battery.words[i + 1],
+                "command {:#04x}",
+                i + 1
+            );
+        }
+        assert_eq!(&dump[18..20], &(-850i16).to_le_bytes());
+        assert_eq!(&dump[56..len], b"\x04ACME\x09PACK-3S1P\x04LION");
+
+        let mut again = [0u8; STANDARD_REGISTER_DUMP_MAX_LEN];
+        assert_eq!(battery.dump_standard_registers(&mut again), Ok(len));
+        assert_eq!(dump, again);
+    }
+
+    #[test]
+    fn dump_standard_registers_rejects_short_buffer() {
+        let mut battery = RegisterFileBattery::new();
+        let mut dump = [0u8; 60];
+        assert_eq!(
+            battery.dump_standard_registers(&mut dump),
+            Err(ErrorKind::BatteryStatus(ErrorCode::BadSize))
+        );
+    }
+
+    #[test]
+    fn wrapper_forwards_manufacturer_access() {
+        let mut wrapper = Wrapper {
+            gauge: crate::mock::VendorGauge::new(),
+        };
+        assert_eq!(wrapper.manufacturer_access(0x1234), Ok(0x3412));
+        assert_eq!(
+            RegisterFileBattery::new().manufacturer_access(0x1234),
+            Err(ErrorKind::Unsupported)
+        );
+    }
+
+    #[test]
+    fn wrapper_forwards_measurement_interval() {
+        let mut wrapper = Wrapper {
+            gauge: crate::mock::VendorGauge::new(),
+        };
+        assert_eq!(wrapper.measurement_interval_ms(), Ok(Some(250)));
+        wrapper.gauge.interval = None;
+        assert_eq!(wrapper.measurement_interval_ms(), Ok(None));
+    }
+
+    #[test]
+    fn wrapper_forwards_manufacturer_data() {
+        let mut wrapper = Wrapper {
+            gauge: crate::mock::VendorGauge::new(),
+        };
+        let mut buf = [0u8; 4];
+        assert_eq!(wrapper.manufacturer_data(&mut buf), Ok(3));
+        assert_eq!(&buf[..3], &[1, 2, 3]);
+        assert_eq!(
+            wrapper.manufacturer_data(&mut buf[..2]),
+            Err(ErrorKind::BatteryStatus(ErrorCode::BadSize))
+        );
+    }
+
+    #[test]
+    fn wrapper_forwards_cell_count() {
+        let mut wrapper = Wrapper {
+            gauge: crate::mock::VendorGauge::new(),
+        };
+        assert_eq!(wrapper.cell_count(), Ok(Some(4)));
+        // Without an override the pack falls back to the 11.1 V Li-ion estimate.
+        assert_eq!(RegisterFileBattery::new().cell_count(), Ok(Some(3)));
+    }
+
+    fn set_capacity_mode(battery: &mut RegisterFileBattery, capacity_mode: bool) {
+        let mode = battery.battery_mode().unwrap().with_capacity_mode(capacity_mode);
+        battery.set_battery_mode(mode).unwrap();
+    }
+
+    #[test]
+    fn capacity_mode_selects_remaining_capacity_variant() {
+        // (initial CAPACITY_MODE, toggled CAPACITY_MODE)
+        for (from, to) in [(false, true), (true, false), (false, false), (true, true)] {
+            let mut battery = RegisterFileBattery::new();
+            set_capacity_mode(&mut battery, from);
+            set_capacity_mode(&mut battery, to);
+
+            assert_eq!(battery.get(Command::BatteryMode) & (1 << 15) != 0, to);
+            let expected = if to {
+                CapacityModeValue::CentiWattUnsigned(3000)
+            } else {
+                CapacityModeValue::MilliAmpUnsigned(3000)
+            };
+            assert_eq!(battery.remaining_capacity(), Ok(expected), "{from} -> {to}");
+        }
+    }
+
+    #[test]
+    fn capacity_mode_toggles_back_and_forth() {
+        let mut battery = RegisterFileBattery::new();
+        assert_eq!(
+            battery.remaining_capacity(),
+            Ok(CapacityModeValue::MilliAmpUnsigned(3000))
+        );
+        set_capacity_mode(&mut battery, true);
+        assert_eq!(
+            battery.remaining_capacity(),
+            Ok(CapacityModeValue::CentiWattUnsigned(3000))
+        );
+        set_capacity_mode(&mut battery, false);
+        assert_eq!(
+            battery.remaining_capacity(),
+            Ok(CapacityModeValue::MilliAmpUnsigned(3000))
+        );
+    }
+
+    #[test]
+    fn is_charging_follows_current_sign() {
+        let mut battery = RegisterFileBattery::new();
+        assert_eq!(battery.is_charging(), Ok(false));
+        battery.set(Command::Current, 1200);
+        assert_eq!(battery.is_charging(), Ok(true));
+        battery.set(Command::Current, (-850i16) as u16);
+        assert_eq!(battery.is_charging(), Ok(false));
+    }
+
+    #[test]
+    fn cached_statics_reads_design_capacity_once() {
+        let mut cached = CachedStatics::new(RegisterFileBattery::new());
+        assert_eq!(cached.design_capacity(), Ok(CapacityModeValue::MilliAmpUnsigned(4200)));
+        assert_eq!(cached.design_capacity(), Ok(CapacityModeValue::MilliAmpUnsigned(4200)));
+        assert_eq!(cached.inner().reads, 1);
+
+        cached.invalidate();
+        assert_eq!(cached.design_capacity(), Ok(CapacityModeValue::MilliAmpUnsigned(4200)));
+        assert_eq!(cached.inner().reads, 2);
+
+        // The design capacity's unit follows CAPACITY_MODE, so writing BatteryMode discards it.
+        cached
+            .set_battery_mode(BatteryModeFields::new().with_capacity_mode(true))
+            .unwrap();
+        assert_eq!(cached.design_capacity(), Ok(CapacityModeValue::CentiWattUnsigned(4200)));
+        assert_eq!(cached.design_capacity(), Ok(CapacityModeValue::CentiWattUnsigned(4200)));
+        assert_eq!(cached.inner().reads, 3);
+    }
+
+    #[test]
+    fn cached_statics_cell_count_uses_cached_values() {
+        let mut cached = CachedStatics::new(RegisterFileBattery::new());
+        assert_eq!(cached.cell_count(), Ok(Some(3)));
+        let reads = cached.inner().reads;
+        assert_eq!(cached.cell_count(), Ok(Some(3)));
+        assert_eq!(cached.inner().reads, reads);
+    }
+
+    #[test]
+    fn write_battery_mode_verified_detects_read_only_change() {
+        let mut battery = RegisterFileBattery::new();
+        // CONDITION_FLAG
+        battery.mode_write_fault = 1 << 7;
+        assert_eq!(
+            battery
+                .write_battery_mode_verified(BatteryModeFields::new().with_capacity_mode(true))
+                .map(BatteryModeFields::into_bits),
+            Err(ErrorKind::ReadOnlyModeChanged)
+        );
+    }
+
+    #[test]
+    fn write_battery_mode_verified_returns_mode_read_back() {
+        let mut battery = RegisterFileBattery::new();
+        // INTERNAL_CHARGE_CONTROLLER, which the gauge keeps regardless of the written value.
+        battery.set(Command::BatteryMode, 1 << 0);
+        let mode = battery
+            .write_battery_mode_verified(BatteryModeFields::new().with_capacity_mode(true))
+            .unwrap();
+        assert_eq!(mode.into_bits(), (1 << 15) | (1 << 0));
+        assert_eq!(battery.get(Command::BatteryMode), mode.into_bits());
+    }
+
+    #[test]
+    fn soc_filter_reports_first_reading() {
+        let mut filter = SocFilter::new(5, 3);
+        assert_eq!(filter.value(), None);
+        assert_eq!(filter.update(50), 50);
+        assert_eq!(filter.value(), Some(50));
+    }
+
+    #[test]
+    fn soc_filter_suppresses_jitter() {
+        let mut filter = SocFilter::new(5, 3);
+        filter.update(50);
+        // Readings within the hysteresis band never count.
+        for soc in [52, 48, 54, 46, 50] {
+            assert_eq!(filter.update(soc), 50);
+        }
+        // Readings outside the band, alternating sides, never persist in one direction.
+        for soc in [45, 55, 45, 55, 45, 55] {
+            assert_eq!(filter.update(soc), 50);
+        }
+    }
+
+    #[test]
+    fn soc_filter_commits_sustained_step() {
+        let mut filter = SocFilter::new(5, 3);
+        filter.update(50);
+        assert_eq!(filter.update(44), 50);
+        assert_eq!(filter.update(43), 50);
+        assert_eq!(filter.update(42), 42);
+        assert_eq!(filter.value(), Some(42));
+
+        // A reading back inside the band restarts the count.
+        assert_eq!(filter.update(50), 42);
+        assert_eq!(filter.update(50), 42);
+        assert_eq!(filter.update(44), 42);
+        assert_eq!(filter.update(50), 42);
+        assert_eq!(filter.update(50), 42);
+        assert_eq!(filter.update(50), 50);
+    }
+
+    #[test]
+    fn soc_filter_zero_samples_behaves_like_one() {
+        let mut filter = SocFilter::new(5, 0);
+        filter.update(50);
+        assert_eq!(filter.update(52), 50);
+        assert_eq!(filter.update(56), 56);
+        assert_eq!(filter.update(50), 50);
+    }
+
+    #[test]
+    fn soc_filter_reset_discards_value_and_pending() {
+        let mut filter = SocFilter::new(5, 2);
+        filter.update(50);
+        assert_eq!(filter.update(40), 50);
+        filter.reset();
+        assert_eq!(filter.value(), None);
+        assert_eq!(filter.update(70), 70);
+        // The pending reading of 40 was discarded, so one more isn't enough.
+        assert_eq!(filter.update(40), 70);
+        assert_eq!(filter.update(40), 40);
+    }
+
+    #[test]
+    fn coulomb_counter_integrates_known_charge() {
+        let mut counter = CoulombCounter::new();
+        counter.accumulate(1000, 3_600_000);
+        assert_eq!(counter.net_mah(), 1000);
+        assert_eq!(counter.net_uah(), 1_000_000);
+
+        // Many short samples add up exactly.
+        let mut counter = CoulombCounter::new();
+        for _ in 0..3600 {
+            counter.accumulate(500, 1000);
+        }
+        assert_eq!(counter.net_mah(), 500);
+    }
+
+    #[test]
+    fn coulomb_counter_sign_follows_current() {
+        let mut counter = CoulombCounter::new();
+        counter.accumulate(-2000, 1_800_000);
+        assert_eq!(counter.net_mah(), -1000);
+        counter.accumulate(1000, 3_600_000);
+        assert_eq!(counter.net_mah(), 0);
+        counter.accumulate(1000, 3_600_000);
+        assert_eq!(counter.net_mah(), 1000);
+    }
+
+    #[test]
+    fn coulomb_counter_rounds_toward_zero() {
+        let mut counter = CoulombCounter::new();
+        // -1.5 µAh
+        counter.accumulate(-3, 1800);
+        assert_eq!(counter.net_uah(), -1);
+        assert_eq!(counter.net_mah(), 0);
+
+        let mut counter = CoulombCounter::new();
+        counter.accumulate(-1500, 3600);
+        assert_eq!(counter.net_mah(), -1);
+        assert_eq!(counter.net_uah(), -1500);
+
+        let mut counter = CoulombCounter::new();
+        counter.accumulate(1500, 3600);
+        assert_eq!(counter.net_mah(), 1);
+    }
+
+    #[test]
+    fn coulomb_counter_reset_clears_total() {
+        let mut counter = CoulombCounter::new();
+        counter.accumulate(1000, 3_600_000);
+        counter.reset();
+        assert_eq!(counter.net_uah(), 0);
+        assert_eq!(counter, CoulombCounter::default());
+    }
+
+    #[test]
+    fn status_log_iterates_newest_first_after_wrapping() {
+        let mut log = StatusLog::<3>::new();
+        assert!(log.is_empty());
+        for now in 1..=5u64 {
+            log.record(now, BatteryStatusFields::from_bits(now as u16));
+        }
+        assert_eq!(log.len(), 3);
+        let entries: [(u64, u16); 3] = core::array::from_fn(|i| {
+            let (now, status) = log.iter().nth(i).unwrap();
+            (*now, status.into_bits())
+        });
+        assert_eq!(entries, [(5, 5), (4, 4), (3, 3)]);
+        assert_eq!(log.iter().count(), 3);
+    }
+
+    #[test]
+    fn status_log_len_caps_at_capacity() {
+        let mut log = StatusLog::<2>::new();
+        log.record(1, BatteryStatusFields::new());
+        assert_eq!(log.len(), 1);
+        assert_eq!(log.iter().next().map(|(now, _)| *now), Some(1));
+        for now in 2..10 {
+            log.record(now, BatteryStatusFields::new());
+            assert_eq!(log.len(), 2);
+        }
+    }
+
+    #[test]
+    fn status_log_clear_discards_entries() {
+        let mut log = StatusLog::<2>::default();
+        log.record(1, BatteryStatusFields::new());
+        log.record(2, BatteryStatusFields::new());
+        log.clear();
+        assert!(log.is_empty());
+        assert_eq!(log.iter().count(), 0);
+
+        log.record(3, BatteryStatusFields::new());
+        assert!(log.iter().map(|(now, _)| *now).eq([3]));
+    }
+
+    #[test]
+    fn status_log_zero_capacity_is_a_no_op() {
+        let mut log = StatusLog::<0>::new();
+        log.record(1, BatteryStatusFields::new());
+        assert!(log.is_empty());
+        assert_eq!(log.iter().count(), 0);
+    }
+
+    #[test]
+    fn poll_schedule_honors_intervals() {
+        let mut schedule = PollSchedule::<2>::new();
+        schedule.register(Command::Voltage, 1000).unwrap();
+        schedule.register(Command::BatteryStatus, 5000).unwrap();
+        assert_eq!(schedule.len(), 2);
+
+        assert!(schedule.due(100).eq([Command::Voltage, Command::BatteryStatus]));
+        assert_eq!(schedule.due(1099).count(), 0);
+        assert!(schedule.due(1100).eq([Command::Voltage]));
+        assert!(schedule.due(5100).eq([Command::Voltage, Command::BatteryStatus]));
+    }
+
+    #[test]
+    fn poll_schedule_keeps_unconsumed_commands_due() {
+        let mut schedule = PollSchedule::<2>::new();
+        schedule.register(Command::Voltage, 1000).unwrap();
+        schedule.register(Command::Current, 1000).unwrap();
+
+        assert_eq!(schedule.due(0).next(), Some(Command::Voltage));
+        // Current wasn't consumed, so it is still due while Voltage isn't.
+        assert!(schedule.due(1).eq([Command::Current]));
+        assert_eq!(schedule.due(2).count(), 0);
+    }
+
+    #[test]
+    fn poll_schedule_register_rejects_when_full() {
+        let mut schedule = PollSchedule::<1>::new();
+        assert_eq!(schedule.register(Command::Voltage, 1000), Ok(()));
+        assert_eq!(schedule.register(Command::Current, 1000), Err(Command::Current));
+        assert_eq!(schedule.len(), 1);
+
+        schedule.clear();
+        assert!(schedule.is_empty());
+        assert_eq!(schedule.register(Command::Current, 1000), Ok(()));
+        assert_eq!(
+            PollSchedule::<0>::new().register(Command::Voltage, 1),
+            Err(Command::Voltage)
+        );
+    }
+
+    #[test]
+    fn poll_schedule_saturates_at_end_of_time() {
+        let mut schedule = PollSchedule::<1>::default();
+        schedule.register(Command::Voltage, u32::MAX).unwrap();
+        assert!(schedule.due(u64::MAX - 1).eq([Command::Voltage]));
+        assert_eq!(schedule.due(u64::MAX - 1).count(), 0);
+        // The next poll time saturated at u64::MAX instead of wrapping around to the past.
+        assert!(schedule.due(u64::MAX).eq([Command::Voltage]));
+    }
+
+    #[test]
+    fn charge_state_tracker_first_reading_is_silent() {
+        for current in [1000, -1000, 0] {
+            assert_eq!(ChargeStateTracker::new(50).update(current), None);
+        }
+    }
+
+    #[test]
+    fn charge_state_tracker_dead_band_is_idle() {
+        let mut tracker = ChargeStateTracker::new(50);
+        tracker.update(1000);
+        assert_eq!(tracker.update(50), Some(ChargeTransition::WentIdle));
+        assert_eq!(tracker.update(-50), None);
+        assert_eq!(tracker.update(0), None);
+        assert_eq!(tracker.update(51), Some(ChargeTransition::StartedCharging));
+
+        let mut tracker = ChargeStateTracker::new(50);
+        tracker.update(-1000);
+        assert_eq!(tracker.update(-20), Some(ChargeTransition::WentIdle));
+    }
+
+    #[test]
+    fn charge_state_tracker_reports_edges_once() {
+        let mut tracker = ChargeStateTracker::new(50);
+        tracker.update(1000);
+        assert_eq!(tracker.update(1200), None);
+        assert_eq!(tracker.update(-800), Some(ChargeTransition::StartedDischarging));
+        assert_eq!(tracker.update(-900), None);
+        assert_eq!(tracker.update(-700), None);
+        assert_eq!(tracker.update(600), Some(ChargeTransition::StartedCharging));
+        assert_eq!(tracker.update(600), None);
+        // i16::MIN's magnitude doesn't fit in an i16 but is still a discharge.
+        assert_eq!(tracker.update(i16::MIN), Some(ChargeTransition::StartedDischarging));
+    }
+
+    fn set_discharging(battery: &mut RegisterFileBattery, current: MilliAmpsSigned) {
+        let status = BatteryStatusFields::new().with_initialized(true).with_discharging(true);
+        battery.set(Command::BatteryStatus, status.into_bits());
+        battery.set(Command::Current, current as u16);
+    }
+
+    #[test]
+    fn self_discharge_estimate_when_idle() {
+        let mut battery = RegisterFileBattery::new();
+        set_discharging(&mut battery, -3);
+        assert_eq!(battery.self_discharge_estimate(), Ok(Some(3)));
+        set_discharging(&mut battery, -(IDLE_CURRENT_THRESHOLD as MilliAmpsSigned));
+        assert_eq!(battery.self_discharge_estimate(), Ok(Some(IDLE_CURRENT_THRESHOLD)));
+    }
+
+    #[test]
+    fn self_discharge_estimate_under_load() {
+        let mut battery = RegisterFileBattery::new();
+        set_discharging(&mut battery, -(IDLE_CURRENT_THRESHOLD as MilliAmpsSigned) - 1);
+        assert_eq!(battery.self_discharge_estimate(), Ok(None));
+        set_discharging(&mut battery, -1500);
+        assert_eq!(battery.self_discharge_estimate(), Ok(None));
+    }
+
+    #[test]
+    fn self_discharge_estimate_while_charging() {
+        let mut battery = RegisterFileBattery::new();
+        set_discharging(&mut battery, 5);
+        assert_eq!(battery.self_discharge_estimate(), Ok(None));
+    }
+
+    #[test]
+    fn self_discharge_estimate_requires_discharging_status() {
+        let mut battery = RegisterFileBattery::new();
+        battery.set(Command::Current, (-3i16) as u16);
+        assert_eq!(battery.self_discharge_estimate(), Ok(None));
+    }
+
+    fn bix_serial(battery: &mut RegisterFileBattery, serial: &mut [u8]) -> Result<(), ErrorKind> {
+        let mut model = [0u8; 16];
+        let mut chem = [0u8; 8];
+        battery.bix_strings(&mut model, serial, &mut chem)?;
+        assert_eq!(&model[..10], b"PACK-3S1P\0");
+        assert_eq!(&chem[..5], b"LION\0");
+        Ok(())
+    }
+
+    #[test]
+    fn bix_strings_formats_serial_extremes() {
+        let mut battery = RegisterFileBattery::new();
+        let mut serial = [0xAAu8; 8];
+        battery.set(Command::SerialNumber, 0);
+        assert_eq!(bix_serial(&mut battery, &mut serial), Ok(()));
+        assert_eq!(&serial[..2], b"0\0");
+
+        battery.set(Command::SerialNumber, u16::MAX);
+        assert_eq!(bix_serial(&mut battery, &mut serial), Ok(()));
+        assert_eq!(&serial[..6], b"65535\0");
+    }
+
+    #[test]
+    fn bix_strings_rejects_short_serial_buffer() {
+        let mut battery = RegisterFileBattery::new();
+        battery.set(Command::SerialNumber, u16::MAX);
+        let mut serial = [0u8; 5];
+        assert_eq!(
+            bix_serial(&mut battery, &mut serial),
+            Err(ErrorKind::BatteryStatus(ErrorCode::BadSize))
+        );
+        battery.set(Command::SerialNumber, 9999);
+        assert_eq!(bix_serial(&mut battery, &mut serial), Ok(()));
+        assert_eq!(&serial, b"9999\0");
+    }
+
+    #[test]
+    fn require_version_accepts_equal_or_newer() {
+        // The mock reports version 1.1 with PEC (0x0031).
+        let mut battery = RegisterFileBattery::new();
+        assert_eq!(battery.require_version(Version::Version1), Ok(()));
+        assert_eq!(battery.require_version(Version::Version1Dot1Pec), Ok(()));
+    }
+
+    #[test]
+    fn require_version_rejects_older() {
+        let mut battery = RegisterFileBattery::new();
+        battery.set(Command::SpecificationInfo, 0x0011);
+        assert_eq!(
+            battery.require_version(Version::Version1Dot1),
+            Err(ErrorKind::UnsupportedVersion(Version::Version1))
+        );
+        assert_eq!(battery.require_version(Version::Version1), Ok(()));
+    }
+
+    #[test]
+    fn cycle_wear_percent_rounds_to_nearest() {
+        // The mock reports 12 cycles.
+        let mut battery = RegisterFileBattery::new();
+        assert_eq!(battery.cycle_wear_percent(500), Ok(2));
+        assert_eq!(battery.cycle_wear_percent(800), Ok(2));
+        assert_eq!(battery.cycle_wear_percent(1000), Ok(1));
+        assert_eq!(battery.cycle_wear_percent(12), Ok(100));
+    }
+
+    #[test]
+    fn cycle_wear_percent_zero_rated_cycles_saturates() {
+        let mut battery = RegisterFileBattery::new();
+        assert_eq!(battery.cycle_wear_percent(0), Ok(Percent::MAX));
+    }
+
+    #[test]
+    fn cycle_wear_percent_saturates_above_255() {
+        let mut battery = RegisterFileBattery::new();
+        battery.set(Command::CycleCount, 1300);
+        assert_eq!(battery.cycle_wear_percent(500), Ok(Percent::MAX));
+        battery.set(Command::CycleCount, 1275);
+        assert_eq!(battery.cycle_wear_percent(500), Ok(255));
+    }
+
+    #[test]
+    fn error_code_from_status_word_ignores_high_bits() {
+        let codes = [
+            ErrorCode::Ok,
+            ErrorCode::Busy,
+            ErrorCode::ReservedCmd,
+            ErrorCode::UnsupportedCmd,
+            ErrorCode::AccessDenied,
+            ErrorCode::UnderOverFlow,
+            ErrorCode::BadSize,
+        ];
+        for (nibble, code) in codes.into_iter().enumerate() {
+            assert_eq!(ErrorCode::from_status_word(nibble as u16), code);
+            assert_eq!(ErrorCode::from_status_word(0xFFF0 | nibble as u16), code);
+            assert_eq!(ErrorCode::from_status_word(0x00C0 | nibble as u16), code);
         }
+        for nibble in 7..=0xF {
+            assert_eq!(ErrorCode::from_status_word(0xFFF0 | nibble), ErrorCode::UnknownError);
+        }
+    }
+
+    #[test]
+    fn temperature_opt_treats_absolute_zero_as_unsupported() {
+        let mut battery = RegisterFileBattery::new();
+        battery.set(Command::Temperature, 0);
+        assert_eq!(battery.temperature_opt(), Ok(None));
+        battery.set(Command::Temperature, 2982);
+        assert_eq!(battery.temperature_opt(), Ok(Some(2982)));
+        battery.set(Command::Temperature, 1);
+        assert_eq!(battery.temperature_opt(), Ok(Some(1)));
+    }
+
+    #[test]
+    fn remaining_energy_mwh_in_ten_milliwatt_hours() {
+        let mut battery = RegisterFileBattery::new();
+        set_capacity_mode(&mut battery, true);
+        battery.set(Command::RemainingCapacity, 3330);
+        assert_eq!(battery.remaining_energy_mwh(), Ok(33_300));
+        battery.set(Command::RemainingCapacity, u16::MAX);
+        assert_eq!(battery.remaining_energy_mwh(), Ok(655_350));
+    }
+
+    #[test]
+    fn remaining_energy_mwh_from_milliamp_hours_and_design_voltage() {
+        // 3000mAh at 11.1V.
+        let mut battery = RegisterFileBattery::new();
+        assert_eq!(battery.remaining_energy_mwh(), Ok(33_300));
+
+        battery.set(Command::RemainingCapacity, u16::MAX);
+        battery.set(Command::DesignVoltage, u16::MAX);
+        assert_eq!(battery.remaining_energy_mwh(), Ok(4_294_836));
+    }
+
+    const LIFETIME_LAYOUT: LifetimeDataLayout = LifetimeDataLayout {
+        max_voltage: 0,
+        min_voltage: 2,
+        max_charge_current: 4,
+        max_discharge_current: 6,
+        max_temperature: 8,
+        min_temperature: 10,
     };
+
+    #[test]
+    fn lifetime_layout_parses_little_endian_words() {
+        let mut block = [0u8; 12];
+        block[0..2].copy_from_slice(&12_750u16.to_le_bytes());
+        block[2..4].copy_from_slice(&8_900u16.to_le_bytes());
+        block[4..6].copy_from_slice(&3_200i16.to_le_bytes());
+        block[6..8].copy_from_slice(&(-6_500i16).to_le_bytes());
+        block[8..10].copy_from_slice(&612i16.to_le_bytes());
+        block[10..12].copy_from_slice(&(-154i16).to_le_bytes());
+        assert_eq!(
+            LIFETIME_LAYOUT.parse(&block),
+            Some(LifetimeData {
+                max_voltage: 12_750,
+                min_voltage: 8_900,
+                max_charge_current: 3_200,
+                max_discharge_current: -6_500,
+                max_temperature: 612,
+                min_temperature: -154,
+            })
+        );
+    }
+
+    #[test]
+    fn lifetime_layout_rejects_out_of_range_offsets() {
+        let block = [0u8; 12];
+        assert_eq!(LIFETIME_LAYOUT.parse(&block[..11]), None);
+        for offset in [11, 12, usize::MAX - 1, usize::MAX] {
+            let layout = LifetimeDataLayout {
+                min_temperature: offset,
+                ..LIFETIME_LAYOUT
+            };
+            assert_eq!(layout.parse(&block), None);
+        }
+    }
+
+    #[test]
+    fn freshly_powered_when_not_initialized() {
+        let mut battery = RegisterFileBattery::new();
+        battery.set(Command::BatteryStatus, 0);
+        assert_eq!(battery.freshly_powered(), Ok(true));
+    }
+
+    #[test]
+    fn freshly_powered_without_cycle_history() {
+        let mut battery = RegisterFileBattery::new();
+        battery.set(Command::CycleCount, 0);
+        assert_eq!(battery.freshly_powered(), Ok(true));
+    }
+
+    #[test]
+    fn settled_gauge_is_not_freshly_powered() {
+        let mut battery = RegisterFileBattery::new();
+        assert_eq!(battery.freshly_powered(), Ok(false));
+    }
+
+    fn set_currents(battery: &mut RegisterFileBattery, current: MilliAmpsSigned, average: MilliAmpsSigned) {
+        battery.set(Command::Current, current as u16);
+        battery.set(Command::AverageCurrent, average as u16);
+    }
+
+    #[test]
+    fn current_transient_sign_convention() {
+        let mut battery = RegisterFileBattery::new();
+        // A discharge burst reads lower than the average.
+        set_currents(&mut battery, -3000, -1000);
+        assert_eq!(battery.current_transient(), Ok(-2000));
+        // Load dropping away reads higher than the average.
+        set_currents(&mut battery, -200, -1000);
+        assert_eq!(battery.current_transient(), Ok(800));
+        set_currents(&mut battery, 1500, 1500);
+        assert_eq!(battery.current_transient(), Ok(0));
+    }
+
+    #[test]
+    fn current_transient_saturates() {
+        let mut battery = RegisterFileBattery::new();
+        set_currents(&mut battery, i16::MAX, i16::MIN);
+        assert_eq!(battery.current_transient(), Ok(i16::MAX));
+        set_currents(&mut battery, i16::MIN, i16::MAX);
+        assert_eq!(battery.current_transient(), Ok(i16::MIN));
+    }
+
+    #[test]
+    fn string_length_limits_match_sbs() {
+        assert_eq!(SBS_MAX_STRING_LEN, 32);
+        assert_eq!(MAX_MANUFACTURER_NAME_LEN, 32);
+        assert_eq!(MAX_DEVICE_NAME_LEN, 32);
+        assert_eq!(MAX_DEVICE_CHEMISTRY_LEN, 32);
+    }
+
+    #[test]
+    fn battery_mode_writable_mask() {
+        assert_eq!(BatteryModeFields::WRITABLE_MASK, 0xE300);
+    }
+
+    #[test]
+    fn with_writable_from_merges_writable_and_read_only_bits() {
+        // Read only INTERNAL_CHARGE_CONTROLLER, PRIMARY_BATTERY_SUPPORT and CONDITION_FLAG set in base.
+        let base = BatteryModeFields::from_bits(0x0083 | 0x2000);
+        let requested = BatteryModeFields::from_bits(0xC100 | 0x0004);
+        assert_eq!(requested.with_writable_from(base).into_bits(), 0xC183);
+
+        let merged = BatteryModeFields::from_bits(0xFFFF).with_writable_from(BatteryModeFields::from_bits(0));
+        assert_eq!(merged.into_bits(), BatteryModeFields::WRITABLE_MASK);
+        let merged = BatteryModeFields::from_bits(0).with_writable_from(BatteryModeFields::from_bits(0xFFFF));
+        assert_eq!(merged.into_bits(), !BatteryModeFields::WRITABLE_MASK);
+    }
+
+    #[test]
+    fn try_to_centiwatt_hours_converts_milliamp_hours() {
+        assert_eq!(
+            CapacityModeValue::MilliAmpUnsigned(3000).try_to_centiwatt_hours(11_100),
+            Ok(CapacityModeValue::CentiWattUnsigned(3330))
+        );
+    }
+
+    #[test]
+    fn try_to_centiwatt_hours_rejects_overflow() {
+        // 20Ah at 36V is 72000 (10mWh).
+        assert_eq!(
+            CapacityModeValue::MilliAmpUnsigned(20_000).try_to_centiwatt_hours(36_000),
+            Err(CapacityOverflow)
+        );
+    }
+
+    #[test]
+    fn try_to_centiwatt_hours_passes_ten_milliwatt_hours_through() {
+        assert_eq!(
+            CapacityModeValue::CentiWattUnsigned(4000).try_to_centiwatt_hours(36_000),
+            Ok(CapacityModeValue::CentiWattUnsigned(4000))
+        );
+    }
+
+    #[test]
+    fn at_rate_full_analysis_writes_then_reads_in_order() {
+        let mut battery = RegisterFileBattery::new();
+        battery.set(Command::AtRateTimeToEmpty, 180);
+        let rate = CapacityModeSignedValue::MilliAmpSigned(-1000);
+
+        let analysis = battery.at_rate_full_analysis(rate).unwrap();
+        assert_eq!(
+            battery.accesses,
+            [
+                Access::Write(Command::AtRate),
+                Access::Read(Command::AtRateTimeToFull),
+                Access::Read(Command::AtRateTimeToEmpty),
+                Access::Read(Command::AtRateOk),
+            ]
+        );
+        assert_eq!(battery.get(Command::AtRate), (-1000i16) as u16);
+        assert_eq!(
+            analysis,
+            AtRateAnalysis {
+                rate,
+                time_to_full: TimeEstimate::NotApplicable,
+                time_to_empty: TimeEstimate::Minutes(180),
+                ok: true,
+            }
+        );
+    }
+
+    #[test]
+    fn power_scale_multiplies_voltage_and_current_scales() {
+        let info = SpecificationInfoFields::new().with_v_scale(1).with_ip_scale(2);
+        assert_eq!(info.power_scale(), 1000);
+        assert_eq!(SpecificationInfoFields::new().power_scale(), 1);
+        let info = SpecificationInfoFields::new().with_v_scale(3).with_ip_scale(3);
+        assert_eq!(info.power_scale(), 1_000_000);
+    }
+
+    #[test]
+    fn power_scale_clamps_undefined_scales() {
+        let info = SpecificationInfoFields::new().with_v_scale(15).with_ip_scale(1);
+        assert_eq!(info.power_scale(), 10_000);
+        let info = SpecificationInfoFields::new().with_v_scale(4).with_ip_scale(15);
+        assert_eq!(info.power_scale(), 1_000_000);
+    }
+
+    #[test]
+    fn ten_milliwatt_units_convert_and_saturate() {
+        assert_eq!(TenMilliWattHours(3330).as_mwh(), 33_300);
+        assert_eq!(TenMilliWattHours(u16::MAX).as_mwh(), 655_350);
+        assert_eq!(TenMilliWattHours::from_mwh(33_309), TenMilliWattHours(3330));
+        assert_eq!(TenMilliWattHours::from_mwh(9), TenMilliWattHours(0));
+        assert_eq!(TenMilliWattHours::from_mwh(655_359), TenMilliWattHours(u16::MAX));
+        assert_eq!(TenMilliWattHours::from_mwh(u32::MAX), TenMilliWattHours(u16::MAX));
+
+        assert_eq!(TenMilliWatts(1710).as_mw(), 17_100);
+        assert_eq!(TenMilliWatts::from_mw(17_105), TenMilliWatts(1710));
+        assert_eq!(TenMilliWatts::from_mw(u32::MAX), TenMilliWatts(u16::MAX));
+    }
+
+    #[test]
+    fn ten_milliwatt_accessors_follow_capacity_mode() {
+        let centiwatt = CapacityModeValue::CentiWattUnsigned(3330);
+        assert_eq!(centiwatt.ten_milliwatt_hours(), Some(TenMilliWattHours(3330)));
+        assert_eq!(centiwatt.ten_milliwatts(), Some(TenMilliWatts(3330)));
+        assert_eq!(CapacityModeValue::from(TenMilliWattHours(3330)), centiwatt);
+
+        let milliamp = CapacityModeValue::MilliAmpUnsigned(3000);
+        assert_eq!(milliamp.ten_milliwatt_hours(), None);
+        assert_eq!(milliamp.ten_milliwatts(), None);
+    }
+
+    #[test]
+    fn highest_priority_alarm_follows_documented_order() {
+        let mut status = BatteryStatusFields::new()
+            .with_remaining_time_alarm(true)
+            .with_remaining_capacity_alarm(true)
+            .with_fully_discharged(true)
+            .with_terminate_discharge_alarm(true)
+            .with_terminate_charge_alarm(true)
+            .with_over_temp_alarm(true)
+            .with_over_charged_alarm(true);
+        assert_eq!(status.highest_priority_alarm(), Some(Alarm::OverCharged));
+        status.set_over_charged_alarm(false);
+        assert_eq!(status.highest_priority_alarm(), Some(Alarm::OverTemp));
+        status.set_over_temp_alarm(false);
+        assert_eq!(status.highest_priority_alarm(), Some(Alarm::TerminateCharge));
+        status.set_terminate_charge_alarm(false);
+        assert_eq!(status.highest_priority_alarm(), Some(Alarm::TerminateDischarge));
+        status.set_terminate_discharge_alarm(false);
+        assert_eq!(status.highest_priority_alarm(), Some(Alarm::FullyDischarged));
+        status.set_fully_discharged(false);
+        assert_eq!(status.highest_priority_alarm(), Some(Alarm::RemainingCapacity));
+        status.set_remaining_capacity_alarm(false);
+        assert_eq!(status.highest_priority_alarm(), Some(Alarm::RemainingTime));
+        status.set_remaining_time_alarm(false);
+        assert_eq!(status.highest_priority_alarm(), None);
+    }
+
+    #[test]
+    fn fully_discharged_is_prioritized_but_not_an_alarm() {
+        let status = BatteryStatusFields::new().with_fully_discharged(true);
+        assert_eq!(status.highest_priority_alarm(), Some(Alarm::FullyDischarged));
+        assert!(!status.any_alarm());
+
+        let status = status.with_remaining_capacity_alarm(true);
+        assert_eq!(status.highest_priority_alarm(), Some(Alarm::FullyDischarged));
+        assert!(status.any_alarm());
+    }
+
+    #[test]
+    fn time_estimate_maps_sentinel() {
+        assert_eq!(TimeEstimate::from(65535), TimeEstimate::NotApplicable);
+        assert_eq!(TimeEstimate::from(0), TimeEstimate::Minutes(0));
+        assert_eq!(TimeEstimate::from(65534), TimeEstimate::Minutes(65534));
+    }
+
+    #[test]
+    fn time_estimate_defaults_read_the_battery() {
+        let mut battery = RegisterFileBattery::new();
+        assert_eq!(battery.run_time_to_empty_estimate(), Ok(TimeEstimate::NotApplicable));
+        battery.set(Command::RunTimeToEmpty, 95);
+        assert_eq!(battery.run_time_to_empty_estimate(), Ok(TimeEstimate::Minutes(95)));
+    }
+
+    #[test]
+    fn capacity_values_display_with_units() {
+        extern crate std;
+        use std::string::ToString;
+
+        assert_eq!(CapacityModeValue::MilliAmpUnsigned(1500).to_string(), "1500 mAh");
+        assert_eq!(CapacityModeValue::CentiWattUnsigned(300).to_string(), "300 (10mWh)");
+        assert_eq!(CapacityModeSignedValue::MilliAmpSigned(-1500).to_string(), "-1500 mA");
+        assert_eq!(
+            CapacityModeSignedValue::CentiWattSigned(-300).to_string(),
+            "-300 (10mW)"
+        );
+    }
+
+    #[test]
+    fn present_power_mw_follows_current_sign() {
+        // The mock reports 11.4V.
+        let mut battery = RegisterFileBattery::new();
+        battery.set(Command::Current, 1500);
+        assert_eq!(battery.present_power_mw(), Ok(17_100));
+        battery.set(Command::Current, (-1500i16) as u16);
+        assert_eq!(battery.present_power_mw(), Ok(-17_100));
+    }
+
+    #[test]
+    fn present_power_mw_truncates_toward_zero() {
+        let mut battery = RegisterFileBattery::new();
+        battery.set(Command::Voltage, 999);
+        battery.set(Command::Current, 1);
+        assert_eq!(battery.present_power_mw(), Ok(0));
+        battery.set(Command::Current, (-1i16) as u16);
+        assert_eq!(battery.present_power_mw(), Ok(0));
+        battery.set(Command::Voltage, 11_400);
+        assert_eq!(battery.present_power_mw(), Ok(-11));
+    }
+
+    #[test]
+    fn snapshot_diff_of_identical_snapshots_is_empty() {
+        let mut battery = RegisterFileBattery::new();
+        let before = battery.snapshot().unwrap();
+        let after = battery.snapshot().unwrap();
+        assert_eq!(before.diff(&after), SnapshotDiff::empty());
+    }
+
+    #[test]
+    fn snapshot_diff_flags_changed_fields() {
+        let mut battery = RegisterFileBattery::new();
+        let before = battery.snapshot().unwrap();
+        battery.set(Command::Voltage, 11_350);
+        battery.set(Command::CycleCount, 13);
+        let after = battery.snapshot().unwrap();
+        assert_eq!(before.diff(&after), SnapshotDiff::VOLTAGE | SnapshotDiff::CYCLE_COUNT);
+        assert_eq!(after.diff(&before), before.diff(&after));
+    }
+
+    #[cfg(feature = "defmt")]
+    #[test]
+    fn log_wrappers_build_unit_values() {
+        assert_eq!(log_minutes(45), UnitValue::Minutes(45));
+        assert_eq!(log_cycles(120), UnitValue::Cycles(120));
+        assert_eq!(log_percent(87), UnitValue::Percent(87));
+        assert_eq!(log_decikelvin(2982), UnitValue::DeciKelvin(2982));
+    }
+
+    #[cfg(feature = "defmt")]
+    #[test]
+    fn decikelvin_splits_into_kelvin_and_tenths() {
+        assert_eq!(split_decikelvin(2982), (298, 2));
+        assert_eq!(split_decikelvin(2980), (298, 0));
+        assert_eq!(split_decikelvin(9), (0, 9));
+        assert_eq!(split_decikelvin(u16::MAX), (6553, 5));
+    }
 }